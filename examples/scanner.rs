@@ -2,7 +2,13 @@ use std::path::PathBuf;
 
 use anyhow::{Context, Result};
 use clap::Parser as ClapParser;
-use wasi_guard::{abi::forbidden_imports, parse_import_funcs, policy::policy, wasi::proc_exit};
+use wasi_guard::{
+    abi::{
+        forbidden_component_imports, forbidden_imports, parse_component_imports,
+        unknown_imports_among,
+    },
+    policy::policy,
+};
 
 policy! {
     default = allow;
@@ -18,9 +24,18 @@ struct Args {
 fn main() -> Result<()> {
     let args = Args::parse();
     let wasm_binary = std::fs::read(args.wasm_path).context("Failed to read WASM file")?;
-    let funcs = parse_import_funcs(&wasm_binary).context("Error parsing WASM")?;
-    for func in forbidden_imports(&funcs, &MUST_BE_KILLED_WASIS) {
+    let imports = parse_component_imports(&wasm_binary).context("Error parsing WASM")?;
+    for interface in imports.interfaces.iter() {
+        println!("Interface: {}", interface);
+    }
+    for func in forbidden_imports(&imports.core_funcs, &MUST_BE_KILLED_WASIS) {
         println!("Fobidden: {}", func.name);
     }
+    for func in forbidden_component_imports(&imports.funcs, &MUST_BE_KILLED_WASIS) {
+        println!("Fobidden: {}::{}", func.interface, func.name);
+    }
+    for func in unknown_imports_among(&imports.core_funcs, CUSTOM_DESCRIPTORS) {
+        println!("Unknown: {}::{}", func.module, func.name);
+    }
     Ok(())
 }
@@ -0,0 +1,346 @@
+//! Composition of policies: a policy declared as `layer NAME;` can be extended by
+//! others with `extends PATH;`, e.g., a company-wide baseline by each service.
+//!
+//! A proc macro can not see the tokens of another invocation, so a layer is
+//! exported as a `macro_rules!` holding its tokens, and those of the layers it
+//! extends. `extends` invokes it with the rest of the policy, and it invokes
+//! `policy!` in turn with its layers prepended:
+//!
+//! ```ignore
+//! policy! { extends base; default = allow; }
+//! // expands to
+//! base! { @layers [] default = allow; }
+//! // which expands to
+//! policy! { @layers [@layer base { default = kill; ... }] default = allow; }
+//! ```
+//!
+//! The macro of a layer is `pub(crate)`, as a `#[macro_export]` one would be
+//! hoisted to the root of the crate, where layers of the same name in distinct
+//! modules would clash. Thus a layer is only extended within its crate.
+//!
+//! The tokens of a layer are expanded verbatim where it is extended, so its paths
+//! are resolved there rather than in its own module. A layer thus names its items
+//! by absolute paths, e.g., `crate::limits::MAX_FD` or `wasi_guard::wasi::Whence`,
+//! and one relative to its module, i.e., headed by `self` or `super`, is rejected.
+
+use proc_macro2::{TokenStream, TokenTree};
+use quote::{quote, ToTokens};
+use syn::{
+    parse::{Parse, ParseStream},
+    Ident, Result,
+};
+
+use crate::statement::{Layer, Policy};
+
+/// Names and tokens of the layers extended by a policy, from the base one.
+type Layers = Vec<(Ident, TokenStream)>;
+
+pub enum ComposedPolicy {
+    /// A policy still extending a layer, whose macro is invoked with the layers
+    /// collected so far and the rest of the policy.
+    Extends {
+        path: syn::Path,
+        layers: Layers,
+        rest: TokenStream,
+    },
+    /// A policy merged with all the layers it extends, and declared as a layer by
+    /// `layer NAME;` with the layers it holds if so.
+    Policy {
+        policy: Policy,
+        layer: Option<(Ident, Layers)>,
+    },
+}
+
+impl Parse for ComposedPolicy {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let layers = parse_layers(input)?;
+
+        let mut extends: Option<syn::Path> = None;
+        let mut name: Option<Ident> = None;
+        let mut rest = TokenStream::new();
+        let mut own = TokenStream::new();
+        for item in items(input.parse()?) {
+            if let Some(path) = header_of(&item, "extends") {
+                let path: syn::Path = syn::parse2(path)?;
+                if extends.is_some() {
+                    return Err(syn::Error::new_spanned(
+                        path,
+                        "only one `extends` per policy",
+                    ));
+                }
+                extends = Some(path);
+                continue;
+            } else if let Some(ident) = header_of(&item, "layer") {
+                let ident: Ident = syn::parse2(ident)?;
+                if name.is_some() {
+                    return Err(syn::Error::new(ident.span(), "the layer is already named"));
+                }
+                if layers.iter().any(|(layer, _)| *layer == ident) {
+                    return Err(syn::Error::new(
+                        ident.span(),
+                        format!("the layer `{ident}` is already extended"),
+                    ));
+                }
+                name = Some(ident);
+                rest.extend(item);
+                continue;
+            }
+            rest.extend(item.clone());
+            own.extend(item);
+        }
+        if let (Some(_), Some(span)) = (&name, relative_path(own.clone())) {
+            return Err(syn::Error::new(
+                span,
+                "a layer is expanded where it is extended; use an absolute path, e.g., `crate::...`",
+            ));
+        }
+        if let Some(path) = extends {
+            return Ok(Self::Extends { path, layers, rest });
+        }
+
+        let mut merged = Vec::with_capacity(layers.len() + 1);
+        for (ident, tokens) in &layers {
+            let mut layer: Layer = syn::parse2(tokens.clone())?;
+            layer.name = ident.to_string();
+            merged.push(layer);
+        }
+        let mut layer: Layer = syn::parse2(own.clone())?;
+        layer.name = match &name {
+            Some(name) => name.to_string(),
+            None => String::from(Layer::UNNAMED),
+        };
+        merged.push(layer);
+        let policy = Policy::from_layers(merged)?;
+
+        let layer = name.map(|name| {
+            let mut layers = layers;
+            layers.push((name.clone(), own));
+            (name, layers)
+        });
+        Ok(Self::Policy { policy, layer })
+    }
+}
+
+/// Parses `@layers [@layer NAME { ... } ...]` heading the input, if any, where a
+/// layer given twice is kept once.
+fn parse_layers(input: ParseStream) -> Result<Layers> {
+    let mut layers = Layers::new();
+    if !input.peek(syn::Token![@]) {
+        return Ok(layers);
+    }
+    input.parse::<syn::Token![@]>()?;
+    expect_ident(input, "layers")?;
+    let content;
+    syn::bracketed!(content in input);
+    while !content.is_empty() {
+        content.parse::<syn::Token![@]>()?;
+        expect_ident(&content, "layer")?;
+        let name: Ident = content.parse()?;
+        let tokens;
+        syn::braced!(tokens in content);
+        let tokens: TokenStream = tokens.parse()?;
+        if !layers.iter().any(|(layer, _)| *layer == name) {
+            layers.push((name, tokens));
+        }
+    }
+    Ok(layers)
+}
+
+fn expect_ident(input: ParseStream, expected: &str) -> Result<()> {
+    let ident: Ident = input.parse()?;
+    if ident != expected {
+        return Err(syn::Error::new(
+            ident.span(),
+            format!("expected `{expected}`"),
+        ));
+    }
+    Ok(())
+}
+
+/// Splits the tokens of a policy into its items, each ended by a semicolon.
+fn items(tokens: TokenStream) -> Vec<TokenStream> {
+    let mut items = Vec::new();
+    let mut item = TokenStream::new();
+    for token in tokens {
+        let end = matches!(&token, TokenTree::Punct(punct) if punct.as_char() == ';');
+        item.extend([token]);
+        if end {
+            items.push(core::mem::take(&mut item));
+        }
+    }
+    if !item.is_empty() {
+        items.push(item);
+    }
+    items
+}
+
+/// The span of the first path among `tokens` relative to the current module, i.e.,
+/// headed by `self::` or `super::`, if any.
+fn relative_path(tokens: TokenStream) -> Option<proc_macro2::Span> {
+    let mut tokens = tokens.into_iter().peekable();
+    while let Some(token) = tokens.next() {
+        match token {
+            TokenTree::Group(group) => {
+                if let Some(span) = relative_path(group.stream()) {
+                    return Some(span);
+                }
+            }
+            TokenTree::Ident(ident) if ident == "self" || ident == "super" => {
+                if matches!(tokens.peek(), Some(TokenTree::Punct(punct)) if punct.as_char() == ':')
+                {
+                    return Some(ident.span());
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// The tokens after `keyword` if the item is headed by it, e.g., the path of
+/// `extends path::to::layer;`.
+fn header_of(item: &TokenStream, keyword: &str) -> Option<TokenStream> {
+    let mut tokens = item.clone().into_iter();
+    match tokens.next() {
+        Some(TokenTree::Ident(ident)) if ident == keyword => {}
+        _ => return None,
+    }
+    Some(
+        tokens
+            .filter(|token| !matches!(token, TokenTree::Punct(punct) if punct.as_char() == ';'))
+            .collect(),
+    )
+}
+
+fn layers_to_tokens(layers: &Layers) -> TokenStream {
+    let (names, tokens): (Vec<_>, Vec<_>) = layers.iter().cloned().unzip();
+    quote! { #(@layer #names { #tokens })* }
+}
+
+impl ToTokens for ComposedPolicy {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        match self {
+            Self::Extends { path, layers, rest } => {
+                let layers = layers_to_tokens(layers);
+                quote! { #path! { @layers [#layers] #rest } }.to_tokens(tokens)
+            }
+            Self::Policy { policy, layer } => {
+                policy.to_tokens(tokens);
+                let Some((name, layers)) = layer else {
+                    return;
+                };
+                let layers = layers_to_tokens(layers);
+                quote! {
+                    #[allow(unused_macros)]
+                    macro_rules! #name {
+                        (@layers [$($layers:tt)*] $($rest:tt)*) => {
+                            wasi_guard::policy::policy! { @layers [$($layers)* #layers] $($rest)* }
+                        };
+                    }
+                    #[allow(unused_imports)]
+                    pub(crate) use #name;
+                }
+                .to_tokens(tokens)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn compose(src: &str) -> Result<ComposedPolicy> {
+        syn::parse_str(src)
+    }
+
+    #[test]
+    fn forward_to_extended_layer() {
+        let Ok(ComposedPolicy::Extends { path, layers, rest }) =
+            compose("@layers [@layer root { default = kill; }] layer svc; extends base::layer; allow fd_write;")
+        else {
+            panic!("expected to extend a layer");
+        };
+        assert_eq!(path.to_token_stream().to_string(), "base :: layer");
+        assert_eq!(layers.len(), 1);
+        assert_eq!(rest.to_string(), "layer svc ; allow fd_write ;");
+    }
+
+    #[test]
+    fn merge_layers() {
+        let Ok(ComposedPolicy::Policy { policy, layer }) = compose(
+            "@layers [@layer base { default = kill; allow fd_write; log fd_read; } \
+             @layer base { default = log; }] \
+             layer svc; default = allow; override kill fd_read; allow fd_read;",
+        ) else {
+            panic!("expected a policy");
+        };
+        assert!(policy.default_action.is_allow());
+        assert_eq!(policy.default_layer, "svc");
+        let layers_of = |wasi: &str| -> Vec<String> {
+            policy
+                .statements
+                .iter()
+                .find(|(ident, _)| *ident == wasi)
+                .unwrap()
+                .1
+                .iter()
+                .map(|stmt| stmt.layer.clone())
+                .collect()
+        };
+        assert_eq!(layers_of("fd_write"), ["base"]);
+        assert_eq!(layers_of("fd_read"), ["svc", "svc"]);
+
+        let (name, layers) = layer.unwrap();
+        assert_eq!(name, "svc");
+        let names: Vec<String> = layers.iter().map(|(name, _)| name.to_string()).collect();
+        assert_eq!(names, ["base", "svc"]);
+        assert!(!layers[1].1.to_string().contains("layer"));
+    }
+
+    #[test]
+    fn composition_errors() {
+        let error = |src: &str| compose(src).err().map(|e| e.to_string());
+        assert_eq!(
+            error("allow fd_write;").as_deref(),
+            Some("expected `default = <action>;` in the policy or a layer it extends")
+        );
+        assert_eq!(
+            error("default = allow; override kill fd_write;").as_deref(),
+            Some("nothing to override in a policy extending no layers")
+        );
+        assert_eq!(
+            error("@layers [@layer base { default = kill; }] layer base; default = allow;")
+                .as_deref(),
+            Some("the layer `base` is already extended")
+        );
+        assert_eq!(
+            error("layer a; layer b; default = allow;").as_deref(),
+            Some("the layer is already named")
+        );
+        assert_eq!(
+            error("extends a; extends b; default = allow;").as_deref(),
+            Some("only one `extends` per policy")
+        );
+        assert!(error("@layers [@layer base { default = kill; }] allow fd_write;").is_none());
+
+        let relative = Some(
+            "a layer is expanded where it is extended; use an absolute path, e.g., `crate::...`",
+        );
+        assert_eq!(
+            error("layer a; default = allow; kill fd_write where |fd: u32, _: u32, _: u32, _: u32| fd > super::MAX;")
+                .as_deref(),
+            relative
+        );
+        assert_eq!(
+            error("layer a; use descriptors self::host; default = allow;").as_deref(),
+            relative
+        );
+        // but a policy which is not a layer resolves them itself
+        assert!(error("extends super::a; kill fd_write where fd > self::MAX;").is_none());
+        assert!(error(
+            "layer a; default = allow; kill fd_write where |fd: u32, _: u32, _: u32, _: u32| fd > crate::MAX;"
+        )
+        .is_none());
+    }
+}
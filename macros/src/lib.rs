@@ -7,6 +7,7 @@ use syn::{
 
 extern crate alloc;
 
+mod compose;
 mod statement;
 
 struct AllTuples {
@@ -128,15 +129,206 @@ pub fn repeat_tuple(input: TokenStream) -> TokenStream {
 ///    default kill;
 ///    allow some_path::to::wasi::abi1 where bound1, bound2, ...;
 ///    ret_errno(12+1) another_path::to::wasi::abi2 where bound3;
+///    ret_errno(PERM) path_open where oflags.contains(Oflags::CREAT);
 /// };
 /// ```
+///
+/// A bound on a known ABI can also be an expression over the names of its args,
+/// typed as declared by `<abi>_params_t`. After the call, these are followed by
+/// `errno` and the values behind the out-pointers, named after them without `_ptr`,
+/// or with `_out` instead if taken, e.g., `size` of `fd_write` and `fd_out` of
+/// `path_open`:
+///
+/// ```no_run,ignore
+/// policy! {
+///    default = allow;
+///    ret_errno(ACCES) fd_write where fd > 2 && iovs_len < 16;
+///    kill path_open where oflags.contains(Oflags::CREAT);
+///    after log fd_read where errno == 0 && size > 1 << 20;
+/// };
+/// ```
+///
+/// The closures of the bounds are checked against the descriptor of a known ABI:
+/// each one takes as many params as the ABI, the primitive types of the params are
+/// as wide as the args, e.g., an `i64` or `u64` for `offset` of `fd_seek`, and all
+/// the closures of a statement agree on the types of the params.
+///
+/// Statements of an ABI may type the params differently. Their guard then takes the
+/// raw params of the ABI, e.g., `wasi::fd_seek_params_raw_t`, and converts them into
+/// those of each statement by [`FromRawArg`][from_raw], keeping their bits:
+///
+/// ```no_run,ignore
+/// policy! {
+///    default = allow;
+///    kill fd_seek where |fd: u32, offset: i64, _: Whence, _: u32| fd > 2 && offset < 0;
+///    log fd_seek where |_: i32, offset: u64, _: u8, _: i32| offset > 1 << 32;
+/// };
+/// ```
+///
+/// An errno can be given by its witx name, e.g., `PERM`, `ERRNO_PERM` or `Errno::PERM`.
+/// Such a name shadows a constant of the same name in scope, which is still reached
+/// by its path, e.g., `ret_errno(self::PERM)`.
+///
+/// A statement without bounds can select a group of ABIs instead of a single one,
+/// either by categories, e.g., `@fs`, `@sock` (see [`wasi::Category`]), or by a
+/// prefix like `path_*`. It applies to the ABIs of the group without statements
+/// of their own:
+///
+/// ```no_run,ignore
+/// policy! {
+///    default = allow;
+///    ret_errno(PERM) @fs;
+///    allow fd_close; // not denied by `@fs`
+///    kill sock_*;
+/// };
+/// ```
+///
+/// By default, every statement of an ABI is evaluated and the actions of all the
+/// satisfied ones are taken. With `mode = first_match;`, the statements are
+/// evaluated in order like the rules of a firewall, and only the first satisfied
+/// one is taken; if none is, fall back to the default action, e.g., by
+/// [`WasiGuard::check_or`][check_or] with `DEFUALT_ACTION`:
+///
+/// ```no_run,ignore
+/// policy! {
+///    mode = first_match;
+///    default = allow;
+///    allow fd_write where |fd: u32, _: u32, _: u32, _: u32| fd <= 2;
+///    ret_errno(ACCES) fd_write;
+/// };
+/// ```
+///
+/// `rewrite(REWRITE)` makes the call with its params rewritten by a
+/// [`Rewrite`][rewrite], e.g., clamped, rather than failing it. The host applies
+/// the rewrites among the actions to the raw params, and to the memory of the
+/// guest if it passes one:
+///
+/// ```no_run,ignore
+/// const CLAMP: Rewrite = Rewrite::new("clamp", |params, _| {
+///     params[1] = params[1].min(256);
+///     true
+/// });
+/// policy! {
+///    default = allow;
+///    rewrite(CLAMP) random_get where buf_len > 256;
+/// };
+/// ```
+///
+/// `emulate(EMULATE)` answers the call by an [`Emulate`][emulate] in place of the
+/// host, which writes the results into the memory of the guest and returns the
+/// errno, e.g., a fixed `clock_time_get` or a seeded `random_get`, and the host does
+/// not make the call:
+///
+/// ```no_run,ignore
+/// static RANDOM: SeededRandom = SeededRandom::new(42);
+/// policy! {
+///    default = allow;
+///    emulate(Emulate::new("random", &RANDOM)) random_get;
+/// };
+/// ```
+///
+/// A statement headed by `after` is checked after the call, on the arguments
+/// followed by the returned errno and the values written behind the out-pointers,
+/// e.g., `nwritten` of `fd_write`, all of them raw: the errno is of 2 bytes, and
+/// each out value of 8, whatever it is in the memory of the guest. Its guard is generated as
+/// `WASI_GUARD_AFTER_<ABI>` beside the one checked before the call:
+///
+/// ```no_run,ignore
+/// policy! {
+///    default = allow;
+///    after log fd_write where |fd: u32, _: u32, _: u32, _: u32, errno: u16, nwritten: u64| {
+///        fd > 2 && errno == 0 && nwritten > 4096
+///    };
+///    after kill @sock;
+/// };
+/// ```
+///
+/// ABIs other than WASI, e.g., functions a host exports from `env`, can be guarded
+/// once their module of descriptors, declared by [`declare_host_abis!`][host], is
+/// registered with `use descriptors`. Besides the guards, the generated
+/// `CUSTOM_DESCRIPTORS` lists the descriptors of these modules for scanners:
+///
+/// ```no_run,ignore
+/// policy! {
+///    use descriptors my_host::abi;
+///    default = allow;
+///    kill host_http_fetch where |_url_ptr: u32, url_len: u32, _resp_ptr: u32| url_len > 1024;
+///    ret_errno(PERM) kv_put;
+/// };
+/// ```
+///
+/// Besides the statics, `policy_set()` builds the guards anew as a
+/// [`PolicySet`][set] on the raw params of their ABIs, which a
+/// [`ReloadablePolicy`][reload] can swap for that of another policy while running:
+///
+/// ```no_run,ignore
+/// let tenant = ReloadablePolicy::new(baseline::policy_set())?;
+/// let decision = tenant.decide(|set| set.check::<fd_write_params_raw_t>("fd_write", false, params));
+/// tenant.reload(strict::policy_set())?; // validated before the swap
+/// ```
+///
+/// Policies compose by layers. `layer NAME;` declares a policy as a layer, exported
+/// to the crate as the macro `NAME`, which others extend by `extends path::to::NAME;`.
+/// A layer is `pub(crate)`, so it can not be extended by another crate, and a policy
+/// extends at most one layer, which may extend another in turn.
+/// The default action and the mode are those of the last layer specifying them, and
+/// the statements of each ABI are appended to the inherited ones, unless headed by
+/// `override`, which replaces the inherited ones of the same phase. Paths in a layer
+/// are resolved where it is extended, so they must be absolute, e.g., `crate::MAX_FD`
+/// rather than `super::MAX_FD`, which is rejected. `GUARD_LAYERS` tells the layer of each
+/// statement of the guards, and `DEFAULT_ACTION_LAYER` that of the default action:
+///
+/// ```no_run,ignore
+/// mod baseline {
+///     policy! {
+///        layer baseline;
+///        default = kill;
+///        allow fd_write where fd <= 2;
+///        ret_errno(PERM) @sock;
+///     };
+/// }
+/// policy! {
+///    extends baseline::baseline;
+///    default = allow; // instead of `kill`
+///    log fd_write where fd > 2; // beside `allow fd_write` of the baseline
+///    override allow sock_send; // instead of `ret_errno(PERM)`
+/// };
+/// ```
+///
+/// A closure whose first param is a reference, e.g., `&GuestCtx`, also sees a context
+/// of the host, such as the tenant of the guest or the [`FdTable`][fds] telling what
+/// its fds refer to, which is not an argument of the ABI.
+/// Expressions of its statement see it as `ctx`. The guard of the ABI then takes
+/// the context, checked by [`check_in`][check_in], and the other statements of the
+/// ABI ignore it:
+///
+/// ```no_run,ignore
+/// policy! {
+///    default = allow;
+///    kill fd_write where |ctx: &GuestCtx, fd: u32, _: u32, _: u32, _: u32| {
+///        !ctx.allowed_fds.contains(&fd)
+///    };
+///    log fd_write where fd > 2;
+/// };
+/// let actions = WASI_GUARD_FD_WRITE.as_ref().unwrap().check_in(&ctx, params);
+/// ```
+///
+/// [check_or]: wasi_guard::policy::WasiGuard
+/// [check_in]: wasi_guard::policy::GuardCheck
+/// [emulate]: wasi_guard::policy::action::Emulate
+/// [fds]: wasi_guard::policy::fd::FdTable
+/// [from_raw]: wasi_guard::wasi_descriptor::FromRawArg
+/// [host]: wasi_guard::wasi::declare_host_abis
+/// [reload]: wasi_guard::policy::ReloadablePolicy
+/// [rewrite]: wasi_guard::policy::action::Rewrite
+/// [set]: wasi_guard::policy::PolicySet
 #[proc_macro]
 pub fn policy(input: TokenStream) -> TokenStream {
     if input.is_empty() {
         return TokenStream::new();
     }
 
-    let _policy = parse_macro_input!(input as statement::Policy);
+    let _policy = parse_macro_input!(input as compose::ComposedPolicy);
     quote! {
         #_policy
     }
@@ -4,13 +4,19 @@ use quote::{format_ident, quote, ToTokens};
 use syn::{
     parse::{Parse, ParseStream},
     punctuated::Punctuated,
+    spanned::Spanned,
     Ident, Result,
 };
+use wasi::wasi_descriptor::ErasedDescriptor;
 
 #[derive(Clone)]
 pub enum Action {
     Allow,
     Log,
+    // With a `Rewrite` of the params.
+    Rewrite(syn::Expr),
+    // With an `Emulate` answering the call.
+    Emulate(syn::Expr),
     // With a user-defined errno.
     ReturnErrno(syn::Expr),
     Kill,
@@ -31,12 +37,25 @@ impl Parse for Action {
             "allow" => Ok(Action::Allow),
             "log" => Ok(Action::Log),
             "kill" => Ok(Action::Kill),
+            "rewrite" => {
+                let rewrite;
+                syn::parenthesized!(rewrite in input);
+                Ok(Action::Rewrite(rewrite.parse()?))
+            }
+            "emulate" => {
+                let emulate;
+                syn::parenthesized!(emulate in input);
+                Ok(Action::Emulate(emulate.parse()?))
+            }
             "ret_err" | "ret_errno" | "return_errno" => {
                 let errno;
                 syn::parenthesized!(errno in input);
                 Ok(Action::ReturnErrno(errno.parse()?))
             }
-            _ => Err(input.error("expected `allow`, `log`, `kill`, or `return_errno`")),
+            _ => Err(syn::Error::new(
+                action.span(),
+                "expected `allow`, `log`, `rewrite`, `emulate`, `kill`, or `return_errno`",
+            )),
         }
     }
 }
@@ -45,15 +64,47 @@ impl ToTokens for Action {
         match self {
             Action::Allow => quote! { wasi_guard::policy::action::Action::Allow },
             Action::Log => quote! { wasi_guard::policy::action::Action::Log },
-            Action::ReturnErrno(errno) => {
-                quote! { wasi_guard::policy::action::Action::ReturnErrno((#errno) as wasi_guard::policy::action::WasiErrno) }
+            Action::Rewrite(rewrite) => {
+                quote! { wasi_guard::policy::action::Action::Rewrite(#rewrite) }
+            }
+            Action::Emulate(emulate) => {
+                quote! { wasi_guard::policy::action::Action::Emulate(#emulate) }
             }
+            Action::ReturnErrno(errno) => match errno_by_name(errno) {
+                Some(errno) => quote! { wasi_guard::policy::action::Action::ReturnErrno(#errno) },
+                None => {
+                    quote! { wasi_guard::policy::action::Action::ReturnErrno((#errno) as wasi_guard::policy::action::WasiErrno) }
+                }
+            },
             Action::Kill => quote! { wasi_guard::policy::action::Action::Kill },
         }
         .to_tokens(tokens)
     }
 }
 
+/// Resolves an errno named exactly like `PERM`, `ERRNO_PERM` or `Errno::PERM` to
+/// its value, where any other expression, e.g., `self::PERM`, is left to the scope.
+fn errno_by_name(errno: &syn::Expr) -> Option<u16> {
+    let syn::Expr::Path(syn::ExprPath {
+        qself: None, path, ..
+    }) = errno
+    else {
+        return None;
+    };
+    let name = path.segments.last()?.ident.to_string();
+    let name = match path.segments.len() {
+        1 => name.strip_prefix("ERRNO_").unwrap_or(&name),
+        _ if path.segments[path.segments.len() - 2].ident == "Errno" => name.as_str(),
+        _ => return None,
+    };
+    // e.g., `Errno::_2BIG`
+    let name = name.strip_prefix('_').unwrap_or(name);
+    wasi::p1::Errno::NAMES
+        .iter()
+        .find(|(variant, _)| *variant == name)
+        .map(|(_, errno)| errno.raw())
+}
+
 /// Bound expression for a WASI guard statement,
 /// used to check the arguments of a WASI function.
 pub enum Bound {
@@ -73,6 +124,10 @@ pub enum Bound {
     /// Access of a named struct field (`obj.k`) or unnamed tuple struct
     /// field (`obj.0`).
     Field(syn::ExprField),
+    /// An expression over the names of the args of the ABI: `fd > 2 && iovs_len < 16`.
+    ///
+    /// It is turned into a closure taking the args by [`WasiStatement::resolve_exprs`].
+    Expr(syn::Expr),
 }
 impl Parse for Bound {
     fn parse(input: ParseStream) -> Result<Self> {
@@ -84,7 +139,16 @@ impl Parse for Bound {
             syn::Expr::Call(call) => Ok(Bound::Call(call)),
             syn::Expr::Index(index) => Ok(Bound::Index(index)),
             syn::Expr::Field(field) => Ok(Bound::Field(field)),
-            _ => Err(input.error("expected a closure, call, path, if, index, or field expression")),
+            expr @ (syn::Expr::Binary(_)
+            | syn::Expr::Unary(_)
+            | syn::Expr::Paren(_)
+            | syn::Expr::MethodCall(_)
+            | syn::Expr::Match(_)
+            | syn::Expr::Cast(_)
+            | syn::Expr::Lit(_)) => Ok(Bound::Expr(expr)),
+            _ => Err(input.error(
+                "expected a closure, call, path, if, index, field, or an expression over the args",
+            )),
         }
     }
 }
@@ -97,34 +161,56 @@ impl ToTokens for Bound {
             Bound::Call(call) => call.to_tokens(tokens),
             Bound::Index(index) => index.to_tokens(tokens),
             Bound::Field(field) => field.to_tokens(tokens),
+            Bound::Expr(expr) => expr.to_tokens(tokens),
         }
     }
 }
 
 pub struct WasiStatement {
+    /// Whether the statement is checked after the call, on the arguments, the
+    /// returned errno and the values behind the out-pointers.
+    pub after: bool,
+    /// Whether the statement replaces those of the ABI in the same phase inherited
+    /// from the layers extended by the policy.
+    pub overrides: bool,
+    /// Name of the layer of the policy the statement comes from.
+    pub layer: String,
     pub action: Action,
     pub wasi: syn::Ident,
     pub bounds: Vec<Bound>,
     pub arg_types: Vec<syn::PatType>,
+    /// The context of the host seen by the closures taking it before the params,
+    /// e.g., `GuestCtx` of `|ctx: &GuestCtx, fd: u32| ..`.
+    pub ctx: Option<syn::Type>,
 }
 impl WasiStatement {
     pub fn new(wasi: syn::Ident, action: Action) -> Self {
         Self {
+            after: false,
+            overrides: false,
+            layer: String::new(),
             action,
             wasi,
             bounds: Vec::new(),
             arg_types: Vec::new(),
+            ctx: None,
         }
     }
 
+    /// Whether the statement kills its ABI before the call whatever the args are.
     pub fn must_be_killed(&self) -> bool {
-        self.action.is_kill() && self.bounds.is_empty()
+        !self.after && self.action.is_kill() && self.bounds.is_empty()
     }
 }
 
 impl Parse for WasiStatement {
     fn parse(input: ParseStream) -> Result<Self> {
         let action = input.parse()?;
+        Self::parse_after_action(action, input)
+    }
+}
+impl WasiStatement {
+    fn parse_after_action(action: Action, input: ParseStream) -> Result<Self> {
         let wasi = input.parse()?;
         let bounds: Vec<Bound> = if input.peek(syn::Token![where]) {
             input.parse::<syn::Token![where]>().unwrap();
@@ -141,13 +227,35 @@ impl Parse for WasiStatement {
 
         // If there is at least one bound that is a closure with typed arguments,
         // we can parse the type of the arguments.
+        let closures = || {
+            bounds.iter().filter_map(|b| match b {
+                Bound::Closure(c) => Some(c),
+                _ => None,
+            })
+        };
+        let mut ctx: Option<&syn::Type> = None;
+        for ty in closures().filter_map(context_of) {
+            match ctx {
+                None => ctx = Some(ty),
+                Some(ctx)
+                    if ctx.to_token_stream().to_string() != ty.to_token_stream().to_string() =>
+                {
+                    return Err(syn::Error::new(
+                        ty.span(),
+                        format!(
+                            "the context is `{}` in another closure of the statement; `{}` differs",
+                            ctx.to_token_stream(),
+                            ty.to_token_stream()
+                        ),
+                    ));
+                }
+                Some(_) => {}
+            }
+        }
         let mut arg_types: Vec<syn::PatType> = Vec::new();
-        for closure in bounds.iter().filter_map(|b| match b {
-            Bound::Closure(c) => Some(c),
-            _ => None,
-        }) {
+        for closure in closures() {
             arg_types.clear();
-            for arg in closure.inputs.iter() {
+            for arg in params_of(closure) {
                 match arg {
                     syn::Pat::Type(pat) => arg_types.push(pat.clone()),
                     _ => {
@@ -160,117 +268,1068 @@ impl Parse for WasiStatement {
                 break;
             }
         }
-        if arg_types.is_empty() && !bounds.is_empty() {
+        // Expressions are typed by the descriptor of the ABI.
+        let has_exprs = bounds.iter().any(|bound| matches!(bound, Bound::Expr(_)));
+        if arg_types.is_empty() && ctx.is_none() && !bounds.is_empty() && !has_exprs {
             return Err(input.error("expected a closure with all arguments typed"));
         }
 
         let mut stat = WasiStatement::new(wasi, action);
+        stat.ctx = ctx.cloned();
         stat.bounds = bounds;
         stat.arg_types = arg_types;
         Ok(stat)
     }
 }
 
-impl ToTokens for WasiStatement {
-    fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
+/// The context taken by a closure before the params, i.e., the type referred to
+/// by its first param, e.g., `GuestCtx` of `|ctx: &GuestCtx, fd: u32| ..`, as the
+/// args of an ABI are never references.
+fn context_of(closure: &syn::ExprClosure) -> Option<&syn::Type> {
+    match closure.inputs.first() {
+        Some(syn::Pat::Type(pat)) => match &*pat.ty {
+            syn::Type::Reference(reference) => Some(&reference.elem),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// The params of a closure bound, after the context if it takes one.
+fn params_of(closure: &syn::ExprClosure) -> impl Iterator<Item = &syn::Pat> {
+    let skip = usize::from(context_of(closure).is_some());
+    closure.inputs.iter().skip(skip)
+}
+
+/// Selector of a group of WASI ABIs, to which a statement applies unless an ABI has
+/// its own statements.
+pub enum Selector {
+    /// ABIs in the categories of a group: `@fs`, `@sock`.
+    Group(syn::Ident, &'static [wasi::Category]),
+    /// ABIs whose names start with a prefix: `path_*`.
+    Prefix(syn::Ident),
+}
+impl Selector {
+    fn peek(input: ParseStream) -> bool {
+        input.peek(syn::Token![@]) || (input.peek(Ident) && input.peek2(syn::Token![*]))
+    }
+
+    pub fn matches(&self, wasi_name: &str) -> bool {
+        match self {
+            Selector::Group(_, categories) => {
+                wasi::Category::of(wasi_name).is_some_and(|category| categories.contains(&category))
+            }
+            Selector::Prefix(prefix) => wasi_name.starts_with(&prefix.to_string()),
+        }
+    }
+
+    fn span(&self) -> proc_macro2::Span {
+        match self {
+            Selector::Group(ident, _) | Selector::Prefix(ident) => ident.span(),
+        }
+    }
+}
+impl Parse for Selector {
+    fn parse(input: ParseStream) -> Result<Self> {
+        if input.parse::<Option<syn::Token![@]>>()?.is_some() {
+            let group = input.parse::<Ident>()?;
+            return match wasi::Category::group(&group.to_string()) {
+                Some(categories) => Ok(Selector::Group(group, categories)),
+                None => {
+                    let groups: Vec<_> = wasi::Category::GROUPS
+                        .iter()
+                        .map(|(name, _)| format!("`@{name}`"))
+                        .collect();
+                    Err(syn::Error::new(
+                        group.span(),
+                        format!("unknown group, expected one of {}", groups.join(", ")),
+                    ))
+                }
+            };
+        }
+        let prefix = input.parse::<Ident>()?;
+        input.parse::<syn::Token![*]>()?;
+        Ok(Selector::Prefix(prefix))
+    }
+}
+
+/// A statement on a single ABI, or on a group of ABIs without bounds.
+///
+/// Either is checked after the call if headed by `after`, and replaces the inherited
+/// statements of the ABIs if headed by `override`.
+pub enum Statement {
+    Wasi(WasiStatement),
+    Group {
+        overrides: bool,
+        after: bool,
+        action: Action,
+        selector: Selector,
+    },
+}
+impl Statement {
+    /// Whether the statement is headed by `after`, e.g., `after log fd_write;`.
+    fn peek_after(input: ParseStream) -> bool {
+        let fork = input.fork();
+        fork.parse::<Ident>().is_ok_and(|ident| ident == "after") && fork.peek(Ident)
+    }
+}
+impl Parse for Statement {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let overrides = input.parse::<Option<syn::Token![override]>>()?.is_some();
+        let after = Self::peek_after(input);
+        if after {
+            input.parse::<Ident>()?;
+        }
+        let action = input.parse()?;
+        if !Selector::peek(input) {
+            let mut stmt = WasiStatement::parse_after_action(action, input)?;
+            stmt.after = after;
+            stmt.overrides = overrides;
+            return Ok(Statement::Wasi(stmt));
+        }
+        let selector = input.parse()?;
+        if input.peek(syn::Token![where]) {
+            return Err(input.error("statements on groups of WASI ABIs can not have bounds"));
+        }
+        Ok(Statement::Group {
+            overrides,
+            after,
+            action,
+            selector,
+        })
+    }
+}
+
+impl WasiStatement {
+    /// Turns the expressions among the bounds into closures taking the params of
+    /// the ABI declared in `module` by the names of its args, as typed by the other
+    /// closures of the statement, or else by `<abi>_params_t`.
+    fn resolve_exprs(&mut self, module: Option<&proc_macro2::TokenStream>) -> Result<()> {
+        let Some(expr) = self.bounds.iter().find_map(|bound| match bound {
+            Bound::Expr(expr) => Some(expr),
+            _ => None,
+        }) else {
+            return Ok(());
+        };
+        let wasi_name = self.wasi.to_string();
+        let Some(desc) = descriptor_of(&wasi_name) else {
+            return Err(syn::Error::new(
+                expr.span(),
+                format!("the args of `{wasi_name}` are unknown; bound it with a closure instead"),
+            ));
+        };
+        let names = param_names(desc, self.after);
+        let types: Vec<syn::Type> = if self.arg_types.is_empty() {
+            let params = get_path_of_param_type(&wasi_name, module, self.after);
+            (0..names.len())
+                .map(|i| syn::parse_quote! { <#params as wasi_guard::util::Element<#i>>::Type })
+                .collect()
+        } else {
+            self.arg_types.iter().map(|pat| (*pat.ty).clone()).collect()
+        };
+        for bound in self.bounds.iter_mut() {
+            let Bound::Expr(expr) = bound else {
+                continue;
+            };
+            // The params are named in the context of the expression, which differs
+            // from that of `policy!` if forwarded by the macro of a layer.
+            let span = match expr.to_token_stream().into_iter().next() {
+                Some(token) => token.span(),
+                None => proc_macro2::Span::call_site(),
+            };
+            let names = names.iter().map(|name| {
+                let mut name = name.clone();
+                name.set_span(span);
+                name
+            });
+            // The context, if seen by the other closures, is named `ctx`.
+            let ctx = self.ctx.as_ref().map(|ctx| {
+                let name = Ident::new("ctx", span);
+                quote! { #[allow(unused_variables)] #name: &#ctx, }
+            });
+            let closure: syn::ExprClosure = syn::parse_quote! {
+                |#ctx #(#[allow(unused_variables)] #names: #types),*| -> bool { #expr }
+            };
+            *bound = Bound::Closure(closure);
+        }
+        if self.arg_types.is_empty() {
+            let arg_types = self
+                .closures()
+                .find(|closure| params_of(closure).all(|input| matches!(input, syn::Pat::Type(_))))
+                .into_iter()
+                .flat_map(params_of)
+                .filter_map(|input| match input {
+                    syn::Pat::Type(pat) => Some(pat.clone()),
+                    _ => None,
+                })
+                .collect();
+            self.arg_types = arg_types;
+        }
+        Ok(())
+    }
+
+    /// Whether the closures of `other` type the params as those of this statement.
+    fn has_arg_types_of(&self, other: &WasiStatement) -> bool {
+        let types = |stmt: &WasiStatement| {
+            stmt.arg_types
+                .iter()
+                .map(|pat| pat.ty.to_token_stream().to_string())
+                .collect::<Vec<_>>()
+        };
+        types(self) == types(other)
+    }
+
+    /// The closures among the bounds.
+    fn closures(&self) -> impl Iterator<Item = &syn::ExprClosure> {
+        self.bounds.iter().filter_map(|bound| match bound {
+            Bound::Closure(closure) => Some(closure),
+            _ => None,
+        })
+    }
+
+    /// Checks the arity and the widths of the params of every closure against
+    /// `desc`, the descriptor of the ABI, and their types against those of the
+    /// other closures of the statement.
+    ///
+    /// Closures of different statements may type the params differently, as they
+    /// are converted from the raw params of the ABI then.
+    fn validate(&self, desc: &ErasedDescriptor<'_>) -> Result<()> {
+        let wasi = &self.wasi;
+        if desc.args.len() > MAX_ARGS {
+            return Err(syn::Error::new(
+                wasi.span(),
+                format!(
+                    "{wasi} takes {} args; the guards take at most {MAX_ARGS}",
+                    desc.args.len()
+                ),
+            ));
+        }
+        let arity = if self.after {
+            desc.args.len() + 1 + desc.args.iter().filter(|arg| arg.is_out()).count()
+        } else {
+            desc.args.len()
+        };
+        let fits = |size: usize, width: usize| match size {
+            0..=4 => (size..=4).contains(&width),
+            _ => width == 8,
+        };
+        let mut errors: Vec<syn::Error> = Vec::new();
+        for closure in self.closures() {
+            let params = params_of(closure).count();
+            if params != arity {
+                let phase = if self.after { " after the call" } else { "" };
+                errors.push(syn::Error::new(
+                    closure.inputs.span(),
+                    format!("{wasi} takes {arity} params{phase}; the closure takes {params}"),
+                ));
+                continue;
+            }
+            for (i, input) in params_of(closure).enumerate() {
+                let syn::Pat::Type(pat) = input else {
+                    continue;
+                };
+                let ty = &pat.ty;
+                let ty_name = ty.to_token_stream().to_string();
+                if let Some(expected) = self.arg_types.get(i).map(|param| &param.ty) {
+                    let expected = expected.to_token_stream().to_string();
+                    if ty_name != expected {
+                        errors.push(syn::Error::new(
+                            ty.span(),
+                            format!("{wasi} param{i} is `{expected}` in another closure of the statement; `{ty_name}` differs"),
+                        ));
+                        continue;
+                    }
+                }
+                let Some(width) = width_of(ty) else {
+                    continue;
+                };
+                match desc.args.get(i) {
+                    // An i32 arg fits the types from its size up to 4 bytes, and an
+                    // i64 one only the 8-byte types.
+                    Some(arg) if !fits(arg.size, width) => {
+                        errors.push(syn::Error::new(
+                            ty.span(),
+                            format!(
+                                "{wasi} arg{i} `{}` is {} bytes; `{ty_name}` is {width}",
+                                arg.name, arg.size
+                            ),
+                        ));
+                    }
+                    Some(_) => {}
+                    // The errno is passed as 2 bytes, and the out values as 8.
+                    None if i == desc.args.len() => {
+                        if !fits(2, width) {
+                            errors.push(syn::Error::new(
+                                ty.span(),
+                                format!("{wasi} errno is 2 bytes; `{ty_name}` is {width}"),
+                            ));
+                        }
+                    }
+                    None if !fits(8, width) => {
+                        errors.push(syn::Error::new(
+                            ty.span(),
+                            format!(
+                                "{wasi} out value `{}` is 8 bytes; `{ty_name}` is {width}",
+                                param_names(desc, true)[i]
+                            ),
+                        ));
+                    }
+                    None => {}
+                }
+            }
+        }
+        match errors.into_iter().reduce(|mut errors, error| {
+            errors.combine(error);
+            errors
+        }) {
+            Some(errors) => Err(errors),
+            None => Ok(()),
+        }
+    }
+
+    /// The statement on the ABI declared in `module`, or in the scope of `policy!` if `None`.
+    ///
+    /// `typed` tells whether the params of the guard are typed by a closure rather
+    /// than the default params of the ABI, and `raw` whether they are the raw params
+    /// of the ABI, which the statements convert into their own. `ctx` is the context
+    /// of the guard, which the statement ignores unless its closures take it.
+    fn to_tokens_in(
+        &self,
+        module: Option<&proc_macro2::TokenStream>,
+        typed: bool,
+        raw: bool,
+        ctx: Option<&syn::Type>,
+    ) -> proc_macro2::TokenStream {
         let Self {
+            after,
             wasi,
             bounds,
             action,
             ..
         } = self;
+        let (_, param_type_name) = guard_names(&wasi.to_string(), *after);
+        let into_raw = if raw && !bounds.is_empty() {
+            quote! { .into_raw::<#param_type_name>() }
+        } else {
+            proc_macro2::TokenStream::new()
+        };
+        let in_context = match ctx {
+            Some(ctx) if self.ctx.is_none() => quote! { .in_context::<#ctx>() },
+            _ => proc_macro2::TokenStream::new(),
+        };
+        let bounds = bounds.iter().map(|bound| match bound {
+            Bound::Closure(closure) if context_of(closure).is_some() => {
+                quote! { wasi_guard::policy::bound::WithContext(#closure) }
+            }
+            bound => bound.to_token_stream(),
+        });
+        if *after || (typed && self.bounds.is_empty()) || self.ctx.is_some() {
+            // The post-call params are not the arguments of the descriptor, a
+            // statement without bounds shares the params typed by the others, and
+            // one seeing the context is not made by `statement!` in that context,
+            // so these are built on the params of the guard rather than by `statement!`.
+            let bounds = bounds.enumerate().map(|(i, bound)| {
+                if i == 0 {
+                    quote! { .when(#bound) }
+                } else {
+                    quote! { .and_when(#bound) }
+                }
+            });
+            let ctx = self.ctx.as_ref().map(|ctx| quote! { , #ctx });
+            return quote! {
+                wasi_guard::policy::stmt::Statement::<#param_type_name #ctx>::new(#action) #(#bounds)* #into_raw #in_context
+            };
+        }
+        let bounds: Vec<proc_macro2::TokenStream> = bounds.collect();
         let bounds = if bounds.is_empty() {
             proc_macro2::TokenStream::new()
         } else {
             quote! { where #(#bounds),* }
         };
+        let wasi = match module {
+            Some(module) => quote! { #module::#wasi },
+            None => quote! { wasi_guard::wasi::#wasi },
+        };
+
+        quote! { wasi_guard::statement!(#wasi #bounds => #action) #into_raw #in_context }
+    }
+}
+impl ToTokens for WasiStatement {
+    fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
+        self.to_tokens_in(None, false, false, None)
+            .to_tokens(tokens);
+    }
+}
+
+/// Name of the module generated by `policy!` to resolve the ABIs of the descriptor modules.
+const DESCRIPTORS_MODULE: &str = "__wasi_guard_descriptors";
+
+/// `use descriptors path::to::module;`, which registers a module of descriptors
+/// declared by `declare_host_abis!`.
+pub struct UseDescriptors(syn::Path);
+impl UseDescriptors {
+    fn peek(input: ParseStream) -> bool {
+        input.peek(syn::Token![use])
+    }
+
+    /// The path of the module from [`DESCRIPTORS_MODULE`], a child of the scope of `policy!`.
+    fn path_from_child(&self) -> proc_macro2::TokenStream {
+        let path = &self.0;
+        match path.segments.first() {
+            Some(first) if path.leading_colon.is_none() && first.ident == "self" => {
+                let rest = path.segments.iter().skip(1);
+                quote! { super #(::#rest)* }
+            }
+            Some(first) if path.leading_colon.is_none() && first.ident == "super" => {
+                quote! { super::#path }
+            }
+            _ => quote! { #path },
+        }
+    }
+}
+impl Parse for UseDescriptors {
+    fn parse(input: ParseStream) -> Result<Self> {
+        input.parse::<syn::Token![use]>()?;
+        let descriptors = input.parse::<Ident>()?;
+        if descriptors != "descriptors" {
+            return Err(syn::Error::new(
+                descriptors.span(),
+                "expected `descriptors`",
+            ));
+        }
+        let path = input.call(syn::Path::parse_mod_style)?;
+        input.parse::<syn::Token![;]>()?;
+        Ok(UseDescriptors(path))
+    }
+}
 
-        quote! { wasi_guard::statement!(#wasi #bounds => #action) }.to_tokens(tokens);
+/// `mode = first_match;` or `mode = all_match;`, how the statements of each
+/// guard are evaluated.
+#[derive(Clone, Copy, Default)]
+pub enum Mode {
+    #[default]
+    AllMatch,
+    FirstMatch,
+}
+impl Mode {
+    fn peek(input: ParseStream) -> bool {
+        let fork = input.fork();
+        fork.parse::<Ident>().is_ok_and(|ident| ident == "mode") && fork.peek(syn::Token![=])
+    }
+}
+impl Parse for Mode {
+    fn parse(input: ParseStream) -> Result<Self> {
+        input.parse::<Ident>()?;
+        input.parse::<syn::Token![=]>()?;
+        let mode = input.parse::<Ident>()?;
+        let mode = match mode.to_string().as_str() {
+            "all_match" => Mode::AllMatch,
+            "first_match" => Mode::FirstMatch,
+            _ => {
+                return Err(syn::Error::new(
+                    mode.span(),
+                    "expected `all_match` or `first_match`",
+                ))
+            }
+        };
+        input.parse::<syn::Token![;]>()?;
+        Ok(mode)
+    }
+}
+impl ToTokens for Mode {
+    fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
+        match self {
+            Mode::AllMatch => quote! { wasi_guard::policy::Mode::AllMatch },
+            Mode::FirstMatch => quote! { wasi_guard::policy::Mode::FirstMatch },
+        }
+        .to_tokens(tokens)
     }
 }
 
 pub struct Policy {
     pub default_action: Action,
+    /// Name of the layer the default action comes from.
+    pub default_layer: String,
+    pub mode: Mode,
     /// { wasi_ident -> statements }
     pub statements: BTreeMap<syn::Ident, Vec<WasiStatement>>,
+    /// Modules of the descriptors of host ABIs other than WASI.
+    pub descriptors: Vec<UseDescriptors>,
 
     wasi_names: Vec<String>,
 }
 impl Policy {
+    /// Whether the ABI has statements checked before the call.
     fn has_wasi_named(&self, wasi_name: &str) -> bool {
         self.wasi_names.iter().any(|ident| ident == wasi_name)
     }
+
+    /// Whether the ABI has statements checked after the call.
+    fn has_post_statements(&self, wasi_name: &str) -> bool {
+        self.statements
+            .iter()
+            .any(|(wasi, stmts)| wasi == wasi_name && stmts.iter().any(|stmt| stmt.after))
+    }
+
+    /// Merges the layers of a policy, from the base one extended by the others to
+    /// the policy itself.
+    ///
+    /// The default action and the mode are those of the last layer specifying them,
+    /// and the statements of each ABI are appended to the inherited ones, unless
+    /// headed by `override`, which replaces those of the same phase.
+    pub fn from_layers(layers: Vec<Layer>) -> Result<Self> {
+        let mut default_action: Option<(Action, String)> = None;
+        let mut mode: Option<Mode> = None;
+        let mut descriptors: Vec<UseDescriptors> = Vec::new();
+        let mut statements: BTreeMap<syn::Ident, Vec<WasiStatement>> = BTreeMap::new();
+        let known_wasis = known_wasis();
+        let extending = layers.len() > 1;
+        for mut layer in layers {
+            if let Some(action) = layer.default_action.take() {
+                default_action = Some((action, layer.name.clone()));
+            }
+            mode = layer.mode.or(mode);
+            for module in core::mem::take(&mut layer.descriptors) {
+                let path = module.0.to_token_stream().to_string();
+                if !descriptors
+                    .iter()
+                    .any(|known| known.0.to_token_stream().to_string() == path)
+                {
+                    descriptors.push(module);
+                }
+            }
+            for (wasi, stmts) in layer.expand(&known_wasis)? {
+                let inherited = statements.entry(wasi).or_default();
+                for after in [false, true] {
+                    if let Some(stmt) = stmts
+                        .iter()
+                        .find(|stmt| stmt.after == after && stmt.overrides)
+                    {
+                        if !extending {
+                            return Err(syn::Error::new(
+                                stmt.wasi.span(),
+                                "nothing to override in a policy extending no layers",
+                            ));
+                        }
+                        inherited.retain(|stmt| stmt.after != after);
+                    }
+                }
+                inherited.extend(stmts);
+            }
+        }
+        let Some((default_action, default_layer)) = default_action else {
+            return Err(syn::Error::new(
+                proc_macro2::Span::call_site(),
+                "expected `default = <action>;` in the policy or a layer it extends",
+            ));
+        };
+
+        for (wasi, stmts) in statements.iter_mut() {
+            let module = known_wasis
+                .iter()
+                .find(|(name, _)| *wasi == name)
+                .and_then(|(_, module)| module.as_ref());
+            for stmt in stmts.iter_mut() {
+                stmt.resolve_exprs(module)?;
+            }
+        }
+        for (wasi, stmts) in statements.iter() {
+            // ABIs of the descriptor modules are unknown until they are compiled.
+            let Some(desc) = descriptor_of(&wasi.to_string()) else {
+                continue;
+            };
+            for stmt in stmts {
+                stmt.validate(desc)?;
+            }
+        }
+        for stmts in statements.values() {
+            for after in [false, true] {
+                let mut contexts = stmts
+                    .iter()
+                    .filter(|stmt| stmt.after == after)
+                    .filter_map(|stmt| stmt.ctx.as_ref());
+                let Some(ctx) = contexts.next() else {
+                    continue;
+                };
+                let name = ctx.to_token_stream().to_string();
+                if let Some(other) =
+                    contexts.find(|other| other.to_token_stream().to_string() != name)
+                {
+                    return Err(syn::Error::new(
+                        other.span(),
+                        format!(
+                            "the context is `{name}` in another statement of the ABI; `{}` differs",
+                            other.to_token_stream()
+                        ),
+                    ));
+                }
+            }
+        }
+        let wasi_names = statements
+            .iter()
+            .filter(|(_, stmts)| stmts.iter().any(|stmt| !stmt.after))
+            .map(|(ident, _)| ident.to_string())
+            .collect();
+
+        Ok(Self {
+            default_action,
+            default_layer,
+            mode: mode.unwrap_or_default(),
+            statements,
+            descriptors,
+            wasi_names,
+        })
+    }
 }
 
 impl Parse for Policy {
     fn parse(input: ParseStream) -> Result<Self> {
-        input.parse::<syn::Token![default]>()?;
-        input.parse::<syn::Token![=]>()?;
-        let default_action = input.parse()?;
+        let mut layer: Layer = input.parse()?;
+        layer.name = String::from(Layer::UNNAMED);
+        Self::from_layers(vec![layer])
+    }
+}
+
+/// The statements of a policy, or of a layer of it declared by `layer NAME;`,
+/// before merging them with the layers extended by `extends PATH;`.
+pub struct Layer {
+    pub name: String,
+    default_action: Option<Action>,
+    mode: Option<Mode>,
+    descriptors: Vec<UseDescriptors>,
+    statements: Vec<WasiStatement>,
+    /// `(overrides, after, action, selector)` of the statements on groups of ABIs.
+    groups: Vec<(bool, bool, Action, Selector)>,
+}
+impl Layer {
+    /// Name of the layer of a policy without `layer NAME;`.
+    pub const UNNAMED: &'static str = "policy";
 
+    /// The statements of each ABI, with the groups expanded into the ABIs without
+    /// their own statements of the same phase in this layer.
+    fn expand(
+        self,
+        known_wasis: &[(&'static str, Option<proc_macro2::TokenStream>)],
+    ) -> Result<BTreeMap<syn::Ident, Vec<WasiStatement>>> {
+        let name = self.name;
+        let mut statements: BTreeMap<syn::Ident, Vec<WasiStatement>> = self
+            .statements
+            .into_iter()
+            .fold(BTreeMap::new(), |mut map, mut stmt| {
+                stmt.layer.clone_from(&name);
+                map.entry(stmt.wasi.clone()).or_default().push(stmt);
+                map
+            });
+
+        let specified: Vec<(String, bool)> = statements
+            .iter()
+            .flat_map(|(wasi, stmts)| stmts.iter().map(|stmt| (wasi.to_string(), stmt.after)))
+            .collect();
+        for (overrides, after, action, selector) in self.groups {
+            let mut matched = false;
+            for (wasi_name, _) in known_wasis
+                .iter()
+                .filter(|(name, _)| selector.matches(name))
+            {
+                matched = true;
+                if specified
+                    .iter()
+                    .any(|(name, phase)| name == wasi_name && *phase == after)
+                {
+                    continue;
+                }
+                let wasi = Ident::new(wasi_name, selector.span());
+                let mut stmt = WasiStatement::new(wasi.clone(), action.clone());
+                stmt.after = after;
+                stmt.overrides = overrides;
+                stmt.layer.clone_from(&name);
+                statements.entry(wasi).or_default().push(stmt);
+            }
+            if !matched {
+                return Err(syn::Error::new(
+                    selector.span(),
+                    "no known WASI ABI matches this selector",
+                ));
+            }
+        }
+        Ok(statements)
+    }
+}
+impl Parse for Layer {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let mut default_action: Option<Action> = None;
+        let mut mode: Option<Mode> = None;
+        let mut descriptors: Vec<UseDescriptors> = Vec::new();
         let mut statements: Vec<WasiStatement> = Vec::new();
+        let mut groups: Vec<(bool, bool, Action, Selector)> = Vec::new();
         while !input.is_empty() {
             // ignore heading semicolons
             while input.peek(syn::Token![;]) {
                 input.parse::<syn::Token![;]>().unwrap();
             }
-            if !input.is_empty() {
-                statements.push(input.parse()?);
+            if UseDescriptors::peek(input) {
+                descriptors.push(input.parse()?);
+            } else if Mode::peek(input) {
+                if mode.is_some() {
+                    return Err(input.error("the mode is already specified"));
+                }
+                mode = Some(input.parse()?);
+            } else if input.peek(syn::Token![default]) {
+                if default_action.is_some() {
+                    return Err(input.error("the default action is already specified"));
+                }
+                input.parse::<syn::Token![default]>()?;
+                input.parse::<syn::Token![=]>()?;
+                default_action = Some(input.parse()?);
+            } else if !input.is_empty() {
+                match input.parse()? {
+                    Statement::Wasi(stmt) => statements.push(stmt),
+                    Statement::Group {
+                        overrides,
+                        after,
+                        action,
+                        selector,
+                    } => groups.push((overrides, after, action, selector)),
+                }
             }
         }
-        let statements: BTreeMap<syn::Ident, Vec<WasiStatement>> =
-            statements
-                .into_iter()
-                .fold(BTreeMap::new(), |mut map, stmt| {
-                    map.entry(stmt.wasi.clone()).or_default().push(stmt);
-                    map
-                });
-        let wasi_names = statements.keys().map(|ident| ident.to_string()).collect();
-
         Ok(Self {
+            name: String::new(),
             default_action,
+            mode,
+            descriptors,
             statements,
-            wasi_names,
+            groups,
         })
     }
 }
 
+/// The context of a guard, seen by some of its statements.
+fn context_of_guard<'a>(stmts: &[&'a WasiStatement]) -> Option<&'a syn::Type> {
+    stmts.iter().find_map(|stmt| stmt.ctx.as_ref())
+}
+
+/// Names of the WASI ABIs to be guarded, with the modules declaring them if
+/// they are not re-exported by `wasi_guard::wasi`.
+///
+/// An ABI of a dialect shadowed by a former one, e.g., `sock_listen` of WASIX
+/// and WasmEdge, is guarded as the former. The WasmEdge `v1` sockets come first,
+/// so that their `sock_accept` replaces the standard one as it does in the host.
+/// The WASI 0.2 functions are named as in Rust, e.g., `descriptor_open_at` for
+/// `[method]descriptor.open-at`, and `now` is that of the monotonic clock.
+///
+/// The ABIs taking more than [`MAX_ARGS`] args, e.g., `tcp_socket_start_bind` of
+/// WASI 0.2, can not be guarded.
+fn known_wasis() -> Vec<(&'static str, Option<proc_macro2::TokenStream>)> {
+    let mut known: Vec<(&str, Option<proc_macro2::TokenStream>)> = Vec::new();
+    for (names, descriptors, module) in dialects() {
+        for (name, desc) in names.iter().zip(descriptors) {
+            if desc.args.len() > MAX_ARGS {
+                continue;
+            }
+            if !known.iter().any(|(known, _)| known == name) {
+                known.push((name, module.clone()));
+            }
+        }
+    }
+    known
+}
+
+/// The most args of an ABI which can be guarded, as the params of the guards
+/// are tuples of so many elements at most, beside the errno and the out values.
+const MAX_ARGS: usize = 10;
+
+/// Names and descriptors of the ABIs of a WASI dialect, with the module declaring them.
+type Dialect = (
+    &'static [&'static str],
+    &'static [ErasedDescriptor<'static>],
+    Option<proc_macro2::TokenStream>,
+);
+
+/// The enabled dialects, in the order of precedence of their ABIs.
+fn dialects() -> Vec<Dialect> {
+    #[allow(unused_mut)]
+    let mut dialects: Vec<Dialect> = vec![(&wasi::WASI_NAMES, &wasi::p1::DESCRIPTORS, None)];
+    #[cfg(feature = "wasmedge-sock-v1")]
+    dialects.insert(
+        0,
+        (
+            &wasi::wasmedge::v1::WASI_NAMES,
+            &wasi::wasmedge::v1::DESCRIPTORS,
+            Some(quote! { wasi_guard::wasi::wasmedge::v1 }),
+        ),
+    );
+    #[cfg(feature = "wasmedge-sock-v2")]
+    dialects.push((
+        &wasi::wasmedge::v2::WASI_NAMES,
+        &wasi::wasmedge::v2::DESCRIPTORS,
+        Some(quote! { wasi_guard::wasi::wasmedge::v2 }),
+    ));
+    #[cfg(feature = "wasix")]
+    dialects.push((
+        &wasi::wasix::WASI_NAMES,
+        &wasi::wasix::DESCRIPTORS,
+        Some(quote! { wasi_guard::wasi::wasix }),
+    ));
+    #[cfg(feature = "wasi-threads")]
+    dialects.push((
+        &wasi::threads::WASI_NAMES,
+        &wasi::threads::DESCRIPTORS,
+        Some(quote! { wasi_guard::wasi::threads }),
+    ));
+    #[cfg(feature = "p2")]
+    {
+        macro_rules! push_interfaces {
+            ($($package:ident::$interface:ident),* $(,)?) => {$(
+                dialects.push((
+                    &wasi::p2::$package::$interface::WASI_NAMES,
+                    &wasi::p2::$package::$interface::DESCRIPTORS,
+                    Some(quote! { wasi_guard::wasi::p2::$package::$interface }),
+                ));
+            )*};
+        }
+        push_interfaces!(
+            cli::environment,
+            clocks::monotonic_clock,
+            clocks::wall_clock,
+            filesystem::types,
+            io::streams,
+            random::random,
+            random::insecure,
+            random::insecure_seed,
+            sockets::tcp,
+        );
+    }
+    dialects
+}
+
+/// The descriptor of the known ABI named `wasi_name`, as guarded by [`known_wasis`].
+fn descriptor_of(wasi_name: &str) -> Option<&'static ErasedDescriptor<'static>> {
+    dialects().into_iter().find_map(|(names, descriptors, _)| {
+        let index = names.iter().position(|name| *name == wasi_name)?;
+        descriptors.get(index)
+    })
+}
+
+/// The width in bytes of a primitive type, e.g., `u32`, or `None` for the others,
+/// whose widths are unknown to the macro.
+fn width_of(ty: &syn::Type) -> Option<usize> {
+    let syn::Type::Path(syn::TypePath { qself: None, path }) = ty else {
+        return None;
+    };
+    let width = match path.get_ident()?.to_string().as_str() {
+        "u8" | "i8" | "bool" => 1,
+        "u16" | "i16" => 2,
+        "u32" | "i32" | "f32" | "char" => 4,
+        "u64" | "i64" | "f64" => 8,
+        _ => return None,
+    };
+    Some(width)
+}
+
 // Only used when the type can not be inferred from the arguments in bounds
-fn get_path_of_default_param_type(wasi_name: &str) -> proc_macro2::TokenStream {
-    let wasi_guard_lib = format_ident!("wasi_guard");
-    let wasi_lib = format_ident!("wasi");
-    let type_name = format_ident!("{}_params_default_t", wasi_name.to_lowercase());
-    quote! { #wasi_guard_lib::#wasi_lib::#type_name }
+fn get_path_of_default_param_type(
+    wasi_name: &str,
+    module: Option<&proc_macro2::TokenStream>,
+    after: bool,
+) -> proc_macro2::TokenStream {
+    let type_name = if after {
+        format_ident!("{}_post_params_default_t", wasi_name.to_lowercase())
+    } else {
+        format_ident!("{}_params_default_t", wasi_name.to_lowercase())
+    };
+    match module {
+        Some(module) => quote! { #module::#type_name },
+        None => quote! { wasi_guard::wasi::#type_name },
+    }
+}
+
+/// The params of the ABI as declared, e.g., `wasi_guard::wasi::fd_write_params_t`.
+fn get_path_of_param_type(
+    wasi_name: &str,
+    module: Option<&proc_macro2::TokenStream>,
+    after: bool,
+) -> proc_macro2::TokenStream {
+    let type_name = if after {
+        format_ident!("{}_post_params_t", wasi_name.to_lowercase())
+    } else {
+        format_ident!("{}_params_t", wasi_name.to_lowercase())
+    };
+    match module {
+        Some(module) => quote! { #module::#type_name },
+        None => quote! { wasi_guard::wasi::#type_name },
+    }
+}
+
+/// Names of the params of the ABI described by `desc` in expressions, i.e., the
+/// names of the args, then `errno` and the values behind the out-pointers after
+/// the call, which are named after them without `_ptr`, or with `_out` instead
+/// if taken, e.g., `size` of `fd_write` and `fd_out` of `path_open`. The `self`
+/// of a method of a WASI 0.2 resource is named `this`.
+fn param_names(desc: &ErasedDescriptor<'_>, after: bool) -> Vec<Ident> {
+    let ident = |name: &str| {
+        syn::parse_str::<Ident>(name)
+            .unwrap_or_else(|_| Ident::new_raw(name, proc_macro2::Span::call_site()))
+    };
+    let mut names: Vec<String> = desc
+        .args
+        .iter()
+        .map(|arg| match arg.name {
+            "self" => "this".to_string(),
+            name => name.to_string(),
+        })
+        .collect();
+    if after {
+        names.push("errno".to_string());
+        for arg in desc.args.iter().filter(|arg| arg.is_out()) {
+            let stripped = arg.name.strip_suffix("_ptr").unwrap_or(arg.name);
+            let name = if names.iter().any(|name| name == stripped) {
+                format!("{stripped}_out")
+            } else {
+                stripped.to_string()
+            };
+            names.push(name);
+        }
+    }
+    names.iter().map(|name| ident(name)).collect()
+}
+
+/// The raw params of the ABI, converted into the params of each statement.
+fn get_path_of_raw_param_type(
+    wasi_name: &str,
+    module: Option<&proc_macro2::TokenStream>,
+    after: bool,
+) -> proc_macro2::TokenStream {
+    let type_name = if after {
+        format_ident!("{}_post_params_raw_t", wasi_name.to_lowercase())
+    } else {
+        format_ident!("{}_params_raw_t", wasi_name.to_lowercase())
+    };
+    match module {
+        Some(module) => quote! { #module::#type_name },
+        None => quote! { wasi_guard::wasi::#type_name },
+    }
+}
+
+/// Names of the guard of an ABI and of its param type, checked after the call if `after`.
+fn guard_names(wasi_name: &str, after: bool) -> (Ident, Ident) {
+    if after {
+        (
+            format_ident!("WASI_GUARD_AFTER_{}", wasi_name.to_uppercase()),
+            format_ident!("{}_guard_post_params_t", wasi_name.to_lowercase()),
+        )
+    } else {
+        (
+            format_ident!("WASI_GUARD_{}", wasi_name.to_uppercase()),
+            format_ident!("{}_guard_params_t", wasi_name.to_lowercase()),
+        )
+    }
 }
 
 impl ToTokens for Policy {
     fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
         let default_action = self.default_action.clone();
-        let specified_guards = self.statements.iter().map(|(wasi, stmts)| {
-            let wasi_name = wasi.to_string();
-            let guard_name = format_ident!("WASI_GUARD_{}", wasi_name.to_uppercase());
-            let param_type = if stmts[0].arg_types.is_empty() {
-                let param_type_path = get_path_of_default_param_type(wasi_name.as_str());
-                quote! { #param_type_path }
-            } else {
-                let param_types: Vec<&Box<syn::Type>> = stmts[0].arg_types.iter().map(|pat| &pat.ty).collect();
-                quote! { (#(#param_types,)*) }
+        let default_layer = &self.default_layer;
+        let mode = self.mode;
+        let known_wasis = known_wasis();
+        let descriptors_module = format_ident!("{}", DESCRIPTORS_MODULE);
+        let descriptors_module = quote! { #descriptors_module };
+        let module_of =
+            |wasi_name: &str| match known_wasis.iter().find(|(name, _)| *name == wasi_name) {
+                Some((_, module)) => module.as_ref(),
+                // ABIs other than WASI are resolved in the descriptor modules.
+                None if !self.descriptors.is_empty() => Some(&descriptors_module),
+                None => None,
             };
-            let param_type_name = format_ident!("{}_guard_params_t", wasi_name.to_lowercase());
-            quote! {
-                pub type #param_type_name = #param_type;
-                wasi_guard::policy::lazy_static! {
-                    pub static ref #guard_name: Option<wasi_guard::policy::WasiGuard<'static, #param_type_name>> =
-                        Some(wasi_guard::policy::WasiGuard::from_arr([
-                            #(#stmts),*
-                        ]));
+        let specified_guards = self.statements.iter().flat_map(|(wasi, stmts)| {
+            let wasi_name = wasi.to_string();
+            let module = module_of(wasi_name.as_str());
+            [false, true].into_iter().filter_map(move |after| {
+                let stmts: Vec<&WasiStatement> = stmts.iter().filter(|stmt| stmt.after == after).collect();
+                let (guard_name, param_type_name) = guard_names(&wasi_name, after);
+                if stmts.is_empty() {
+                    return None;
                 }
-            }
+                let typed = stmts.iter().find(|stmt| !stmt.arg_types.is_empty());
+                // Statements typing the params differently are held on the raw params.
+                let raw = stmts.iter().any(|stmt| {
+                    !stmt.arg_types.is_empty() && typed.is_some_and(|typed| !typed.has_arg_types_of(stmt))
+                });
+                let param_type = match typed {
+                    _ if raw => get_path_of_raw_param_type(wasi_name.as_str(), module, after),
+                    None => get_path_of_default_param_type(wasi_name.as_str(), module, after),
+                    Some(stmt) => {
+                        let param_types: Vec<&Box<syn::Type>> = stmt.arg_types.iter().map(|pat| &pat.ty).collect();
+                        quote! { (#(#param_types,)*) }
+                    }
+                };
+                let ctx = context_of_guard(&stmts);
+                let stmts = stmts.iter().map(|stmt| stmt.to_tokens_in(module, typed.is_some(), raw, ctx));
+                let ctx = ctx.map(|ctx| quote! { , #ctx });
+                Some(quote! {
+                    pub type #param_type_name = #param_type;
+                    wasi_guard::policy::lazy_static! {
+                        pub static ref #guard_name: Option<wasi_guard::policy::WasiGuard<'static, #param_type_name #ctx>> =
+                            Some(wasi_guard::policy::WasiGuard::from_arr([
+                                #(#stmts),*
+                            ]).with_mode(MODE));
+                    }
+                })
+            })
         });
 
-        let rest_wasis: Vec<String> = wasi::WASI_NAMES
+        // The same guards on the raw params, held by the `PolicySet` built at runtime.
+        let set_guards = self.statements.iter().flat_map(|(wasi, stmts)| {
+            let wasi_name = wasi.to_string();
+            let module = module_of(wasi_name.as_str());
+            [false, true].into_iter().filter_map(move |after| {
+                let stmts: Vec<&WasiStatement> =
+                    stmts.iter().filter(|stmt| stmt.after == after).collect();
+                if stmts.is_empty() {
+                    return None;
+                }
+                let (_, param_type_name) = guard_names(&wasi_name, after);
+                let param_type = get_path_of_raw_param_type(wasi_name.as_str(), module, after);
+                let ctx = context_of_guard(&stmts);
+                let stmts = stmts
+                    .iter()
+                    .map(|stmt| stmt.to_tokens_in(module, true, true, ctx));
+                let ctx = ctx.map(|ctx| quote! { , #ctx });
+                Some(quote! {
+                    .with_guard(#wasi_name, #after, {
+                        // shadows the params of the static guard
+                        type #param_type_name = #param_type;
+                        wasi_guard::policy::WasiGuard::<'static, #param_type_name #ctx>::from_arr([
+                            #(#stmts),*
+                        ]).with_mode(MODE)
+                    })
+                })
+            })
+        });
+
+        let guard_layers = self.statements.iter().flat_map(|(wasi, stmts)| {
+            let wasi_name = wasi.to_string();
+            [false, true].into_iter().filter_map(move |after| {
+                let layers: Vec<&str> = stmts
+                    .iter()
+                    .filter(|stmt| stmt.after == after)
+                    .map(|stmt| stmt.layer.as_str())
+                    .collect();
+                (!layers.is_empty()).then(|| {
+                    quote! {
+                        wasi_guard::policy::GuardLayers {
+                            wasi: #wasi_name,
+                            after: #after,
+                            layers: &[#(#layers),*],
+                        }
+                    }
+                })
+            })
+        });
+
+        let rest_wasis: Vec<String> = known_wasis
             .iter()
-            .filter_map(|wasi_name| {
+            .filter_map(|(wasi_name, _)| {
                 if !self.has_wasi_named(wasi_name) {
                     Some(wasi_name.to_string())
                 } else {
@@ -278,27 +1337,43 @@ impl ToTokens for Policy {
                 }
             })
             .collect();
-        let default_guards = rest_wasis.iter().map(|wasi_name| {
-            let guard_name = format_ident!("WASI_GUARD_{}", wasi_name.to_uppercase());
-            let param_type = get_path_of_default_param_type(wasi_name);
-            let param_type_name = format_ident!("{}_guard_params_t", wasi_name.to_lowercase());
+        let default_guards = known_wasis.iter().flat_map(|(wasi_name, module)| {
+            let phases = [
+                (!self.has_wasi_named(wasi_name)).then_some(false),
+                (!self.has_post_statements(wasi_name)).then_some(true),
+            ];
+            phases.into_iter().flatten().map(move |after| {
+                let (guard_name, param_type_name) = guard_names(wasi_name, after);
+                let param_type = get_path_of_default_param_type(wasi_name, module.as_ref(), after);
 
-            quote! {
-                pub type #param_type_name = #param_type;
-                pub const #guard_name: Option<wasi_guard::policy::WasiGuard<'static, #param_type_name>> = None;
-            }
+                quote! {
+                    pub type #param_type_name = #param_type;
+                    pub const #guard_name: Option<wasi_guard::policy::WasiGuard<'static, #param_type_name>> = None;
+                }
+            })
         });
 
         let must_be_killed = {
             let mut must_be_killed: Vec<_> = self
                 .statements
                 .iter()
-                .filter(|(_, stmts)| stmts.iter().any(|stmt| stmt.must_be_killed()))
+                .filter(|(_, stmts)| match self.mode {
+                    Mode::AllMatch => stmts.iter().any(|stmt| stmt.must_be_killed()),
+                    // a former statement may be satisfied instead
+                    Mode::FirstMatch => stmts
+                        .iter()
+                        .find(|stmt| !stmt.after)
+                        .is_some_and(|stmt| stmt.must_be_killed()),
+                })
                 .map(|(wasi, _)| wasi.to_string())
                 .collect();
 
             if default_action.is_kill() {
-                must_be_killed.extend(rest_wasis.clone());
+                for wasi_name in &rest_wasis {
+                    if !must_be_killed.contains(wasi_name) {
+                        must_be_killed.push(wasi_name.clone());
+                    }
+                }
             }
 
             let vec_len = must_be_killed.len();
@@ -307,11 +1382,47 @@ impl ToTokens for Policy {
             }
         };
 
+        let descriptor_modules = {
+            let paths: Vec<_> = self.descriptors.iter().map(|module| &module.0).collect();
+            let paths_from_child = self.descriptors.iter().map(UseDescriptors::path_from_child);
+            let glob_uses = if paths.is_empty() {
+                proc_macro2::TokenStream::new()
+            } else {
+                quote! {
+                    #[doc(hidden)]
+                    #[allow(unused_imports)]
+                    mod #descriptors_module {
+                        use super::*;
+                        #(pub use #paths_from_child::*;)*
+                    }
+                }
+            };
+            quote! {
+                #glob_uses
+                pub const CUSTOM_DESCRIPTORS: &[&[wasi_guard::wasi_descriptor::ErasedDescriptor<'static>]] = &[
+                    #(&#paths::DESCRIPTORS),*
+                ];
+            }
+        };
+
         quote! {
+            #descriptor_modules
             pub const DEFUALT_ACTION: wasi_guard::policy::action::Action = #default_action;
+            pub const MODE: wasi_guard::policy::Mode = #mode;
+            pub const DEFAULT_ACTION_LAYER: &str = #default_layer;
+            pub const GUARD_LAYERS: &[wasi_guard::policy::GuardLayers] = &[#(#guard_layers),*];
             #(#specified_guards)*
             #(#default_guards)*
             #must_be_killed
+
+            /// Builds the guards of the policy anew on the raw params of their ABIs,
+            /// e.g., to be reloaded by a `wasi_guard::policy::ReloadablePolicy`.
+            pub fn policy_set() -> wasi_guard::policy::PolicySet {
+                wasi_guard::policy::PolicySet::new(DEFUALT_ACTION, MODE)
+                    .with_must_be_killed(&MUST_BE_KILLED_WASIS)
+                    .with_custom_descriptors(CUSTOM_DESCRIPTORS)
+                    #(#set_guards)*
+            }
         }
         .to_tokens(tokens)
     }
@@ -319,11 +1430,331 @@ impl ToTokens for Policy {
 
 #[cfg(test)]
 mod tests {
+    use super::*;
+
+    #[test]
+    fn errno_names() {
+        let errno = |expr: &str| errno_by_name(&syn::parse_str(expr).unwrap());
+        assert_eq!(errno("PERM"), Some(63));
+        assert_eq!(errno("acces"), None);
+        assert_eq!(errno("Perm"), None);
+        assert_eq!(errno("self::PERM"), None);
+        assert_eq!(errno("ERRNO_2BIG"), Some(1));
+        assert_eq!(errno("wasi::Errno::PERM"), Some(63));
+        assert_eq!(errno("Errno::_2BIG"), Some(1));
+        assert_eq!(errno("wasi::PERM"), None);
+        assert_eq!(errno("NOT_AN_ERRNO"), None);
+        assert_eq!(errno("12 + 1"), None);
+    }
+
+    #[test]
+    fn actions() {
+        let action = |src: &str| {
+            syn::parse_str::<Action>(src).map(|action| action.to_token_stream().to_string())
+        };
+        assert_eq!(
+            action("rewrite(rewrites::CLAMP)").unwrap(),
+            "wasi_guard :: policy :: action :: Action :: Rewrite (rewrites :: CLAMP)"
+        );
+        assert_eq!(
+            action("emulate(CLOCK)").unwrap(),
+            "wasi_guard :: policy :: action :: Action :: Emulate (CLOCK)"
+        );
+        assert_eq!(
+            action("ret_errno(PERM)").unwrap(),
+            "wasi_guard :: policy :: action :: Action :: ReturnErrno (63u16)"
+        );
+        assert_eq!(
+            action("deny").unwrap_err().to_string(),
+            "expected `allow`, `log`, `rewrite`, `emulate`, `kill`, or `return_errno`"
+        );
+    }
+
+    #[test]
+    fn group_selectors() {
+        let policy: Policy = syn::parse_str(
+            "default = allow; ret_errno(PERM) @fs; kill @sock; log path_*; allow fd_close;",
+        )
+        .unwrap();
+        let stmts_of = |wasi_name: &str| {
+            policy
+                .statements
+                .iter()
+                .find(|(wasi, _)| *wasi == wasi_name)
+                .map(|(_, stmts)| stmts)
+        };
+        assert_eq!(stmts_of("fd_read").map(Vec::len), Some(1));
+        assert_eq!(stmts_of("path_open").map(Vec::len), Some(2));
+        assert_eq!(stmts_of("fd_close").map(Vec::len), Some(1));
+        assert!(stmts_of("fd_close").unwrap()[0].action.is_allow());
+        assert!(stmts_of("sock_recv").unwrap()[0].must_be_killed());
+        assert!(stmts_of("proc_exit").is_none());
+
+        let error = |src: &str| syn::parse_str::<Policy>(src).err().map(|e| e.to_string());
+        assert!(error("default = allow; kill @files;")
+            .unwrap()
+            .starts_with("unknown group"));
+        assert!(error("default = allow; kill no_such_*;").is_some());
+        assert!(error("default = allow; kill @fs where |fd: u32| fd > 2;").is_some());
+    }
+
+    #[test]
+    fn post_call_statements() {
+        let policy: Policy = syn::parse_str(
+            "default = allow; \
+             log fd_write; \
+             after log fd_write where |_: u32, _: u32, _: u32, _: u32, errno: u16, _: u64| errno != 0; \
+             after kill @sock; allow sock_recv;",
+        )
+        .unwrap();
+        let stmts_of = |wasi_name: &str| {
+            policy
+                .statements
+                .iter()
+                .find(|(wasi, _)| *wasi == wasi_name)
+                .map(|(_, stmts)| stmts)
+        };
+        let fd_write = stmts_of("fd_write").unwrap();
+        assert_eq!(fd_write.len(), 2);
+        assert!(!fd_write[0].after && fd_write[1].after);
+        assert_eq!(fd_write[1].arg_types.len(), 6);
+        // `allow sock_recv` is checked before the call, so `@sock` still applies after it.
+        let sock_recv = stmts_of("sock_recv").unwrap();
+        assert_eq!(sock_recv.len(), 2);
+        assert!(sock_recv[1].after && !sock_recv[1].must_be_killed());
+        assert!(!stmts_of("sock_send").unwrap()[0].must_be_killed());
+        assert!(policy.has_wasi_named("fd_write"));
+        assert!(policy.has_wasi_named("sock_recv"));
+        assert!(!policy.has_wasi_named("sock_send"));
+        assert!(policy.has_post_statements("sock_send"));
+        assert!(!policy.has_post_statements("fd_read"));
+    }
+
+    #[test]
+    fn modes() {
+        let mode = |src: &str| syn::parse_str::<Policy>(src).map(|policy| policy.mode);
+        assert!(matches!(mode("default = allow;"), Ok(Mode::AllMatch)));
+        assert!(matches!(
+            mode("mode = first_match; default = allow;"),
+            Ok(Mode::FirstMatch)
+        ));
+        assert!(matches!(
+            mode("default = allow; mode = first_match; allow fd_write;"),
+            Ok(Mode::FirstMatch)
+        ));
+        assert!(mode("default = allow; mode = all_match; mode = first_match;").is_err());
+        assert!(mode("default = allow; mode = any_match;").is_err());
+    }
+
+    #[test]
+    fn closure_validation() {
+        let error = |src: &str| {
+            syn::parse_str::<Policy>(&format!("default = allow; {src}"))
+                .err()
+                .map(|e| e.to_string())
+        };
+        assert_eq!(
+            error("kill fd_seek where |_fd: u32, offset: u32, _whence: u8, _ptr: u32| offset > 0;"),
+            Some("fd_seek arg1 `offset` is 8 bytes; `u32` is 4".to_string())
+        );
+        assert_eq!(
+            error("kill fd_close where |fd: u64| fd > 2;"),
+            Some("fd_close arg0 `fd` is 4 bytes; `u64` is 8".to_string())
+        );
+        assert_eq!(
+            error("kill fd_close where |fd: u32, _x: u32| fd > 2;"),
+            Some("fd_close takes 1 params; the closure takes 2".to_string())
+        );
+        assert_eq!(
+            error("kill proc_exit where |code: u32| code > 0, |_| true, |code: i32| code < 0;"),
+            Some(
+                "proc_exit param0 is `u32` in another closure of the statement; `i32` differs"
+                    .to_string()
+            )
+        );
+        // but statements can type them differently
+        assert!(error(
+            "kill proc_exit where |code: u32| code > 0; log proc_exit where |code: i32| code < 0;"
+        )
+        .is_none());
+        assert_eq!(
+            error("after log fd_close where |_fd: u32, errno: u64| errno != 0;"),
+            Some("fd_close errno is 2 bytes; `u64` is 8".to_string())
+        );
+        assert_eq!(
+            error("after log fd_write where |_fd: u32, _a: u32, _b: u32, _c: u32, _e: u16, n: u8| n > 0;"),
+            Some("fd_write out value `size` is 8 bytes; `u8` is 1".to_string())
+        );
+        assert!(error(
+            "after log fd_write where |_fd: u32, _a: u32, _b: u32, _c: u32, _e: u16, n: u64| n > 0;"
+        )
+        .is_none());
+        assert_eq!(
+            error("after log fd_write where |_fd: u32, _a: u32, _b: u32, _c: u32, errno: u16| errno != 0;"),
+            Some("fd_write takes 6 params after the call; the closure takes 5".to_string())
+        );
+        // narrower args fit the wider types of an i32, and unknown types are not checked
+        assert!(error("kill sock_shutdown where |_fd: u32, how: u32| how > 0;").is_none());
+        assert!(
+            error("kill sock_shutdown where |_fd: Fd, how: Sdflags| how.is_empty();").is_none()
+        );
+
+        // the context is not a param of the ABI
+        assert!(error("kill fd_close where |ctx: &Ctx, fd: u32| ctx.allows(fd);").is_none());
+        assert_eq!(
+            error("kill fd_close where |_: &A, fd: u32| fd > 2, |_: &B, fd: u32| fd > 3;"),
+            Some("the context is `A` in another closure of the statement; `B` differs".to_string())
+        );
+        assert_eq!(
+            error("kill fd_close where |_: &A, fd: u32| fd > 2; log fd_close where |_: &B, _: u32| true;"),
+            Some("the context is `A` in another statement of the ABI; `B` differs".to_string())
+        );
+    }
+
+    #[test]
+    fn expression_bounds() {
+        let policy: Policy = syn::parse_str(
+            "default = allow; \
+             ret_errno(ACCES) fd_write where fd > 2 && iovs_len < 16; \
+             kill fd_seek where |fd: u32, _: i64, _: Whence, _: u32| fd == 0, offset < 0; \
+             after log path_open where errno == 0 && fd_out > 1024;",
+        )
+        .unwrap();
+        let stmt_of = |wasi_name: &str| {
+            policy
+                .statements
+                .iter()
+                .find(|(wasi, _)| *wasi == wasi_name)
+                .map(|(_, stmts)| &stmts[0])
+                .unwrap()
+        };
+        let fd_write = stmt_of("fd_write");
+        assert!(matches!(fd_write.bounds[0], Bound::Closure(_)));
+        assert_eq!(fd_write.arg_types.len(), 4);
+        let ty = fd_write.arg_types[2].ty.to_token_stream().to_string();
+        assert!(ty.contains("fd_write_params_t as wasi_guard :: util :: Element < 2usize >"));
+        // typed by the closure of the statement
+        let fd_seek = stmt_of("fd_seek");
+        let Bound::Closure(closure) = &fd_seek.bounds[1] else {
+            panic!("not resolved");
+        };
+        assert!(closure
+            .inputs
+            .to_token_stream()
+            .to_string()
+            .contains("offset : i64"));
+        assert_eq!(stmt_of("path_open").arg_types.len(), 9 + 1 + 1);
+
+        let error = |src: &str| syn::parse_str::<Policy>(src).err().map(|e| e.to_string());
+        assert!(error("default = allow; kill fd_write where fd > 2;").is_none());
+        assert!(error("default = allow; kill no_such_abi where fd > 2;")
+            .unwrap()
+            .starts_with("the args of `no_such_abi` are unknown"));
+    }
+
+    #[test]
+    fn dialect_descriptors() {
+        for (names, descriptors, _) in dialects() {
+            assert_eq!(names.len(), descriptors.len());
+        }
+        assert_eq!(
+            descriptor_of("fd_seek").map(|desc| desc.args.len()),
+            Some(4)
+        );
+        assert!(descriptor_of("kv_put").is_none());
+    }
+
+    #[test]
+    fn descriptor_modules() {
+        let policy: Policy = syn::parse_str(
+            "use descriptors self::host; use descriptors super::abi; default = allow;
+             use descriptors ::my_host::abi; kill kv_get;",
+        )
+        .unwrap();
+        let paths: Vec<String> = policy
+            .descriptors
+            .iter()
+            .map(|module| module.path_from_child().to_string())
+            .collect();
+        assert_eq!(
+            paths,
+            [
+                quote! { super::host }.to_string(),
+                quote! { super::super::abi }.to_string(),
+                quote! { ::my_host::abi }.to_string(),
+            ]
+        );
+        assert!(syn::parse_str::<Policy>("default = allow; use wasi_guard::wasi;").is_err());
+    }
+
+    #[test]
+    fn dialect_abis() {
+        let known = known_wasis();
+        assert!(known
+            .iter()
+            .any(|(name, module)| *name == "fd_write" && module.is_none()));
+        #[cfg(feature = "wasix")]
+        assert!(known
+            .iter()
+            .any(|(name, module)| *name == "proc_fork" && module.is_some()));
+        #[cfg(feature = "wasi-threads")]
+        assert!(known
+            .iter()
+            .any(|(name, module)| *name == "thread_spawn" && module.is_some()));
+        #[cfg(not(feature = "wasix"))]
+        assert!(!known.iter().any(|(name, _)| *name == "proc_fork"));
+        #[cfg(feature = "p2")]
+        {
+            let module_of = |wasi_name: &str| {
+                known
+                    .iter()
+                    .find(|(name, _)| *name == wasi_name)
+                    .and_then(|(_, module)| module.as_ref().map(ToString::to_string))
+            };
+            assert_eq!(
+                module_of("descriptor_open_at"),
+                Some(quote! { wasi_guard::wasi::p2::filesystem::types }.to_string())
+            );
+            assert_eq!(
+                module_of("now"),
+                Some(quote! { wasi_guard::wasi::p2::clocks::monotonic_clock }.to_string())
+            );
+            assert_eq!(
+                descriptor_of("drop_tcp_socket").map(|desc| desc.name),
+                Some("[resource-drop]tcp-socket")
+            );
+            assert!(module_of("tcp_socket_start_bind").is_none());
+            let error = syn::parse_str::<Policy>("default = allow; kill tcp_socket_start_bind;")
+                .err()
+                .unwrap();
+            assert!(error.to_string().contains("takes 15 args"));
+        }
+    }
+
     #[test]
     fn wasmedge_sock_abis() {
-        #[cfg(feature = "wasmedge-sock")]
-        assert!(wasi::WASI_NAMES.iter().any(|name| name == &"sock_listen"));
-        #[cfg(not(feature = "wasmedge-sock"))]
-        assert!(!wasi::WASI_NAMES.iter().any(|name| name == &"sock_listen"));
+        let known = known_wasis();
+        let module_of = |wasi_name: &str| {
+            known
+                .iter()
+                .find(|(name, _)| *name == wasi_name)
+                .map(|(_, module)| module.as_ref().map(ToString::to_string))
+        };
+        #[cfg(not(feature = "wasmedge-sock-v1"))]
+        assert_eq!(module_of("sock_accept"), Some(None));
+        #[cfg(feature = "wasmedge-sock-v1")]
+        {
+            let v1 = Some(Some(quote! { wasi_guard::wasi::wasmedge::v1 }.to_string()));
+            assert_eq!(module_of("sock_accept"), v1);
+            assert_eq!(module_of("sock_getaddrinfo"), v1);
+            assert_eq!(descriptor_of("sock_accept").unwrap().args.len(), 2);
+        }
+        #[cfg(feature = "wasmedge-sock-v2")]
+        assert_eq!(
+            module_of("sock_send_to_v2"),
+            Some(Some(quote! { wasi_guard::wasi::wasmedge::v2 }.to_string()))
+        );
+        #[cfg(not(any(feature = "wasmedge-sock-v1", feature = "wasmedge-sock-v2")))]
+        assert_eq!(module_of("sock_getaddrinfo"), None);
     }
 }
@@ -1,11 +1,9 @@
-use alloc::{
-    collections::{BTreeMap, BTreeSet},
-    rc::Rc,
-    vec::Vec,
-};
+use alloc::{collections::BTreeSet, rc::Rc, vec::Vec};
 
 use wasmparser::{
-    CompositeInnerType, FuncType, Import, Parser, Payload, RecGroup, SubType, TypeRef,
+    ComponentAlias, ComponentExternalKind, ComponentOuterAliasKind, ComponentType,
+    ComponentTypeRef, CompositeInnerType, Encoding, FuncType, InstanceTypeDeclaration, Parser,
+    Payload, RecGroup, SubType, TypeRef,
 };
 
 /// An imported function in a WebAssembly module.
@@ -31,6 +29,25 @@ impl ImportFunc<'_> {
     pub fn unwrap_func(&self) -> &FuncType {
         self.ty.types().next().unwrap().unwrap_func()
     }
+
+    /// The descriptor of the imported ABI among the enabled WASI dialects, if recognised.
+    pub fn descriptor(&self) -> Option<&'static wasi_descriptor::ErasedDescriptor<'static>> {
+        wasi::descriptor_by_name(self.module, self.name)
+    }
+
+    /// The descriptor of the imported ABI among the enabled WASI dialects and the
+    /// `custom` descriptors of host ABIs, e.g., the `CUSTOM_DESCRIPTORS` of `policy!`.
+    pub fn descriptor_among<'d>(
+        &self,
+        custom: &[&'d [wasi_descriptor::ErasedDescriptor<'d>]],
+    ) -> Option<&'d wasi_descriptor::ErasedDescriptor<'d>> {
+        self.descriptor().or_else(|| {
+            custom
+                .iter()
+                .flat_map(|descriptors| descriptors.iter())
+                .find(|desc| desc.is(self.module, self.name))
+        })
+    }
 }
 
 fn is_c_abi_func(ty: &SubType) -> bool {
@@ -53,80 +70,229 @@ pub enum ParseError {
     UnsupportedImportSectionError,
 }
 
+/// Parses the functions imported by a core module, or by the core modules nested in a component.
+///
+/// For a component, e.g., a `wasm32-wasip2` artifact, the imports of all its
+/// nested core modules are listed in order, including the `wasi_snapshot_preview1`
+/// imports of an embedded adapter. Non-function imports of the nested modules and
+/// the internal imports of the shims generated by `wit-component`, whose module
+/// names are empty, are skipped. Use [`parse_component_imports`] for the WIT
+/// interfaces imported by the component itself.
 pub fn parse_import_funcs(wasm_binary: &[u8]) -> Result<Vec<ImportFunc>, ParseError> {
-    struct UntypedImportFunc<'a> {
-        /// The module being imported from.
-        pub module: &'a str,
-        /// The name of the imported item.
-        pub name: &'a str,
-        /// The type index of the imported item.
-        pub type_ref: u32,
+    Ok(parse_imports(wasm_binary)?.core_funcs)
+}
+
+/// A function of a WIT interface imported by a component.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct ComponentImportFunc<'a> {
+    /// The interface being imported from, e.g., `wasi:filesystem/types@0.2.0`.
+    ///
+    /// It is empty for the functions imported by the world directly.
+    pub interface: &'a str,
+    /// The name of the function, e.g., `[method]descriptor.read-via-stream`.
+    pub name: &'a str,
+}
+
+impl ComponentImportFunc<'_> {
+    /// The descriptor of the function as lowered into a core module among the
+    /// enabled WASI dialects, i.e., WASI 0.2 with the `p2` feature, if recognised.
+    pub fn descriptor(&self) -> Option<&'static wasi_descriptor::ErasedDescriptor<'static>> {
+        wasi::descriptor_by_name(self.interface, self.name)
     }
-    impl<'a> TryFrom<Import<'a>> for UntypedImportFunc<'a> {
-        type Error = ParseError;
-        fn try_from(import: Import<'a>) -> Result<Self, Self::Error> {
-            if let TypeRef::Func(index) = import.ty {
-                Ok(UntypedImportFunc {
-                    module: import.module,
-                    name: import.name,
-                    type_ref: index,
-                })
-            } else {
-                Err(ParseError::InvalidImportType)
-            }
-        }
+
+    /// The name of the function in Rust, as guarded by `policy!`, e.g.,
+    /// `descriptor_read_via_stream` for `[method]descriptor.read-via-stream`.
+    pub fn wasi_name(&self) -> Option<&'static str> {
+        self.descriptor()?;
+        wasi::p2::wasi_name_of(self.interface, self.name)
     }
+}
 
-    let mut import_funcs: Vec<UntypedImportFunc> = Vec::new();
-    let mut types: BTreeMap<usize, Rc<RecGroup>> = BTreeMap::new();
+/// The imports of a component.
+#[derive(Debug, Clone, Default)]
+pub struct ComponentImports<'a> {
+    /// The imported instances, e.g., `wasi:filesystem/types@0.2.0`.
+    pub interfaces: Vec<&'a str>,
+    /// The functions of the imported instances and the functions imported directly.
+    pub funcs: Vec<ComponentImportFunc<'a>>,
+    /// The functions imported by the nested core modules, see [`parse_import_funcs`].
+    pub core_funcs: Vec<ImportFunc<'a>>,
+}
+
+/// Whether the binary is a component rather than a core module.
+pub fn is_component(wasm_binary: &[u8]) -> bool {
+    Parser::is_component(wasm_binary)
+}
+
+/// Parses the imports of a component, including the imports of its nested core modules.
+///
+/// A core module is parsed as a component without any interfaces.
+pub fn parse_component_imports(wasm_binary: &[u8]) -> Result<ComponentImports, ParseError> {
+    parse_imports(wasm_binary)
+}
+
+/// Index spaces of the module or component being parsed.
+enum Scope<'a> {
+    Module {
+        types: Vec<Rc<RecGroup>>,
+        /// `(module, name, type index)` of the imported functions.
+        imports: Vec<(&'a str, &'a str, u32)>,
+    },
+    Component {
+        /// The exported functions of the instance types, or `None` for other types.
+        types: Vec<Option<Vec<&'a str>>>,
+    },
+}
+
+fn parse_imports(wasm_binary: &[u8]) -> Result<ComponentImports, ParseError> {
+    let mut imports = ComponentImports::default();
+    let mut scopes: Vec<Scope> = Vec::new();
+    let is_component = Parser::is_component(wasm_binary);
 
     let parser = Parser::new(0);
     for payload in parser.parse_all(wasm_binary) {
-        if payload.is_err() {
-            return Err(ParseError::WasmParseError);
-        }
-        match payload.unwrap() {
-            Payload::ImportSection(reader) => {
+        let payload = payload.map_err(|_| ParseError::WasmParseError)?;
+        let is_outermost = scopes.len() == 1;
+        match (payload, scopes.last_mut()) {
+            (Payload::Version { encoding, .. }, _) => scopes.push(match encoding {
+                Encoding::Module => Scope::Module {
+                    types: Vec::new(),
+                    imports: Vec::new(),
+                },
+                Encoding::Component => Scope::Component { types: Vec::new() },
+            }),
+            (Payload::End(_), _) => {
+                if let Some(Scope::Module {
+                    types,
+                    imports: funcs,
+                }) = scopes.pop()
+                {
+                    for (module, name, type_ref) in funcs {
+                        if is_component && module.is_empty() {
+                            continue;
+                        }
+                        imports.core_funcs.push(ImportFunc {
+                            module,
+                            name,
+                            ty: types
+                                .get(type_ref as usize)
+                                .ok_or(ParseError::WasmParseError)?
+                                .clone(),
+                        });
+                    }
+                }
+            }
+            (Payload::TypeSection(reader), Some(Scope::Module { types, .. })) => {
+                for record_group in reader {
+                    let record_group = record_group.map_err(|_| ParseError::WasmParseError)?;
+                    types.push(Rc::new(record_group));
+                }
+            }
+            (Payload::ImportSection(reader), Some(Scope::Module { imports: funcs, .. })) => {
                 for import in reader {
-                    if import.is_err() {
-                        return Err(ParseError::WasmParseError);
+                    let import = import.map_err(|_| ParseError::WasmParseError)?;
+                    match import.ty {
+                        TypeRef::Func(index) => funcs.push((import.module, import.name, index)),
+                        // memories and tables shared between the modules of a component
+                        _ if is_component => {}
+                        _ => return Err(ParseError::UnsupportedImportSectionError),
                     }
-                    let import = import.unwrap();
-                    if matches!(import.ty, TypeRef::Func(..)) {
-                        import_funcs.push(import.try_into().unwrap());
-                    } else {
-                        return Err(ParseError::UnsupportedImportSectionError);
+                }
+            }
+            (Payload::ComponentTypeSection(reader), Some(Scope::Component { types })) => {
+                for ty in reader {
+                    let ty = ty.map_err(|_| ParseError::WasmParseError)?;
+                    types.push(match ty {
+                        ComponentType::Instance(decls) => Some(
+                            decls
+                                .iter()
+                                .filter_map(|decl| match decl {
+                                    InstanceTypeDeclaration::Export {
+                                        name,
+                                        ty: ComponentTypeRef::Func(_),
+                                    } => Some(name.0),
+                                    _ => None,
+                                })
+                                .collect(),
+                        ),
+                        _ => None,
+                    });
+                }
+            }
+            (Payload::ComponentAliasSection(reader), Some(Scope::Component { types })) => {
+                for alias in reader {
+                    match alias.map_err(|_| ParseError::WasmParseError)? {
+                        ComponentAlias::InstanceExport {
+                            kind: ComponentExternalKind::Type,
+                            ..
+                        }
+                        | ComponentAlias::Outer {
+                            kind: ComponentOuterAliasKind::Type,
+                            ..
+                        } => types.push(None),
+                        _ => {}
                     }
                 }
             }
-            Payload::TypeSection(reader) => {
-                for (offset, record_group) in reader.into_iter().enumerate() {
-                    if record_group.is_err() {
-                        return Err(ParseError::WasmParseError);
+            (Payload::ComponentExportSection(reader), Some(Scope::Component { types })) => {
+                for export in reader {
+                    let export = export.map_err(|_| ParseError::WasmParseError)?;
+                    if export.kind == ComponentExternalKind::Type {
+                        types.push(None);
+                    }
+                }
+            }
+            (Payload::ComponentImportSection(reader), Some(Scope::Component { types })) => {
+                for import in reader {
+                    let import = import.map_err(|_| ParseError::WasmParseError)?;
+                    let name = import.name.0;
+                    match import.ty {
+                        ComponentTypeRef::Type(_) => types.push(None),
+                        // The imports of nested components are satisfied by their parents.
+                        _ if !is_outermost => {}
+                        ComponentTypeRef::Instance(index) => {
+                            imports.interfaces.push(name);
+                            let funcs = types
+                                .get(index as usize)
+                                .ok_or(ParseError::WasmParseError)?
+                                .as_deref()
+                                .ok_or(ParseError::InvalidImportType)?;
+                            imports
+                                .funcs
+                                .extend(funcs.iter().map(|&func| ComponentImportFunc {
+                                    interface: name,
+                                    name: func,
+                                }));
+                        }
+                        ComponentTypeRef::Func(_) => imports.funcs.push(ComponentImportFunc {
+                            interface: "",
+                            name,
+                        }),
+                        _ => {}
                     }
-                    types.insert(offset, Rc::new(record_group.unwrap()));
                 }
             }
             _ => {}
         }
     }
+    Ok(imports)
+}
 
-    let import_funcs: Vec<ImportFunc> = import_funcs
-        .into_iter()
-        .map(|func| ImportFunc {
-            module: func.module,
-            name: func.name,
-            ty: types.get(&(func.type_ref as usize)).unwrap().clone(),
-        })
-        .collect();
+/// Imports which are not recognised as ABIs of the enabled WASI dialects,
+/// e.g., `proc_fork` of WASIX without the `wasix` feature.
+pub fn unknown_imports<'a, 'i>(imports: &'i [ImportFunc<'a>]) -> Vec<&'i ImportFunc<'a>> {
+    unknown_imports_among(imports, &[])
+}
 
-    // all imported functions' type should be C ABI type.
-    // for func in import_funcs.iter() {
-    //     if !func.is_c_abi() {
-    //         bail!("Imported function type is not C ABI type");
-    //     }
-    // }
-    Ok(import_funcs)
+/// Like [`unknown_imports`], but also recognises the `custom` descriptors of host ABIs.
+pub fn unknown_imports_among<'a, 'i>(
+    imports: &'i [ImportFunc<'a>],
+    custom: &[&[wasi_descriptor::ErasedDescriptor<'_>]],
+) -> Vec<&'i ImportFunc<'a>> {
+    imports
+        .iter()
+        .filter(|import| import.descriptor_among(custom).is_none())
+        .collect()
 }
 
 pub fn forbidden_imports<'a, 'i>(
@@ -140,9 +306,31 @@ pub fn forbidden_imports<'a, 'i>(
         .collect()
 }
 
+/// Functions imported by a component whose Rust names are in `blacklist`, e.g.,
+/// the `MUST_BE_KILLED_WASIS` of `policy!`.
+pub fn forbidden_component_imports<'a, 'i>(
+    imports: &'i [ComponentImportFunc<'a>],
+    blacklist: &'i [&str],
+) -> Vec<&'i ComponentImportFunc<'a>> {
+    let blacklist: BTreeSet<&str> = blacklist.iter().cloned().collect();
+    imports
+        .iter()
+        .filter(|import| {
+            import
+                .wasi_name()
+                .is_some_and(|name| blacklist.contains(name))
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
-    use super::parse_import_funcs;
+    use alloc::vec::Vec;
+
+    use super::{
+        forbidden_component_imports, forbidden_imports, is_component, parse_component_imports,
+        parse_import_funcs, unknown_imports, unknown_imports_among,
+    };
 
     #[test]
     fn parse_import_funcs_test() {
@@ -194,4 +382,166 @@ r#"(module $rust-672937185f5392fa.wasm
         assert_eq!(import_funcs[4].unwrap_func().params().len(), 1);
         assert_eq!(import_funcs[4].unwrap_func().params()[0], ValType::I32);
     }
+
+    #[test]
+    fn unknown_imports_test() {
+        let wasm_binary = wat::parse_str(
+            r#"(module
+    (import "wasi_snapshot_preview1" "fd_write" (func (param i32 i32 i32 i32) (result i32)))
+    (import "wasix_32v1" "proc_fork" (func (param i32 i32) (result i32)))
+    (import "wasi" "thread-spawn" (func (param i32) (result i32)))
+    (import "wasi_unstable" "fd_seek" (func (param i32 i64 i32 i32) (result i32)))
+)"#,
+        )
+        .unwrap();
+        let import_funcs = parse_import_funcs(&wasm_binary).unwrap();
+        assert!(import_funcs[0].descriptor().is_some());
+        let unknown: Vec<&str> = unknown_imports(&import_funcs)
+            .iter()
+            .map(|import| import.name)
+            .collect();
+        let mut expected = Vec::new();
+        if cfg!(not(feature = "wasix")) {
+            expected.push("proc_fork");
+        }
+        if cfg!(not(feature = "wasi-threads")) {
+            expected.push("thread-spawn");
+        }
+        if cfg!(not(feature = "p0")) {
+            expected.push("fd_seek");
+        }
+        assert_eq!(unknown, expected);
+    }
+
+    #[allow(dead_code)]
+    mod host {
+        wasi::declare_host_abis!("env";
+            kv_get(key_ptr, key_len, val_ptr);
+        );
+    }
+
+    #[test]
+    fn custom_imports_test() {
+        let wasm_binary = wat::parse_str(
+            r#"(module
+    (import "env" "kv_get" (func (param i32 i32 i32) (result i32)))
+    (import "env" "kv_put" (func (param i32 i32 i32 i32) (result i32)))
+    (import "wasi_snapshot_preview1" "fd_write" (func (param i32 i32 i32 i32) (result i32)))
+)"#,
+        )
+        .unwrap();
+        let import_funcs = parse_import_funcs(&wasm_binary).unwrap();
+        let custom: &[&[_]] = &[&host::DESCRIPTORS];
+        assert!(import_funcs[0].descriptor().is_none());
+        assert_eq!(
+            import_funcs[0].descriptor_among(custom),
+            host::descriptor_by_name("env", "kv_get")
+        );
+        assert!(import_funcs[2].descriptor_among(custom).is_some());
+        let unknown: Vec<&str> = unknown_imports_among(&import_funcs, custom)
+            .iter()
+            .map(|import| import.name)
+            .collect();
+        assert_eq!(unknown, ["kv_put"]);
+    }
+
+    #[test]
+    fn parse_component_imports_test() {
+        let wasm_binary = wat::parse_str(
+            r#"(component
+    (type $random (instance
+        (type $u64 (func (result u64)))
+        (export "get-random-u64" (func (type $u64)))
+        (type $bytes (func (param "len" u64) (result (list u8))))
+        (export "get-random-bytes" (func (type $bytes)))
+    ))
+    (import "wasi:random/random@0.2.0" (instance (type $random)))
+    (type $environment (instance
+        (type $arguments (func (result (list string))))
+        (export "get-arguments" (func (type $arguments)))
+    ))
+    (import "wasi:cli/environment@0.2.0" (instance (type $environment)))
+    (component $inner
+        (import "wasi:clocks/wall-clock@0.2.0" (instance (export "resolution" (func))))
+        (core module (import "wasi:clocks/wall-clock@0.2.0" "resolution" (func)))
+    )
+    (core module $adapter
+        (import "wasi:random/random@0.2.0" "get-random-u64" (func (result i64)))
+        (import "env" "memory" (memory 1))
+        (func (export "random_get") (param i32 i32) (result i32) i32.const 0)
+    )
+    (core module $main
+        (import "wasi_snapshot_preview1" "random_get" (func (param i32 i32) (result i32)))
+        (import "wasi:cli/environment@0.2.0" "get-arguments" (func (param i32)))
+        (import "" "0" (func (param i32)))
+    )
+)"#,
+        )
+        .unwrap();
+        assert!(is_component(&wasm_binary));
+        let imports = parse_component_imports(&wasm_binary).unwrap();
+        assert_eq!(
+            imports.interfaces,
+            ["wasi:random/random@0.2.0", "wasi:cli/environment@0.2.0"]
+        );
+        let funcs: Vec<_> = imports
+            .funcs
+            .iter()
+            .map(|func| (func.interface, func.name))
+            .collect();
+        assert_eq!(
+            funcs,
+            [
+                ("wasi:random/random@0.2.0", "get-random-u64"),
+                ("wasi:random/random@0.2.0", "get-random-bytes"),
+                ("wasi:cli/environment@0.2.0", "get-arguments"),
+            ]
+        );
+
+        let core_funcs = parse_import_funcs(&wasm_binary).unwrap();
+        let names: Vec<_> = core_funcs
+            .iter()
+            .map(|func| (func.module, func.name))
+            .collect();
+        assert_eq!(
+            names,
+            [
+                ("wasi:clocks/wall-clock@0.2.0", "resolution"),
+                ("wasi:random/random@0.2.0", "get-random-u64"),
+                ("wasi_snapshot_preview1", "random_get"),
+                ("wasi:cli/environment@0.2.0", "get-arguments"),
+            ]
+        );
+        assert_eq!(core_funcs[2].unwrap_func().params().len(), 2);
+        assert_eq!(forbidden_imports(&core_funcs, &["random_get"]).len(), 1);
+
+        // the lowered imports of the interfaces are WASI 0.2 functions
+        #[cfg(feature = "p2")]
+        {
+            let lowered: Vec<_> = core_funcs
+                .iter()
+                .filter(|func| func.module.starts_with("wasi:"))
+                .collect();
+            assert_eq!(lowered.len(), 3);
+            assert!(lowered.iter().all(|func| func.descriptor().is_some()));
+            assert_eq!(
+                unknown_imports(&core_funcs)
+                    .iter()
+                    .map(|func| func.name)
+                    .collect::<Vec<_>>(),
+                Vec::<&str>::new()
+            );
+            let random_u64 = imports.funcs[0].descriptor().unwrap();
+            assert_eq!(Some(random_u64), core_funcs[1].descriptor());
+            assert!(imports.funcs.iter().all(|func| func.descriptor().is_some()));
+            assert_eq!(imports.funcs[1].wasi_name(), Some("get_random_bytes"));
+            let forbidden = forbidden_component_imports(&imports.funcs, &["get_random_bytes"]);
+            assert_eq!(forbidden, [&imports.funcs[1]]);
+        }
+        #[cfg(not(feature = "p2"))]
+        {
+            assert!(imports.funcs.iter().all(|func| func.descriptor().is_none()));
+            assert!(forbidden_component_imports(&imports.funcs, &["get_random_bytes"]).is_empty());
+        }
+    }
 }
@@ -1,4 +1,6 @@
-use core::cmp::Ordering;
+use core::{cmp::Ordering, fmt};
+
+use super::{emulate::Emulator, memory::GuestMemoryMut};
 
 pub type WasiErrno = u16;
 
@@ -7,6 +9,13 @@ pub type WasiErrno = u16;
 pub enum Action {
     Allow,
     Log,
+    /// Make the WASI call with its params rewritten, e.g., with `buf_len` of
+    /// `random_get` clamped. The rewrites of all the satisfied statements are
+    /// applied in order.
+    Rewrite(Rewrite),
+    /// Answer the WASI call by an [`Emulator`] in place of the host, e.g., with a
+    /// fixed `clock_time_get`, without making it.
+    Emulate(Emulate),
     /// Return the WASI call with a user-defined errno.
     ReturnErrno(WasiErrno),
     /// Terminate the WASM task.
@@ -24,6 +33,78 @@ impl Default for Action {
     }
 }
 
+/// Rewrites the raw params of a call before it is made, and the memory of the
+/// guest behind them if needed, e.g., the length of an iovec of `fd_read`.
+///
+/// The rewrite returns whether the call can be made as rewritten, and it is
+/// failed by the host otherwise, e.g., if the rewrite needs the memory but none is
+/// passed to [`Rewrite::apply`].
+#[derive(Clone, Copy)]
+pub struct Rewrite {
+    /// Names the rewrite, e.g., in logs. Rewrites are equal if both their names
+    /// and their functions are.
+    pub name: &'static str,
+    rewrite: fn(&mut [i64], Option<&mut dyn GuestMemoryMut>) -> bool,
+}
+impl Rewrite {
+    pub const fn new(
+        name: &'static str,
+        rewrite: fn(&mut [i64], Option<&mut dyn GuestMemoryMut>) -> bool,
+    ) -> Self {
+        Self { name, rewrite }
+    }
+
+    /// Rewrites `params`, the raw args of the call, and `memory` if passed,
+    /// returning whether the call can be made as rewritten.
+    pub fn apply(&self, params: &mut [i64], memory: Option<&mut dyn GuestMemoryMut>) -> bool {
+        (self.rewrite)(params, memory)
+    }
+}
+impl PartialEq for Rewrite {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name && self.rewrite as usize == other.rewrite as usize
+    }
+}
+impl Eq for Rewrite {}
+impl fmt::Debug for Rewrite {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Rewrite").field(&self.name).finish()
+    }
+}
+
+/// Answers a call in place of the host, by an [`Emulator`] writing the results
+/// into the memory of the guest, and returning the errno of the call.
+#[derive(Clone, Copy)]
+pub struct Emulate {
+    /// Names the emulation, e.g., in logs.
+    pub name: &'static str,
+    emulator: &'static dyn Emulator,
+}
+impl Emulate {
+    pub const fn new(name: &'static str, emulator: &'static dyn Emulator) -> Self {
+        Self { name, emulator }
+    }
+
+    /// Answers the call on `params`, its raw args, returning its errno.
+    pub fn apply(&self, params: &[i64], memory: &mut dyn GuestMemoryMut) -> WasiErrno {
+        self.emulator.emulate(params, memory)
+    }
+}
+impl PartialEq for Emulate {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name && core::ptr::addr_eq(self.emulator, other.emulator)
+    }
+}
+impl Eq for Emulate {}
+impl fmt::Debug for Emulate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Emulate").field(&self.name).finish()
+    }
+}
+
+/// Actions are ordered by how far they depart from the call made by the guest,
+/// where a rewritten call is still made, and an emulated one is answered, unlike a
+/// returned one. Errnos are not comparable, nor are distinct rewrites or emulations.
 impl PartialOrd for Action {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
         match (self, other) {
@@ -33,6 +114,12 @@ impl PartialOrd for Action {
             (Self::Log, Self::Log) => Some(Ordering::Equal),
             (Self::Log, _) => Some(Ordering::Less),
             (_, Self::Log) => Some(Ordering::Greater),
+            (Self::Rewrite(a), Self::Rewrite(b)) => (a == b).then_some(Ordering::Equal),
+            (Self::Rewrite(_), _) => Some(Ordering::Less),
+            (_, Self::Rewrite(_)) => Some(Ordering::Greater),
+            (Self::Emulate(a), Self::Emulate(b)) => (a == b).then_some(Ordering::Equal),
+            (Self::Emulate(_), _) => Some(Ordering::Less),
+            (_, Self::Emulate(_)) => Some(Ordering::Greater),
             (Self::ReturnErrno(_), Self::ReturnErrno(_)) => None,
             (Self::ReturnErrno(_), _) => Some(Ordering::Less),
             (_, Self::ReturnErrno(_)) => Some(Ordering::Greater),
@@ -57,5 +144,55 @@ mod test {
         assert!(Action::Allow < Action::Log);
         assert!(Action::Log < Action::ReturnErrno(0));
         assert!(Action::ReturnErrno(0) < Action::Kill);
+
+        const CLAMP: Rewrite = Rewrite::new("clamp", |params, _| {
+            params[1] = params[1].min(256);
+            true
+        });
+        const DROP: Rewrite = Rewrite::new("drop", |_, _| false);
+        assert!(Action::Log < Action::Rewrite(CLAMP));
+        assert!(Action::Rewrite(CLAMP) < Action::ReturnErrno(0));
+        assert!(Action::Rewrite(CLAMP) <= Action::Rewrite(CLAMP));
+        assert_eq!(
+            Action::ReturnErrno(1).partial_cmp(&Action::ReturnErrno(1)),
+            None
+        );
+        assert_eq!(
+            Action::Rewrite(CLAMP).partial_cmp(&Action::Rewrite(DROP)),
+            None
+        );
+        // a rewrite named after another is still told apart by its function
+        const FAKE_CLAMP: Rewrite = Rewrite::new("clamp", |_, _| false);
+        assert_ne!(CLAMP, FAKE_CLAMP);
+        assert_eq!(CLAMP, CLAMP);
+    }
+
+    #[test]
+    fn rewrite_params() {
+        use crate::policy::memory::GuestMemory;
+
+        // caps the length of the first iovec of `fd_read`
+        const CAP_IOVEC: Rewrite = Rewrite::new("cap_iovec", |params, memory| {
+            let Some(memory) = memory else {
+                return false;
+            };
+            let len_ptr = params[1] as u32 + 4;
+            let Some(len) = memory.read(len_ptr, 4) else {
+                return false;
+            };
+            let len = u32::from_le_bytes(len.try_into().unwrap()).min(16);
+            memory.write(len_ptr, &len.to_le_bytes()).is_some()
+        });
+        let mut memory = alloc::vec![0u8, 0, 0, 0, 64, 0, 0, 0];
+        let mut params = [3, 0, 1, 32];
+        assert!(!CAP_IOVEC.apply(&mut params, None));
+        assert!(CAP_IOVEC.apply(&mut params, Some(&mut memory)));
+        assert_eq!(memory.read(4, 4), Some(&[16, 0, 0, 0][..]));
+        params[1] = 8;
+        assert!(!CAP_IOVEC.apply(&mut params, Some(&mut memory)));
+        assert_eq!(
+            alloc::format!("{:?}", Action::Rewrite(CAP_IOVEC)),
+            "Rewrite(Rewrite(\"cap_iovec\"))"
+        );
     }
 }
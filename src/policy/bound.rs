@@ -1,6 +1,7 @@
 use alloc::{boxed::Box, sync::Arc};
 use core::{fmt::Debug, marker::PhantomData};
 
+use wasi_descriptor::FromRawArg;
 use wasi_guard_macros::all_tuples;
 
 use crate::util::Tuple;
@@ -14,7 +15,7 @@ macro_rules! impl_predicate_param_for_tuple {
         {}
     };
 }
-all_tuples!(impl_predicate_param_for_tuple[0, 10]: P);
+all_tuples!(impl_predicate_param_for_tuple[0, 12]: P);
 
 macro_rules! impl_predicate_param {
     ($type:ty) => {
@@ -27,10 +28,120 @@ macro_rules! impl_predicate_param {
 }
 impl_predicate_param!(bool, i8, u8, i16, u16, i32, u32, i64, u64, f32, f64);
 impl_predicate_param!([u8; 1 << 0], [u8; 1 << 1], [u8; 1 << 2], [u8; 1 << 3]);
+impl_predicate_param!(
+    wasi::p1::Advice,
+    wasi::p1::Clockid,
+    wasi::p1::Errno,
+    wasi::p1::Eventtype,
+    wasi::p1::Filetype,
+    wasi::p1::Preopentype,
+    wasi::p1::Signal,
+    wasi::p1::Whence
+);
+impl_predicate_param!(
+    wasi::p1::Eventrwflags,
+    wasi::p1::Fdflags,
+    wasi::p1::Fstflags,
+    wasi::p1::Lookupflags,
+    wasi::p1::Oflags,
+    wasi::p1::Rights,
+    wasi::p1::Riflags,
+    wasi::p1::Roflags,
+    wasi::p1::Sdflags,
+    wasi::p1::Subclockflags
+);
+
+/// Params converted element by element by [`FromRawArg`] from `Raw`, the raw params
+/// of an ABI, e.g., `wasi::fd_seek_params_raw_t`.
+pub trait FromRawParams<Raw>: PredicateParams {
+    fn from_raw_params(raw: Raw) -> Self;
+}
+macro_rules! impl_from_raw_params {
+    ($(($P:ident, $R:ident)),*) => {
+        impl<$($P, $R),*> FromRawParams<( $($R,)* )> for ( $($P,)* )
+        where $( $P : PredicateParam + FromRawArg<$R>, )*
+        {
+            #[allow(non_snake_case, clippy::unused_unit)]
+            fn from_raw_params(( $($R,)* ): ( $($R,)* )) -> Self {
+                ( $($P::from_raw_arg($R),)* )
+            }
+        }
+    };
+}
+all_tuples!(impl_from_raw_params[0, 12]: P, R);
 
 pub trait PredicateFunction<'pred, Params: PredicateParams>: Sync + Send + 'pred {
     fn call(&self, params: Params) -> bool;
-    // TODO: automatic param type conversion
+}
+
+/// A predicate which also sees `Ctx`, the context of the host making the call,
+/// e.g., the tenant, the hash of the module or the instance.
+pub trait ContextPredicate<'pred, Ctx, Params: PredicateParams>: Sync + Send + 'pred {
+    fn call_in(&self, ctx: &Ctx, params: Params) -> bool;
+}
+
+/// A closure taking the context before the params, e.g.,
+/// `WithContext(|ctx: &GuestCtx, fd: u32| ctx.allowed_fds.contains(&fd))`.
+#[derive(Clone, Copy)]
+pub struct WithContext<F>(pub F);
+
+/// A [`PredicateFunction`], which ignores the context.
+struct WithoutContext<'pred, Params: PredicateParams>(Arc<dyn PredicateFunction<'pred, Params>>);
+impl<'pred, Ctx, Params: PredicateParams + 'pred> ContextPredicate<'pred, Ctx, Params>
+    for WithoutContext<'pred, Params>
+{
+    fn call_in(&self, _: &Ctx, params: Params) -> bool {
+        self.0.call(params)
+    }
+}
+
+/// A predicate on the empty context, e.g., of a statement without the context,
+/// called in any context.
+struct InAnyContext<'pred, Params: PredicateParams>(Arc<dyn ContextPredicate<'pred, (), Params>>);
+impl<'pred, Ctx, Params: PredicateParams + 'pred> ContextPredicate<'pred, Ctx, Params>
+    for InAnyContext<'pred, Params>
+{
+    fn call_in(&self, _: &Ctx, params: Params) -> bool {
+        self.0.call_in(&(), params)
+    }
+}
+
+/// Both or either of two predicates in the same context.
+enum ContextComposition<'pred, Ctx, Params: PredicateParams> {
+    And(
+        Arc<dyn ContextPredicate<'pred, Ctx, Params>>,
+        Arc<dyn ContextPredicate<'pred, Ctx, Params>>,
+    ),
+    Or(
+        Arc<dyn ContextPredicate<'pred, Ctx, Params>>,
+        Arc<dyn ContextPredicate<'pred, Ctx, Params>>,
+    ),
+}
+impl<'pred, Ctx: 'pred, Params: PredicateParams + Clone + 'pred>
+    ContextPredicate<'pred, Ctx, Params> for ContextComposition<'pred, Ctx, Params>
+{
+    fn call_in(&self, ctx: &Ctx, params: Params) -> bool {
+        match self {
+            Self::And(a, b) => a.call_in(ctx, params.clone()) && b.call_in(ctx, params),
+            Self::Or(a, b) => a.call_in(ctx, params.clone()) || b.call_in(ctx, params),
+        }
+    }
+}
+
+/// A predicate on `Params`, called on raw params converted into them.
+struct FromRawPredicate<'pred, Ctx, Params: PredicateParams, Raw> {
+    predicate: Arc<dyn ContextPredicate<'pred, Ctx, Params>>,
+    _raw: PhantomData<fn(Raw)>,
+}
+impl<'pred, Ctx: 'pred, Params, Raw> ContextPredicate<'pred, Ctx, Raw>
+    for FromRawPredicate<'pred, Ctx, Params, Raw>
+where
+    Params: FromRawParams<Raw> + 'pred,
+    Raw: PredicateParams + 'pred,
+{
+    fn call_in(&self, ctx: &Ctx, raw: Raw) -> bool {
+        self.predicate.call_in(ctx, Params::from_raw_params(raw))
+    }
 }
 
 macro_rules! impl_predicate_function_for_ptr {
@@ -78,7 +189,22 @@ macro_rules! impl_predicate {
         }
     };
 }
-all_tuples!(impl_predicate[0, 10]: P);
+all_tuples!(impl_predicate[0, 12]: P);
+
+macro_rules! impl_context_predicate {
+    ($($P:ident),*) => {
+        impl<'pred, F, Ctx, $($P,)*> ContextPredicate<'pred, Ctx, ( $($P,)* )> for WithContext<F>
+            where F: Sync + Send + ::core::ops::Fn(&Ctx, $($P,)*) -> bool + 'pred,
+                ( $($P,)* ) : $crate::policy::bound::PredicateParams,
+        {
+            #[allow(non_snake_case, clippy::too_many_arguments)]
+            fn call_in(&self, ctx: &Ctx, ($($P,)*): ( $($P,)* )) -> bool {
+                (self.0)(ctx, $($P),*)
+            }
+        }
+    };
+}
+all_tuples!(impl_context_predicate[0, 11]: P);
 
 pub enum PredicateComposition<'pred, Params, A, B>
 where
@@ -146,51 +272,70 @@ where
     }
 }
 
-#[derive(Clone)]
-pub struct AbiArgBound<'bound, Params: PredicateParams> {
-    predicate: Arc<dyn PredicateFunction<'bound, Params>>,
+/// A bound on `Params`, in `Ctx`, the context of the host making the call,
+/// which is ignored by the bounds converted from a [`PredicateFunction`].
+pub struct AbiArgBound<'bound, Params: PredicateParams, Ctx = ()> {
+    predicate: Arc<dyn ContextPredicate<'bound, Ctx, Params>>,
 }
-// Safety: PredicateFunction<Params: PredicateParams>: Sync + Send
-unsafe impl<'bound, Params: PredicateParams> Sync for AbiArgBound<'bound, Params> {}
-unsafe impl<'bound, Params: PredicateParams> Send for AbiArgBound<'bound, Params> {}
-
-impl<'bound, Params: PredicateParams> AbiArgBound<'bound, Params> {
-    fn from_predicate(predicate: impl PredicateFunction<'bound, Params>) -> Self {
+impl<Params: PredicateParams, Ctx> Clone for AbiArgBound<'_, Params, Ctx> {
+    fn clone(&self) -> Self {
         Self {
-            predicate: Arc::new(predicate),
+            predicate: self.predicate.clone(),
         }
     }
+}
+// Safety: ContextPredicate<Ctx, Params: PredicateParams>: Sync + Send
+unsafe impl<'bound, Params: PredicateParams, Ctx> Sync for AbiArgBound<'bound, Params, Ctx> {}
+unsafe impl<'bound, Params: PredicateParams, Ctx> Send for AbiArgBound<'bound, Params, Ctx> {}
+
+impl<'bound, Params: PredicateParams + 'bound, Ctx> AbiArgBound<'bound, Params, Ctx> {
+    fn from_predicate(predicate: impl PredicateFunction<'bound, Params>) -> Self {
+        Self::from_boxed_predicate(Box::new(predicate))
+    }
     fn from_boxed_predicate(predicate: Box<dyn PredicateFunction<'bound, Params>>) -> Self {
+        Self::from_context_predicate(WithoutContext(predicate.into()))
+    }
+    fn from_context_predicate(predicate: impl ContextPredicate<'bound, Ctx, Params>) -> Self {
         Self {
-            predicate: predicate.into(),
+            predicate: Arc::new(predicate),
         }
     }
 }
-impl<'bound, Params: PredicateParams + Clone + 'bound> AbiArgBound<'bound, Params> {
+impl<'bound, Params: PredicateParams + 'bound, Ctx: 'bound> AbiArgBound<'bound, Params, Ctx> {
+    /// The bound on `Raw`, the raw params of the ABI, converted into `Params`
+    /// before being checked.
+    pub fn into_raw<Raw>(self) -> AbiArgBound<'bound, Raw, Ctx>
+    where
+        Params: FromRawParams<Raw>,
+        Raw: PredicateParams + 'bound,
+    {
+        AbiArgBound::from_context_predicate(FromRawPredicate {
+            predicate: self.predicate,
+            _raw: PhantomData,
+        })
+    }
+}
+impl<'bound, Params: PredicateParams + 'bound> AbiArgBound<'bound, Params> {
+    /// The bound checked in any context, which it ignores.
+    pub fn in_context<Ctx>(self) -> AbiArgBound<'bound, Params, Ctx> {
+        AbiArgBound::from_context_predicate(InAnyContext(self.predicate))
+    }
+}
+impl<'bound, Params: PredicateParams + Clone + 'bound, Ctx: 'bound>
+    AbiArgBound<'bound, Params, Ctx>
+{
     pub fn and(self, other: Self) -> Self {
-        let Self {
-            predicate: this, ..
-        } = self;
-        let Self {
-            predicate: other, ..
-        } = other;
-        Self::from_predicate(PredicateComposition::all(this, other))
+        Self::from_context_predicate(ContextComposition::And(self.predicate, other.predicate))
     }
     pub fn or(self, other: Self) -> Self {
-        let Self {
-            predicate: this, ..
-        } = self;
-        let Self {
-            predicate: other, ..
-        } = other;
-        Self::from_predicate(PredicateComposition::any(this, other))
+        Self::from_context_predicate(ContextComposition::Or(self.predicate, other.predicate))
     }
 }
 
 macro_rules! impl_from_fn_for_bound {
     ($($P:ident),*) => {
-        impl<'bound, Predicate, $($P,)*> From<Predicate> for AbiArgBound<'bound, ( $($P,)* )>
-        where ( $($P,)* ) : $crate::policy::bound::PredicateParams,
+        impl<'bound, Predicate, Ctx, $($P,)*> From<Predicate> for AbiArgBound<'bound, ( $($P,)* ), Ctx>
+        where ( $($P,)* ) : $crate::policy::bound::PredicateParams + 'bound,
             Predicate : 'static + Fn($($P,)*) -> bool + Sync + Send,
         {
             fn from(predicate: Predicate) -> Self {
@@ -198,8 +343,17 @@ macro_rules! impl_from_fn_for_bound {
             }
         }
 
-        impl<'bound, $($P,)*> From<Box<dyn PredicateFunction<'bound, ($($P,)*)>>> for AbiArgBound<'bound, ( $($P,)* )>
-        where ( $($P,)* ) : $crate::policy::bound::PredicateParams,
+        impl<'bound, F, Ctx, $($P,)*> From<WithContext<F>> for AbiArgBound<'bound, ( $($P,)* ), Ctx>
+        where ( $($P,)* ) : $crate::policy::bound::PredicateParams + 'bound,
+            WithContext<F> : ContextPredicate<'bound, Ctx, ( $($P,)* )>,
+        {
+            fn from(predicate: WithContext<F>) -> Self {
+                Self::from_context_predicate(predicate)
+            }
+        }
+
+        impl<'bound, Ctx, $($P,)*> From<Box<dyn PredicateFunction<'bound, ($($P,)*)>>> for AbiArgBound<'bound, ( $($P,)* ), Ctx>
+        where ( $($P,)* ) : $crate::policy::bound::PredicateParams + 'bound,
         {
             fn from(predicate: Box<dyn PredicateFunction<'bound, ($($P,)*)>>) -> Self {
                 Self::from_boxed_predicate(predicate)
@@ -207,7 +361,7 @@ macro_rules! impl_from_fn_for_bound {
         }
     };
 }
-all_tuples!(impl_from_fn_for_bound[0,10]: P);
+all_tuples!(impl_from_fn_for_bound[0,12]: P);
 
 macro_rules! impl_check_for_bound {
     ($($P:ident),*) => {
@@ -216,13 +370,21 @@ macro_rules! impl_check_for_bound {
         {
             #[allow(non_snake_case, clippy::too_many_arguments)]
             pub fn check(&self, params: ( $($P,)* )) -> bool {
-                // PredicateFunction::<'bound, ( $($P,)* )>::call(&self.predicate, params)
-                self.predicate.call(params)
+                self.predicate.call_in(&(), params)
+            }
+        }
+        impl<'bound, Ctx: 'bound, $($P,)*> AbiArgBound<'bound, ( $($P,)* ), Ctx>
+        where ( $($P,)* ) : $crate::policy::bound::PredicateParams + 'bound,
+        {
+            /// Checks the bound on `params` in `ctx`, the context of the host making the call.
+            #[allow(non_snake_case, clippy::too_many_arguments)]
+            pub fn check_in(&self, ctx: &Ctx, params: ( $($P,)* )) -> bool {
+                self.predicate.call_in(ctx, params)
             }
         }
     };
 }
-all_tuples!(impl_check_for_bound[0,10]: P);
+all_tuples!(impl_check_for_bound[0,12]: P);
 
 #[cfg(test)]
 mod test {
@@ -349,6 +511,33 @@ mod test {
         assert!(!pred_2_2.call((0, 0)));
     }
 
+    #[test]
+    fn bound_in_context() {
+        use alloc::vec;
+
+        use crate::policy::bound::WithContext;
+
+        struct GuestCtx {
+            allowed_fds: Vec<u32>,
+        }
+        let bound: AbiArgBound<(u32, u32), GuestCtx> =
+            WithContext(|ctx: &GuestCtx, fd: u32, _: u32| ctx.allowed_fds.contains(&fd)).into();
+        let ctx = GuestCtx {
+            allowed_fds: vec![1, 2],
+        };
+        assert!(bound.check_in(&ctx, (1, 0)));
+        assert!(!bound.check_in(&ctx, (3, 0)));
+
+        // context-free bounds ignore it
+        let bound = bound.and((|_: u32, len: u32| len < 16).into());
+        assert!(bound.check_in(&ctx, (2, 8)));
+        assert!(!bound.check_in(&ctx, (2, 32)));
+        let bound: AbiArgBound<(u32, u32)> = (|fd: u32, _: u32| fd > 2).into();
+        let bound = bound.in_context::<GuestCtx>();
+        assert!(bound.check_in(&ctx, (3, 0)));
+        assert!(bound.into_raw::<(i32, i32)>().check_in(&ctx, (-1, 0)));
+    }
+
     #[test]
     fn bound_compositions() {
         use crate::policy::bound::AbiArgBound;
@@ -0,0 +1,496 @@
+//! Emulators answering calls in place of the host, for [`Action::Emulate`], e.g.,
+//! for determinism or privacy:
+//!
+//! ```ignore
+//! static CLOCK: FixedClock = FixedClock(1_700_000_000_000_000_000);
+//! static RANDOM: SeededRandom = SeededRandom::new(42);
+//! const ENV: Strings = Strings::get(&["LANG=C"]);
+//! const ENV_SIZES: Strings = Strings::sizes_get(&["LANG=C"]);
+//! policy! {
+//!    default = allow;
+//!    emulate(Emulate::new("clock", &CLOCK)) clock_time_get;
+//!    emulate(Emulate::new("random", &RANDOM)) random_get;
+//!    emulate(Emulate::new("environ", &ENV)) environ_get;
+//!    emulate(Emulate::new("environ_sizes", &ENV_SIZES)) environ_sizes_get;
+//! };
+//! ```
+//!
+//! [`Action::Emulate`]: super::action::Action::Emulate
+
+use core::sync::atomic::{AtomicU64, Ordering};
+
+use super::{action::WasiErrno, memory::GuestMemoryMut};
+
+const SUCCESS: WasiErrno = wasi::ERRNO_SUCCESS.raw();
+/// Returned if the results do not fit in the memory of the guest.
+const FAULT: WasiErrno = wasi::ERRNO_FAULT.raw();
+const INVAL: WasiErrno = wasi::ERRNO_INVAL.raw();
+const NOTSUP: WasiErrno = wasi::ERRNO_NOTSUP.raw();
+
+/// Answers a call on its raw params, writing the results into `memory`, and
+/// returning the errno of the call.
+pub trait Emulator: Sync {
+    fn emulate(&self, params: &[i64], memory: &mut dyn GuestMemoryMut) -> WasiErrno;
+}
+impl<F: Fn(&[i64], &mut dyn GuestMemoryMut) -> WasiErrno + Sync> Emulator for F {
+    fn emulate(&self, params: &[i64], memory: &mut dyn GuestMemoryMut) -> WasiErrno {
+        self(params, memory)
+    }
+}
+
+/// The raw param `n`, or `INVAL` if the call passes fewer params.
+fn param(params: &[i64], n: usize) -> Result<i64, WasiErrno> {
+    params.get(n).copied().ok_or(INVAL)
+}
+
+/// The address held by `raw`, a raw `i32` arg, whether zero- or sign-extended,
+/// or `FAULT` if it holds none.
+fn addr(raw: i64) -> Result<u32, WasiErrno> {
+    u32::try_from(raw)
+        .or_else(|_| i32::try_from(raw).map(|raw| raw as u32))
+        .map_err(|_| FAULT)
+}
+
+/// The address `offset` bytes past `addr`, or `FAULT` if it overflows.
+fn offset(addr: u32, offset: usize) -> Result<u32, WasiErrno> {
+    u32::try_from(offset)
+        .ok()
+        .and_then(|offset| addr.checked_add(offset))
+        .ok_or(FAULT)
+}
+
+/// Writes `bytes` at the address `ptr`, or tells it is a fault.
+fn write(memory: &mut dyn GuestMemoryMut, ptr: u32, bytes: &[u8]) -> Result<(), WasiErrno> {
+    memory.write(ptr, bytes).ok_or(FAULT)
+}
+
+/// Writes `time` at the address held by the raw param `n`.
+fn write_time(params: &[i64], n: usize, memory: &mut dyn GuestMemoryMut, time: u64) -> WasiErrno {
+    let written = param(params, n)
+        .and_then(addr)
+        .and_then(|ptr| write(memory, ptr, &time.to_le_bytes()));
+    match written {
+        Ok(()) => SUCCESS,
+        Err(errno) => errno,
+    }
+}
+
+/// `clock_time_get` always telling the same time, in nanoseconds.
+pub struct FixedClock(pub u64);
+impl Emulator for FixedClock {
+    fn emulate(&self, params: &[i64], memory: &mut dyn GuestMemoryMut) -> WasiErrno {
+        // (id, precision, timestamp_ptr)
+        write_time(params, 2, memory, self.0)
+    }
+}
+
+/// How far a [`VirtualClock`] advances, in nanoseconds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tick {
+    /// Per `clock_time_get`, which tells the time before it advances.
+    PerCall(u64),
+    /// Per unit of fuel consumed by the guest, as told by
+    /// [`VirtualClock::set_fuel`].
+    PerFuel(u64),
+}
+
+/// `clock_time_get` telling a virtual time, from `start` advanced by `tick`,
+/// whatever the clock asked for.
+pub struct VirtualClock {
+    start: u64,
+    tick: Tick,
+    /// The calls, or the fuel consumed, so far.
+    ticks: AtomicU64,
+}
+impl VirtualClock {
+    pub const fn new(start: u64, tick: Tick) -> Self {
+        Self {
+            start,
+            tick,
+            ticks: AtomicU64::new(0),
+        }
+    }
+
+    /// The time told by the next `clock_time_get`.
+    pub fn now(&self) -> u64 {
+        self.time_at(self.ticks.load(Ordering::Relaxed))
+    }
+
+    /// Advances the clock to `fuel`, the fuel consumed by the guest so far, if it
+    /// ticks [`Tick::PerFuel`]. The clock never goes back.
+    pub fn set_fuel(&self, fuel: u64) {
+        if let Tick::PerFuel(_) = self.tick {
+            self.ticks.fetch_max(fuel, Ordering::Relaxed);
+        }
+    }
+
+    /// Advances the clock to `time` at least, e.g., past the timeouts the guest
+    /// sleeps on. The clock never goes back.
+    pub fn advance_to(&self, time: u64) {
+        let (Tick::PerCall(step) | Tick::PerFuel(step)) = self.tick;
+        if step == 0 || time <= self.start {
+            return;
+        }
+        let ticks = (time - self.start).div_ceil(step);
+        self.ticks.fetch_max(ticks, Ordering::Relaxed);
+    }
+
+    /// Restarts the clock from `start`, e.g., to replay the guest.
+    pub fn reset(&self) {
+        self.ticks.store(0, Ordering::Relaxed);
+    }
+
+    fn time_at(&self, ticks: u64) -> u64 {
+        let (Tick::PerCall(step) | Tick::PerFuel(step)) = self.tick;
+        self.start.saturating_add(ticks.saturating_mul(step))
+    }
+}
+impl Emulator for VirtualClock {
+    fn emulate(&self, params: &[i64], memory: &mut dyn GuestMemoryMut) -> WasiErrno {
+        let time = match self.tick {
+            Tick::PerCall(_) => self.time_at(self.ticks.fetch_add(1, Ordering::Relaxed)),
+            Tick::PerFuel(_) => self.now(),
+        };
+        // (id, precision, timestamp_ptr)
+        write_time(params, 2, memory, time)
+    }
+}
+
+/// `clock_res_get` telling the same resolution of any clock, in nanoseconds.
+pub struct ClockResolution(pub u64);
+impl Emulator for ClockResolution {
+    fn emulate(&self, params: &[i64], memory: &mut dyn GuestMemoryMut) -> WasiErrno {
+        // (id, timestamp_ptr)
+        write_time(params, 1, memory, self.0)
+    }
+}
+
+/// `poll_oneoff` on timeouts only, all of which are taken as fired at once, so
+/// that a guest sleeping does not wait. Subscriptions to fds are not supported.
+pub struct PollTimeouts;
+impl PollTimeouts {
+    /// The sizes of a `subscription` and of an `event`.
+    const SUBSCRIPTION: usize = 48;
+    const EVENT: usize = 32;
+
+    /// Fires the timeouts, returning the latest of them, where a relative one is
+    /// taken from `now`.
+    fn poll(params: &[i64], memory: &mut dyn GuestMemoryMut, now: u64) -> Result<u64, WasiErrno> {
+        // (in_ptr, out_ptr, nsubscriptions, size_ptr)
+        let (subscriptions, events) = (addr(param(params, 0)?)?, addr(param(params, 1)?)?);
+        let n = u32::try_from(param(params, 2)?).map_err(|_| INVAL)?;
+        if n == 0 {
+            return Err(INVAL);
+        }
+        let mut latest = now;
+        for i in 0..n as usize {
+            let subscription = offset(subscriptions, i.saturating_mul(Self::SUBSCRIPTION))?;
+            let subscription = memory
+                .read(subscription, Self::SUBSCRIPTION as u32)
+                .ok_or(FAULT)?;
+            // the tag of the union, which is 0 for a clock
+            if subscription[8] != 0 {
+                return Err(NOTSUP);
+            }
+            // (id, timeout, precision, flags) of the clock from the offset 16
+            let timeout = u64::from_le_bytes(subscription[24..32].try_into().unwrap());
+            let abstime = subscription[40] & 1 != 0;
+            latest = latest.max(match abstime {
+                true => timeout,
+                false => now.saturating_add(timeout),
+            });
+            let mut event = [0u8; Self::EVENT];
+            event[..8].copy_from_slice(&subscription[..8]);
+            let event_ptr = offset(events, i.saturating_mul(Self::EVENT))?;
+            write(memory, event_ptr, &event)?;
+        }
+        write(memory, addr(param(params, 3)?)?, &n.to_le_bytes())?;
+        Ok(latest)
+    }
+}
+impl Emulator for PollTimeouts {
+    fn emulate(&self, params: &[i64], memory: &mut dyn GuestMemoryMut) -> WasiErrno {
+        match Self::poll(params, memory, 0) {
+            Ok(_) => SUCCESS,
+            Err(errno) => errno,
+        }
+    }
+}
+
+/// `poll_oneoff` on timeouts only, fired at once as by [`PollTimeouts`], but on a
+/// [`VirtualClock`] advanced to the latest of them, as if the guest had slept
+/// until then. It owns the clock, which [`clock`](Self::clock) lends to
+/// `clock_time_get`.
+pub struct VirtualSleep {
+    clock: VirtualClock,
+}
+impl VirtualSleep {
+    pub const fn new(clock: VirtualClock) -> Self {
+        Self { clock }
+    }
+
+    pub const fn clock(&self) -> &VirtualClock {
+        &self.clock
+    }
+}
+impl Emulator for VirtualSleep {
+    fn emulate(&self, params: &[i64], memory: &mut dyn GuestMemoryMut) -> WasiErrno {
+        match PollTimeouts::poll(params, memory, self.clock.now()) {
+            Ok(latest) => {
+                self.clock.advance_to(latest);
+                SUCCESS
+            }
+            Err(errno) => errno,
+        }
+    }
+}
+
+/// Any call succeeding without results, e.g., `sched_yield`.
+pub struct Succeed;
+impl Emulator for Succeed {
+    fn emulate(&self, _: &[i64], _: &mut dyn GuestMemoryMut) -> WasiErrno {
+        SUCCESS
+    }
+}
+
+/// `random_get` filling the buffer with a pseudo-random stream from a seed, by
+/// SplitMix64, which is not secure. Concurrent calls interleave the stream.
+pub struct SeededRandom {
+    state: AtomicU64,
+}
+impl SeededRandom {
+    pub const fn new(seed: u64) -> Self {
+        Self {
+            state: AtomicU64::new(seed),
+        }
+    }
+
+    /// Restarts the stream from `seed`, e.g., to replay the guest.
+    pub fn reset(&self, seed: u64) {
+        self.state.store(seed, Ordering::Relaxed);
+    }
+
+    fn next(&self) -> u64 {
+        const GAMMA: u64 = 0x9e37_79b9_7f4a_7c15;
+        let mut z = self
+            .state
+            .fetch_add(GAMMA, Ordering::Relaxed)
+            .wrapping_add(GAMMA);
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    fn fill(&self, params: &[i64], memory: &mut dyn GuestMemoryMut) -> Result<(), WasiErrno> {
+        // (buf_ptr, buf_len)
+        let mut ptr = addr(param(params, 0)?)?;
+        let mut len = u32::try_from(param(params, 1)?).map_err(|_| INVAL)?;
+        while len > 0 {
+            let bytes = self.next().to_le_bytes();
+            let n = len.min(8);
+            write(memory, ptr, &bytes[..n as usize])?;
+            len -= n;
+            if len > 0 {
+                ptr = offset(ptr, n as usize)?;
+            }
+        }
+        Ok(())
+    }
+}
+impl Emulator for SeededRandom {
+    fn emulate(&self, params: &[i64], memory: &mut dyn GuestMemoryMut) -> WasiErrno {
+        match self.fill(params, memory) {
+            Ok(()) => SUCCESS,
+            Err(errno) => errno,
+        }
+    }
+}
+
+/// A virtual list of strings, e.g., of `args_get` or of `environ_get`, answering
+/// the call itself, or that of its sizes, e.g., `environ_sizes_get`.
+#[derive(Clone, Copy)]
+pub struct Strings {
+    strings: &'static [&'static str],
+    sizes: bool,
+}
+impl Strings {
+    /// Answers `args_get` or `environ_get`, writing the pointers to the
+    /// NUL-terminated strings, and the strings into the buffer.
+    pub const fn get(strings: &'static [&'static str]) -> Self {
+        Self {
+            strings,
+            sizes: false,
+        }
+    }
+    /// Answers `args_sizes_get` or `environ_sizes_get`, writing the number of the
+    /// strings, and the size of the buffer holding them.
+    pub const fn sizes_get(strings: &'static [&'static str]) -> Self {
+        Self {
+            strings,
+            sizes: true,
+        }
+    }
+
+    fn write(&self, params: &[i64], memory: &mut dyn GuestMemoryMut) -> Result<(), WasiErrno> {
+        if self.sizes {
+            // (count_ptr, buf_size_ptr)
+            let size: usize = self.strings.iter().map(|s| s.len() + 1).sum();
+            let (count_ptr, size_ptr) = (addr(param(params, 0)?)?, addr(param(params, 1)?)?);
+            write(
+                memory,
+                count_ptr,
+                &(self.strings.len() as u32).to_le_bytes(),
+            )?;
+            return write(memory, size_ptr, &(size as u32).to_le_bytes());
+        }
+        // (ptrs_ptr, buf_ptr)
+        let (mut ptr, mut buf) = (addr(param(params, 0)?)?, addr(param(params, 1)?)?);
+        for (i, s) in self.strings.iter().enumerate() {
+            if i > 0 {
+                ptr = offset(ptr, 4)?;
+                buf = offset(buf, self.strings[i - 1].len() + 1)?;
+            }
+            write(memory, ptr, &buf.to_le_bytes())?;
+            write(memory, buf, s.as_bytes())?;
+            write(memory, offset(buf, s.len())?, &[0])?;
+        }
+        Ok(())
+    }
+}
+impl Emulator for Strings {
+    fn emulate(&self, params: &[i64], memory: &mut dyn GuestMemoryMut) -> WasiErrno {
+        match self.write(params, memory) {
+            Ok(()) => SUCCESS,
+            Err(errno) => errno,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use alloc::vec;
+
+    use super::*;
+    use crate::policy::memory::GuestMemory;
+
+    #[test]
+    fn fixed_clock() {
+        let mut memory = vec![0u8; 16];
+        assert_eq!(FixedClock(7).emulate(&[0, 1, 8], &mut memory), SUCCESS);
+        assert_eq!(memory.read(8, 8), Some(&7u64.to_le_bytes()[..]));
+        assert_eq!(FixedClock(7).emulate(&[0, 1, 12], &mut memory), FAULT);
+        // too few params, or a pointer out of the 32-bit memory
+        assert_eq!(FixedClock(7).emulate(&[0, 1], &mut memory), INVAL);
+        assert_eq!(FixedClock(7).emulate(&[0, 1, 1 << 32], &mut memory), FAULT);
+        assert_eq!(ClockResolution(1).emulate(&[0], &mut memory), INVAL);
+    }
+
+    #[test]
+    fn virtual_clock() {
+        let mut memory = vec![0u8; 8];
+        let time = |memory: &[u8]| u64::from_le_bytes(memory.try_into().unwrap());
+        let clock = VirtualClock::new(100, Tick::PerCall(10));
+        for expected in [100, 110, 120] {
+            assert_eq!(clock.emulate(&[1, 0, 0], &mut memory), SUCCESS);
+            assert_eq!(time(&memory), expected);
+        }
+        clock.set_fuel(1000);
+        assert_eq!(clock.now(), 130);
+        clock.advance_to(155);
+        assert_eq!(clock.now(), 160);
+        clock.advance_to(0);
+        assert_eq!(clock.now(), 160);
+        clock.reset();
+        assert_eq!(clock.now(), 100);
+
+        let clock = VirtualClock::new(100, Tick::PerFuel(2));
+        clock.emulate(&[0, 0, 0], &mut memory);
+        clock.emulate(&[0, 0, 0], &mut memory);
+        assert_eq!(time(&memory), 100);
+        clock.set_fuel(50);
+        clock.set_fuel(40);
+        assert_eq!(clock.emulate(&[0, 0, 0], &mut memory), SUCCESS);
+        assert_eq!(time(&memory), 200);
+        assert_eq!(clock.emulate(&[0, 0, 4], &mut memory), FAULT);
+    }
+
+    #[test]
+    fn poll_timeouts() {
+        // two subscriptions at 0, events at 96, and their number at 160
+        let mut memory = vec![0u8; 164];
+        memory[..8].copy_from_slice(&7u64.to_le_bytes());
+        memory[48..56].copy_from_slice(&9u64.to_le_bytes());
+        assert_eq!(PollTimeouts.emulate(&[0, 96, 2, 160], &mut memory), SUCCESS);
+        assert_eq!(memory.read(96, 8), Some(&7u64.to_le_bytes()[..]));
+        assert_eq!(memory.read(128, 8), Some(&9u64.to_le_bytes()[..]));
+        assert_eq!(memory.read(160, 4), Some(&[2, 0, 0, 0][..]));
+        assert_eq!(PollTimeouts.emulate(&[0, 96, 0, 160], &mut memory), INVAL);
+        assert_eq!(PollTimeouts.emulate(&[0, 96, 2], &mut memory), INVAL);
+        // the events past the end of the 32-bit memory
+        let end = i64::from(u32::MAX - 40);
+        assert_eq!(PollTimeouts.emulate(&[0, end, 2, 160], &mut memory), FAULT);
+        // a subscription to an fd
+        memory[56] = 1;
+        assert_eq!(PollTimeouts.emulate(&[0, 96, 2, 160], &mut memory), NOTSUP);
+    }
+
+    #[test]
+    fn virtual_sleep() {
+        // a relative timeout of 50 and an absolute one at 120 at 0, events at 96
+        let mut memory = vec![0u8; 164];
+        memory[24..32].copy_from_slice(&50u64.to_le_bytes());
+        memory[72..80].copy_from_slice(&120u64.to_le_bytes());
+        memory[88] = 1;
+        let sleep = VirtualSleep::new(VirtualClock::new(100, Tick::PerCall(10)));
+        assert_eq!(sleep.emulate(&[0, 96, 1, 160], &mut memory), SUCCESS);
+        assert_eq!(sleep.clock().now(), 150);
+        // the absolute timeout is past, and the relative one is from 150
+        assert_eq!(sleep.emulate(&[0, 96, 2, 160], &mut memory), SUCCESS);
+        assert_eq!(sleep.clock().now(), 200);
+        memory[40] = 1;
+        assert_eq!(sleep.emulate(&[0, 96, 2, 160], &mut memory), SUCCESS);
+        assert_eq!(sleep.clock().now(), 200);
+        assert_eq!(sleep.emulate(&[0, 96, 0, 160], &mut memory), INVAL);
+    }
+
+    #[test]
+    fn seeded_random() {
+        let (mut a, mut b) = (vec![0u8; 20], vec![0u8; 20]);
+        assert_eq!(SeededRandom::new(42).emulate(&[0, 20], &mut a), SUCCESS);
+        assert_eq!(SeededRandom::new(42).emulate(&[0, 20], &mut b), SUCCESS);
+        assert_eq!(a, b);
+        assert!(a.iter().any(|&byte| byte != 0));
+
+        let random = SeededRandom::new(42);
+        random.emulate(&[0, 20], &mut b);
+        assert_eq!(a, b);
+        random.emulate(&[0, 20], &mut b);
+        assert_ne!(a, b);
+        random.reset(42);
+        random.emulate(&[0, 20], &mut b);
+        assert_eq!(a, b);
+        assert_eq!(random.emulate(&[8, 16], &mut b), FAULT);
+        assert_eq!(random.emulate(&[0], &mut b), INVAL);
+    }
+
+    #[test]
+    fn strings() {
+        const ENV: &[&str] = &["LANG=C", "HOME=/"];
+        let mut memory = vec![0u8; 32];
+        assert_eq!(
+            Strings::sizes_get(ENV).emulate(&[0, 4], &mut memory),
+            SUCCESS
+        );
+        assert_eq!(memory.read(0, 8), Some(&[2, 0, 0, 0, 14, 0, 0, 0][..]));
+
+        assert_eq!(Strings::get(ENV).emulate(&[0, 8], &mut memory), SUCCESS);
+        assert_eq!(memory.read(0, 8), Some(&[8, 0, 0, 0, 15, 0, 0, 0][..]));
+        assert_eq!(memory.read(8, 14), Some(&b"LANG=C\0HOME=/\0"[..]));
+        assert_eq!(Strings::get(ENV).emulate(&[0, 24], &mut memory), FAULT);
+        assert_eq!(Strings::get(ENV).emulate(&[0], &mut memory), INVAL);
+        assert_eq!(
+            Strings::get(ENV).emulate(&[0, i64::from(u32::MAX - 4)], &mut memory),
+            FAULT
+        );
+    }
+}
@@ -0,0 +1,285 @@
+use alloc::{
+    collections::{BTreeMap, BTreeSet},
+    string::String,
+    vec::Vec,
+};
+
+use wasi::p1::Oflags;
+use wasi_descriptor::{ArgKind, ErasedDescriptor};
+
+use super::memory::GuestMemory;
+
+/// What an fd of the guest refers to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FdKind {
+    /// `stdin`, `stdout` or `stderr`.
+    Stdio,
+    /// A directory preopened by the host, e.g., `/data`.
+    Preopen,
+    File,
+    Directory,
+    Socket,
+}
+
+/// An open fd tracked by a [`FdTable`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FdEntry {
+    kind: FdKind,
+    path: String,
+}
+impl FdEntry {
+    pub const fn kind(&self) -> FdKind {
+        self.kind
+    }
+    /// The path the fd was opened at, resolved against the preopened directory,
+    /// e.g., `/tmp/a.txt` for `a.txt` opened under `/tmp`, or empty if it has none,
+    /// e.g., for a socket.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+}
+
+/// Why an fd is not in a [`FdTable`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+pub enum FdError {
+    #[error("the fd {0} is closed")]
+    Closed(u32),
+    #[error("the fd {0} was never opened")]
+    NeverOpened(u32),
+    #[error("the path of the fd {0} is out of the memory or not UTF-8")]
+    InvalidPath(u32),
+}
+
+/// A shadow of the fd table of a guest, so that the bounds of fd-based ABIs can
+/// tell what an fd refers to, e.g., as the context of their statements:
+///
+/// ```ignore
+/// kill fd_write where |fds: &FdTable, fd: u32, _: u32, _: u32, _: u32| {
+///     fds.get(fd).is_ok_and(|entry| entry.kind() == FdKind::Socket)
+/// };
+/// ```
+///
+/// The host keeps it up to date from the preopens, and from the calls seen after
+/// them by [`update_from`](Self::update_from), i.e., `path_open`, `sock_open`,
+/// `sock_accept`, `fd_renumber` and `fd_close` once they succeed, or hands it
+/// to a [`PolicySet`](super::PolicySet) by `with_fd_table`, which does so itself.
+#[derive(Debug, Clone, Default)]
+pub struct FdTable {
+    open: BTreeMap<u32, FdEntry>,
+    closed: BTreeSet<u32>,
+}
+
+impl FdTable {
+    pub const fn new() -> Self {
+        Self {
+            open: BTreeMap::new(),
+            closed: BTreeSet::new(),
+        }
+    }
+
+    /// A table with `stdin`, `stdout` and `stderr` opened as the fds 0 to 2.
+    pub fn with_stdio() -> Self {
+        let mut table = Self::new();
+        for fd in 0..=2 {
+            table.insert(fd, FdKind::Stdio, String::new());
+        }
+        table
+    }
+
+    /// The open fd `fd`, or why it is not open.
+    pub fn get(&self, fd: u32) -> Result<&FdEntry, FdError> {
+        match self.open.get(&fd) {
+            Some(entry) => Ok(entry),
+            None if self.closed.contains(&fd) => Err(FdError::Closed(fd)),
+            None => Err(FdError::NeverOpened(fd)),
+        }
+    }
+
+    pub fn is_open(&self, fd: u32) -> bool {
+        self.open.contains_key(&fd)
+    }
+
+    /// Checks that every fd among `args`, the arguments of the ABI described by
+    /// `desc`, is open, e.g., that `fd_write` is not called on a closed fd.
+    pub fn check_fds(&self, desc: &ErasedDescriptor, args: &[i64]) -> Result<(), FdError> {
+        desc.args
+            .iter()
+            .zip(args)
+            .filter(|(arg, _)| arg.kind == ArgKind::Fd)
+            .try_for_each(|(_, fd)| self.get(*fd as u32).map(|_| ()))
+    }
+
+    /// Opens `fd` as the directory preopened at `path`.
+    pub fn preopen(&mut self, fd: u32, path: &str) {
+        self.insert(fd, FdKind::Preopen, normalize(path, ""));
+    }
+
+    /// Opens `fd` at `path` relative to the directory `dir`, as `path_open` does,
+    /// as a directory if `directory`, i.e., if `oflags` has `DIRECTORY`.
+    pub fn path_open(
+        &mut self,
+        dir: u32,
+        path: &str,
+        directory: bool,
+        fd: u32,
+    ) -> Result<(), FdError> {
+        let path = normalize(self.get(dir)?.path(), path);
+        let kind = if directory {
+            FdKind::Directory
+        } else {
+            FdKind::File
+        };
+        self.insert(fd, kind, path);
+        Ok(())
+    }
+
+    /// Opens `fd` as a socket, as `sock_open` does.
+    pub fn sock_open(&mut self, fd: u32) {
+        self.insert(fd, FdKind::Socket, String::new());
+    }
+
+    /// Opens `fd` as a connection accepted by the socket `listener`, as
+    /// `sock_accept` does.
+    pub fn sock_accept(&mut self, listener: u32, fd: u32) -> Result<(), FdError> {
+        self.get(listener)?;
+        self.insert(fd, FdKind::Socket, String::new());
+        Ok(())
+    }
+
+    /// Moves `from` to `to`, closing the former `to`, as `fd_renumber` does.
+    pub fn renumber(&mut self, from: u32, to: u32) -> Result<(), FdError> {
+        if from == to {
+            return self.get(from).map(|_| ());
+        }
+        self.get(to)?;
+        let entry = self.close(from)?;
+        self.open.insert(to, entry);
+        Ok(())
+    }
+
+    /// Closes `fd`, returning what it referred to.
+    pub fn close(&mut self, fd: u32) -> Result<FdEntry, FdError> {
+        self.get(fd)?;
+        self.closed.insert(fd);
+        Ok(self.open.remove(&fd).unwrap())
+    }
+
+    /// Updates the table by a call to the ABI described by `desc`, seen after it as
+    /// `post`, i.e., its args, then the errno and the out values, as passed to its
+    /// guard after the call, where the path of `path_open` is read out of `memory`.
+    ///
+    /// A failed call, or one of an ABI not opening nor closing fds, changes nothing.
+    pub fn update_from<M: GuestMemory + ?Sized>(
+        &mut self,
+        desc: &ErasedDescriptor,
+        post: &[i64],
+        memory: &M,
+    ) -> Result<(), FdError> {
+        let (args, rest) = post.split_at(desc.args.len().min(post.len()));
+        let Some((0, outs)) = rest.split_first() else {
+            return Ok(());
+        };
+        let arg = |name: &str| {
+            let index = desc.args.iter().position(|arg| arg.name == name);
+            index
+                .and_then(|index| args.get(index))
+                .map(|arg| *arg as u32)
+        };
+        let out_fd = outs.first().map(|fd| *fd as u32);
+        match (desc.name, arg("fd"), out_fd) {
+            ("path_open", Some(dir), Some(fd)) => {
+                let (ptr, len) = (arg("path_ptr").unwrap_or(0), arg("path_len").unwrap_or(0));
+                let path = memory
+                    .read(ptr, len)
+                    .and_then(|path| core::str::from_utf8(path).ok())
+                    .ok_or(FdError::InvalidPath(fd))?;
+                let oflags = Oflags::from_raw(arg("oflags").unwrap_or(0) as u16);
+                self.path_open(dir, path, oflags.contains(Oflags::DIRECTORY), fd)
+            }
+            ("sock_open", _, Some(fd)) => {
+                self.sock_open(fd);
+                Ok(())
+            }
+            ("sock_accept", Some(listener), Some(fd)) => self.sock_accept(listener, fd),
+            ("fd_renumber", Some(from), _) => self.renumber(from, arg("to").unwrap_or(from)),
+            ("fd_close", Some(fd), _) => self.close(fd).map(|_| ()),
+            _ => Ok(()),
+        }
+    }
+
+    fn insert(&mut self, fd: u32, kind: FdKind, path: String) {
+        self.closed.remove(&fd);
+        self.open.insert(fd, FdEntry { kind, path });
+    }
+}
+
+/// Joins `path` to `base`, resolving `.` and `..` lexically, so that a path
+/// escaping its directory, e.g., `/data/../etc`, is not taken as under it.
+fn normalize(base: &str, path: &str) -> String {
+    let mut components: Vec<&str> = Vec::new();
+    for component in base.split('/').chain(path.split('/')) {
+        match component {
+            "" | "." => {}
+            ".." => {
+                components.pop();
+            }
+            component => components.push(component),
+        }
+    }
+    let mut normalized = String::new();
+    if base.starts_with('/') || (base.is_empty() && path.starts_with('/')) {
+        normalized.push('/');
+    }
+    normalized.push_str(&components.join("/"));
+    normalized
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn normalize_paths() {
+        assert_eq!(normalize("/data", "a/./b.txt"), "/data/a/b.txt");
+        assert_eq!(normalize("/data/", "../etc/passwd"), "/etc/passwd");
+        assert_eq!(normalize("/", "../../tmp"), "/tmp");
+        assert_eq!(normalize(".", "a/b/../c"), "a/c");
+        assert_eq!(normalize("/tmp", ""), "/tmp");
+    }
+
+    #[test]
+    fn track_fds() {
+        let mut fds = FdTable::with_stdio();
+        fds.preopen(3, "/tmp");
+        assert_eq!(fds.get(1).unwrap().kind(), FdKind::Stdio);
+        assert_eq!(fds.get(3).unwrap().path(), "/tmp");
+
+        fds.path_open(3, "logs/a.txt", false, 4).unwrap();
+        let entry = fds.get(4).unwrap();
+        assert_eq!(entry.kind(), FdKind::File);
+        assert!(entry.path().starts_with("/tmp"));
+        assert_eq!(
+            fds.path_open(9, "a.txt", false, 5),
+            Err(FdError::NeverOpened(9))
+        );
+
+        fds.sock_open(5);
+        fds.sock_accept(5, 6).unwrap();
+        assert_eq!(fds.get(6).unwrap().kind(), FdKind::Socket);
+        assert_eq!(fds.get(6).unwrap().path(), "");
+
+        fds.renumber(4, 6).unwrap();
+        assert_eq!(fds.get(4), Err(FdError::Closed(4)));
+        assert_eq!(fds.get(6).unwrap().kind(), FdKind::File);
+        assert_eq!(fds.renumber(4, 6), Err(FdError::Closed(4)));
+        fds.renumber(6, 6).unwrap();
+        assert_eq!(fds.get(6).unwrap().kind(), FdKind::File);
+        assert_eq!(fds.renumber(4, 4), Err(FdError::Closed(4)));
+
+        assert_eq!(fds.close(6).unwrap().path(), "/tmp/logs/a.txt");
+        assert_eq!(fds.close(6), Err(FdError::Closed(6)));
+        // reused by a later open
+        fds.path_open(3, ".", true, 6).unwrap();
+        assert_eq!(fds.get(6).unwrap().kind(), FdKind::Directory);
+    }
+}
@@ -0,0 +1,74 @@
+use alloc::vec::Vec;
+
+/// The linear memory of a guest, read by the bounds which follow pointers.
+pub trait GuestMemory {
+    /// The `len` bytes at `ptr`, if all of them are in the memory.
+    fn read(&self, ptr: u32, len: u32) -> Option<&[u8]>;
+}
+impl GuestMemory for [u8] {
+    fn read(&self, ptr: u32, len: u32) -> Option<&[u8]> {
+        let start = usize::try_from(ptr).ok()?;
+        let end = start.checked_add(usize::try_from(len).ok()?)?;
+        self.get(start..end)
+    }
+}
+impl GuestMemory for Vec<u8> {
+    fn read(&self, ptr: u32, len: u32) -> Option<&[u8]> {
+        self[..].read(ptr, len)
+    }
+}
+/// The memory of a runtime is usually lent as a slice, which can not be passed as
+/// `&dyn GuestMemory` itself, unlike a reference to it.
+impl GuestMemory for &[u8] {
+    fn read(&self, ptr: u32, len: u32) -> Option<&[u8]> {
+        (**self).read(ptr, len)
+    }
+}
+impl GuestMemory for &mut [u8] {
+    fn read(&self, ptr: u32, len: u32) -> Option<&[u8]> {
+        (**self).read(ptr, len)
+    }
+}
+
+/// The linear memory of a guest, written by the rewrites of calls, e.g.,
+/// [`Action::Rewrite`](super::action::Action::Rewrite).
+pub trait GuestMemoryMut: GuestMemory {
+    /// Writes `bytes` at `ptr`, if all of them fit in the memory.
+    fn write(&mut self, ptr: u32, bytes: &[u8]) -> Option<()>;
+}
+impl GuestMemoryMut for [u8] {
+    fn write(&mut self, ptr: u32, bytes: &[u8]) -> Option<()> {
+        let start = usize::try_from(ptr).ok()?;
+        let end = start.checked_add(bytes.len())?;
+        self.get_mut(start..end)?.copy_from_slice(bytes);
+        Some(())
+    }
+}
+impl GuestMemoryMut for Vec<u8> {
+    fn write(&mut self, ptr: u32, bytes: &[u8]) -> Option<()> {
+        self[..].write(ptr, bytes)
+    }
+}
+impl GuestMemoryMut for &mut [u8] {
+    fn write(&mut self, ptr: u32, bytes: &[u8]) -> Option<()> {
+        (**self).write(ptr, bytes)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use alloc::vec;
+
+    use super::*;
+
+    #[test]
+    fn read_and_write() {
+        let mut memory = vec![0u8; 8];
+        assert_eq!(memory.write(4, &[1, 2, 3, 4]), Some(()));
+        assert_eq!(memory.read(3, 2), Some(&[0, 1][..]));
+        assert_eq!(memory.write(6, &[1, 2, 3]), None);
+        assert_eq!(memory.read(6, 3), None);
+        assert_eq!(memory.read(u32::MAX, 2), None);
+        assert_eq!(memory.read(8, 0), Some(&[][..]));
+    }
+}
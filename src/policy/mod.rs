@@ -1,5 +1,12 @@
 pub mod action;
 pub mod bound;
+pub mod emulate;
+pub mod fd;
+pub mod memory;
+pub mod net;
+pub mod preset;
+pub mod reload;
+pub mod set;
 pub mod stmt;
 
 use alloc::vec::Vec;
@@ -7,6 +14,8 @@ use alloc::vec::Vec;
 pub use action::Action;
 use bound::PredicateParams;
 pub use lazy_static::lazy_static;
+pub use reload::ReloadablePolicy;
+pub use set::PolicySet;
 use smallvec::SmallVec;
 use stmt::Statement;
 pub use stmt::Trigger;
@@ -19,38 +28,83 @@ use crate::util::Tuple;
 /// Further statements will be ignored.
 pub const STMT_EACH_GUARD: usize = 2;
 
+/// How the statements of a [`WasiGuard`] are evaluated.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Mode {
+    /// Every statement is evaluated, and the actions of all the satisfied ones are taken.
+    #[default]
+    AllMatch,
+    /// Statements are evaluated in order, and only the action of the first satisfied
+    /// one is taken, like the rules of a firewall.
+    FirstMatch,
+}
+
+/// The layers of a `policy!` which the statements of a guard come from, e.g., the
+/// `GUARD_LAYERS` generated for a policy extending a baseline by `extends`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GuardLayers {
+    /// Name of the guarded ABI.
+    pub wasi: &'static str,
+    /// Whether the guard is checked after the call.
+    pub after: bool,
+    /// Name of the layer of each statement of the guard, in order.
+    pub layers: &'static [&'static str],
+}
+
 /// One guard for each WASI ABI.
 /// Represents a security policy for a specific WASI ABI,
 /// containing multiple statements that share the same parameter types.
 /// Each statement consists of a predicate and an action to be taken
 /// when the predicate is satisfied.
-pub struct WasiGuard<'desc, Params: Tuple + PredicateParams + Clone> {
-    statements: SmallVec<[Statement<'desc, Params>; STMT_EACH_GUARD]>,
+///
+/// A guard checked after the call, e.g., `WASI_GUARD_AFTER_FD_WRITE` of `policy!`,
+/// takes the arguments, then the returned errno, then the values written behind
+/// the [`ArgKind::Out`][out] arguments, e.g., `nwritten` of `fd_write`.
+///
+/// The bounds of a guard may also see `Ctx`, the context of the host making the
+/// call, e.g., the tenant, which is passed to [`check_in`](Self::check_in).
+///
+/// [out]: wasi_descriptor::ArgKind::Out
+pub struct WasiGuard<'desc, Params: Tuple + PredicateParams + Clone, Ctx = ()> {
+    statements: SmallVec<[Statement<'desc, Params, Ctx>; STMT_EACH_GUARD]>,
+    mode: Mode,
 }
 
-impl<'desc, Params: Tuple + PredicateParams + Clone> From<Vec<Statement<'desc, Params>>>
-    for WasiGuard<'desc, Params>
+impl<'desc, Params: Tuple + PredicateParams + Clone, Ctx> From<Vec<Statement<'desc, Params, Ctx>>>
+    for WasiGuard<'desc, Params, Ctx>
 {
-    fn from(statements: Vec<Statement<'desc, Params>>) -> Self {
+    fn from(statements: Vec<Statement<'desc, Params, Ctx>>) -> Self {
         Self {
             statements: statements.into(),
+            mode: Mode::AllMatch,
         }
     }
 }
-impl<'desc, Params: Tuple + PredicateParams + Clone> WasiGuard<'desc, Params> {
-    pub fn from_arr<const N: usize>(statements: [Statement<'desc, Params>; N]) -> Self {
+impl<'desc, Params: Tuple + PredicateParams + Clone, Ctx> WasiGuard<'desc, Params, Ctx> {
+    pub fn from_arr<const N: usize>(statements: [Statement<'desc, Params, Ctx>; N]) -> Self {
         Self {
-            statements: statements.to_vec().into(),
+            statements: statements.into_iter().collect(),
+            mode: Mode::AllMatch,
         }
     }
+
+    /// Evaluates the statements in `mode` instead of [`Mode::AllMatch`].
+    pub fn with_mode(mut self, mode: Mode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    pub const fn mode(&self) -> Mode {
+        self.mode
+    }
 }
 macro_rules! impl_from_arr_to_wasi_guard {
     ($($N:literal),*) => {
         $(
-            impl<'desc, Params: Tuple + PredicateParams + Clone> From<[Statement<'desc, Params>; $N]>
-                for WasiGuard<'desc, Params>
+            impl<'desc, Params: Tuple + PredicateParams + Clone, Ctx> From<[Statement<'desc, Params, Ctx>; $N]>
+                for WasiGuard<'desc, Params, Ctx>
             {
-                fn from(statements: [Statement<'desc, Params>; $N]) -> Self {
+                fn from(statements: [Statement<'desc, Params, Ctx>; $N]) -> Self {
                     Self::from_arr(statements)
                 }
             }
@@ -62,6 +116,18 @@ impl_from_arr_to_wasi_guard!(0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10);
 /// The recommended number of actions that can be taken for one [`WasiGuard`].
 pub const ACTION_NUM: usize = STMT_EACH_GUARD;
 
+/// [`WasiGuard::check_in`] and [`WasiGuard::check_or_in`] on any `Params`, e.g.,
+/// for the guards of a [`PolicySet`] on the raw params of their ABIs.
+pub trait GuardCheck<Params, Ctx = ()> {
+    fn check_in(&self, ctx: &Ctx, params: Params) -> SmallVec<[Action; ACTION_NUM]>;
+    fn check_or_in(
+        &self,
+        ctx: &Ctx,
+        params: Params,
+        default: Action,
+    ) -> SmallVec<[Action; ACTION_NUM]>;
+}
+
 macro_rules! impl_check_for_wasi_guard {
     ($($P:ident),*) => {
         impl<'desc, $($P,)*> WasiGuard<'desc, ( $($P,)* )>
@@ -72,20 +138,71 @@ macro_rules! impl_check_for_wasi_guard {
         {
             /// Checks if the bounds is satisfied by the given parameters,
             /// and returns the actions that should be taken.
+            ///
+            /// In [`Mode::FirstMatch`], at most the action of the first satisfied
+            /// statement is returned.
             #[allow(unused)]
             pub fn check(&self, params: ( $($P,)* )) -> smallvec::SmallVec<[Action; ACTION_NUM]> {
-                self.statements.iter().filter_map(|stmt| {
-                    if stmt.check_bound(params.clone()) {
+                self.check_in(&(), params)
+            }
+
+            /// Like [`check`](Self::check), but falls back to `default` if no
+            /// statement is satisfied.
+            #[allow(unused)]
+            pub fn check_or(&self, params: ( $($P,)* ), default: Action) -> smallvec::SmallVec<[Action; ACTION_NUM]> {
+                self.check_or_in(&(), params, default)
+            }
+        }
+        impl<'desc, Ctx: 'desc, $($P,)*> WasiGuard<'desc, ( $($P,)* ), Ctx>
+        where
+            ( $($P,)* ) : $crate::policy::bound::PredicateParams,
+            ( $($P,)* ) : $crate::util::Tuple + 'desc,
+            ( $($P,)* ) : ::core::clone::Clone,
+        {
+            /// Like [`check`](WasiGuard::check), in `ctx`, the context of the
+            /// host making the call.
+            #[allow(unused)]
+            pub fn check_in(&self, ctx: &Ctx, params: ( $($P,)* )) -> smallvec::SmallVec<[Action; ACTION_NUM]> {
+                let actions = self.statements.iter().filter_map(|stmt| {
+                    if stmt.check_bound_in(ctx, params.clone()) {
                         Some(stmt.action)
                     } else {
                         None
                     }
-                }).collect()
+                });
+                match self.mode {
+                    Mode::AllMatch => actions.collect(),
+                    Mode::FirstMatch => actions.take(1).collect(),
+                }
+            }
+
+            /// Like [`check_or`](WasiGuard::check_or), in `ctx`, the context of
+            /// the host making the call.
+            #[allow(unused)]
+            pub fn check_or_in(&self, ctx: &Ctx, params: ( $($P,)* ), default: Action) -> smallvec::SmallVec<[Action; ACTION_NUM]> {
+                let mut actions = self.check_in(ctx, params);
+                if actions.is_empty() {
+                    actions.push(default);
+                }
+                actions
+            }
+        }
+        impl<'desc, Ctx: 'desc, $($P,)*> GuardCheck<( $($P,)* ), Ctx> for WasiGuard<'desc, ( $($P,)* ), Ctx>
+        where
+            ( $($P,)* ) : $crate::policy::bound::PredicateParams,
+            ( $($P,)* ) : $crate::util::Tuple + 'desc,
+            ( $($P,)* ) : ::core::clone::Clone,
+        {
+            fn check_in(&self, ctx: &Ctx, params: ( $($P,)* )) -> smallvec::SmallVec<[Action; ACTION_NUM]> {
+                self.check_in(ctx, params)
+            }
+            fn check_or_in(&self, ctx: &Ctx, params: ( $($P,)* ), default: Action) -> smallvec::SmallVec<[Action; ACTION_NUM]> {
+                self.check_or_in(ctx, params, default)
             }
         }
     };
 }
-all_tuples!(impl_check_for_wasi_guard[0,10]: P);
+all_tuples!(impl_check_for_wasi_guard[0,12]: P);
 
 #[cfg(test)]
 mod test {
@@ -143,6 +260,62 @@ mod test {
         assert_eq!(actions[2], crate::policy::action::Action::Allow);
     }
 
+    #[test]
+    fn first_match() {
+        use crate::{
+            _inner_allow, _inner_return_errno,
+            policy::{action::Action, Mode},
+        };
+        let statements = [
+            _inner_allow!(WASI where |x: i32, _y: i64| x <= 2),
+            _inner_return_errno!(WASI where |_x: i32, _y: i64| true => 2),
+            _inner_return_errno!(WASI where |x: i32, _y: i64| x > 0 => 76),
+        ];
+        let guard: WasiGuard<_> = statements.clone().into();
+        assert_eq!(guard.mode(), Mode::AllMatch);
+        assert_eq!(
+            guard.check((1, 0))[..],
+            [
+                Action::Allow,
+                Action::ReturnErrno(2),
+                Action::ReturnErrno(76)
+            ]
+        );
+
+        let guard = WasiGuard::from_arr(statements).with_mode(Mode::FirstMatch);
+        assert_eq!(guard.check((1, 0))[..], [Action::Allow]);
+        assert_eq!(guard.check((3, 0))[..], [Action::ReturnErrno(2)]);
+
+        let guard = WasiGuard::from_arr([_inner_allow!(WASI where |x: i32, _y: i64| x <= 2)])
+            .with_mode(Mode::FirstMatch);
+        assert!(guard.check((3, 0)).is_empty());
+        assert_eq!(guard.check_or((3, 0), Action::Kill)[..], [Action::Kill]);
+        assert_eq!(guard.check_or((1, 0), Action::Kill)[..], [Action::Allow]);
+    }
+
+    #[test]
+    fn guard_in_context() {
+        use crate::policy::{action::Action, bound::WithContext, stmt::Statement};
+
+        struct Tenant {
+            max_fd: i32,
+        }
+        let guard: WasiGuard<(i32, i64), Tenant> = WasiGuard::from_arr([
+            Statement::<(i32, i64), Tenant>::new(Action::Kill).when(WithContext(
+                |tenant: &Tenant, fd: i32, _: i64| fd > tenant.max_fd,
+            )),
+            // a statement without the context
+            crate::_inner_log!(WASI where |_: i32, time: i64| time < 0).in_context(),
+        ]);
+        let tenant = Tenant { max_fd: 2 };
+        assert_eq!(
+            guard.check_in(&tenant, (3, -1))[..],
+            [Action::Kill, Action::Log]
+        );
+        assert!(guard.check_in(&tenant, (2, 0)).is_empty());
+        assert_eq!(guard.check_in(&Tenant { max_fd: 3 }, (3, 0)).len(), 0);
+    }
+
     lazy_static::lazy_static! {
         pub static ref LAZY_GUARD: WasiGuard<'static, (i32, i64)> =
         WasiGuard::from_arr([crate::_inner_allow!(WASI where |x: i32, y: i64| x > 0 && y > 0)]);
@@ -0,0 +1,318 @@
+//! Network egress rules for the socket ABIs of WasmEdge, e.g., `sock_connect`,
+//! `sock_bind` and `sock_send_to`, whose destination is a `WasiAddress` behind a
+//! pointer, along with a port.
+//!
+//! A bound reads the address out of the memory of the guest, seen as the context
+//! of its statement, and matches it against the [`EgressRules`] of the guest:
+//!
+//! ```ignore
+//! allow sock_connect where |guest: &Guest, _: u32, addr_ptr: u32, port_num: u32| {
+//!     guest.egress.permits_at(&guest.memory, addr_ptr, port_num)
+//! };
+//! ```
+//!
+//! or against a single block, checked at compile time:
+//!
+//! ```ignore
+//! const PRIVATE: Cidr = Cidr::v4([10, 0, 0, 0], 8);
+//! log sock_connect where |guest: &Guest, _: u32, addr_ptr: u32, port_num: u32| {
+//!     read_address(&guest.memory, addr_ptr, port_num).is_some_and(|dst| dst_in(dst, PRIVATE, 443))
+//! };
+//! ```
+
+use alloc::vec::Vec;
+use core::{
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr},
+    ops::RangeInclusive,
+    str::FromStr,
+};
+
+pub use super::memory::GuestMemory;
+
+/// Reads the `WasiAddress` at `addr_ptr`, i.e., a `buf` pointer and its length,
+/// both `u32`, holding the octets of an IPv4 or an IPv6 address, and pairs it with
+/// `port`, the port argument of the ABI.
+///
+/// `None` if the address is out of the memory, or neither 4 nor 16 octets long,
+/// or if `port` is not a port.
+pub fn read_address<M: GuestMemory + ?Sized>(
+    memory: &M,
+    addr_ptr: u32,
+    port: u32,
+) -> Option<SocketAddr> {
+    let address = memory.read(addr_ptr, 8)?;
+    let buf = u32::from_le_bytes(address[..4].try_into().ok()?);
+    let buf_len = u32::from_le_bytes(address[4..].try_into().ok()?);
+    let ip = match memory.read(buf, buf_len)? {
+        &[a, b, c, d] => IpAddr::V4(Ipv4Addr::new(a, b, c, d)),
+        octets => IpAddr::V6(Ipv6Addr::from(<[u8; 16]>::try_from(octets).ok()?)),
+    };
+    Some(SocketAddr::new(ip, u16::try_from(port).ok()?))
+}
+
+/// Why a CIDR could not be parsed.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum NetError {
+    #[error("`{0}` is not a CIDR, e.g., `10.0.0.0/8` or `fd00::/8`")]
+    InvalidCidr(alloc::string::String),
+}
+
+/// A block of IPv4 or IPv6 addresses, e.g., `10.0.0.0/8`, where an address
+/// without a prefix length is a block of itself.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cidr {
+    network: IpAddr,
+    prefix_len: u8,
+}
+
+impl Cidr {
+    pub const fn new(network: IpAddr, prefix_len: u8) -> Option<Self> {
+        let max = match network {
+            IpAddr::V4(_) => 32,
+            IpAddr::V6(_) => 128,
+        };
+        if prefix_len > max {
+            return None;
+        }
+        Some(Self {
+            network,
+            prefix_len,
+        })
+    }
+
+    /// The block `octets/prefix_len`, e.g., `Cidr::v4([10, 0, 0, 0], 8)`, which
+    /// panics if `prefix_len` is over 32, i.e., fails to compile in a const.
+    pub const fn v4(octets: [u8; 4], prefix_len: u8) -> Self {
+        let [a, b, c, d] = octets;
+        match Self::new(IpAddr::V4(Ipv4Addr::new(a, b, c, d)), prefix_len) {
+            Some(cidr) => cidr,
+            None => panic!("the prefix length of an IPv4 CIDR is at most 32"),
+        }
+    }
+
+    /// The block `segments/prefix_len`, e.g., `Cidr::v6([0xfd00, 0, 0, 0, 0, 0, 0, 0], 8)`,
+    /// which panics if `prefix_len` is over 128, i.e., fails to compile in a const.
+    pub const fn v6(segments: [u16; 8], prefix_len: u8) -> Self {
+        let [a, b, c, d, e, f, g, h] = segments;
+        match Self::new(
+            IpAddr::V6(Ipv6Addr::new(a, b, c, d, e, f, g, h)),
+            prefix_len,
+        ) {
+            Some(cidr) => cidr,
+            None => panic!("the prefix length of an IPv6 CIDR is at most 128"),
+        }
+    }
+
+    /// Whether `ip` is in the block, where an IPv4-mapped IPv6 address, e.g.,
+    /// `::ffff:10.0.0.1`, is taken as the IPv4 one.
+    pub fn contains(&self, ip: IpAddr) -> bool {
+        match (self.network, ip.to_canonical()) {
+            (IpAddr::V4(network), IpAddr::V4(ip)) => {
+                prefix_eq(&network.octets(), &ip.octets(), self.prefix_len)
+            }
+            (IpAddr::V6(network), IpAddr::V6(ip)) => {
+                prefix_eq(&network.octets(), &ip.octets(), self.prefix_len)
+            }
+            _ => false,
+        }
+    }
+}
+
+impl FromStr for Cidr {
+    type Err = NetError;
+    fn from_str(s: &str) -> Result<Self, NetError> {
+        let invalid = || NetError::InvalidCidr(s.into());
+        let (network, prefix_len) = match s.split_once('/') {
+            Some((network, prefix_len)) => (
+                network.parse().map_err(|_| invalid())?,
+                Some(prefix_len.parse().map_err(|_| invalid())?),
+            ),
+            None => (s.parse().map_err(|_| invalid())?, None),
+        };
+        let prefix_len = prefix_len.unwrap_or(match network {
+            IpAddr::V4(_) => 32,
+            IpAddr::V6(_) => 128,
+        });
+        Self::new(network, prefix_len).ok_or_else(invalid)
+    }
+}
+
+/// Whether the first `prefix_len` bits of `a` and `b` are equal.
+fn prefix_eq(a: &[u8], b: &[u8], prefix_len: u8) -> bool {
+    let (bytes, bits) = (usize::from(prefix_len / 8), prefix_len % 8);
+    if a[..bytes] != b[..bytes] {
+        return false;
+    }
+    bits == 0 || (a[bytes] ^ b[bytes]) >> (8 - bits) == 0
+}
+
+/// An inclusive range of ports, e.g., `443`, `8000..=8999` or [`Ports::ANY`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Ports {
+    pub start: u16,
+    pub end: u16,
+}
+impl Ports {
+    pub const ANY: Self = Self {
+        start: 0,
+        end: u16::MAX,
+    };
+    pub const fn contains(&self, port: u16) -> bool {
+        self.start <= port && port <= self.end
+    }
+}
+impl From<u16> for Ports {
+    fn from(port: u16) -> Self {
+        Self {
+            start: port,
+            end: port,
+        }
+    }
+}
+impl From<RangeInclusive<u16>> for Ports {
+    fn from(ports: RangeInclusive<u16>) -> Self {
+        Self {
+            start: *ports.start(),
+            end: *ports.end(),
+        }
+    }
+}
+
+/// Whether `dst` is in the block `cidr` and the range `ports`.
+pub fn dst_in(dst: SocketAddr, cidr: Cidr, ports: impl Into<Ports>) -> bool {
+    cidr.contains(dst.ip()) && ports.into().contains(dst.port())
+}
+
+/// An allowlist and a denylist of destinations, where a destination is permitted
+/// if it is allowed and not denied, so an empty allowlist permits nothing.
+#[derive(Debug, Clone, Default)]
+pub struct EgressRules {
+    allowed: Vec<(Cidr, Ports)>,
+    denied: Vec<(Cidr, Ports)>,
+}
+
+impl EgressRules {
+    pub const fn new() -> Self {
+        Self {
+            allowed: Vec::new(),
+            denied: Vec::new(),
+        }
+    }
+
+    pub fn allow(mut self, cidr: &str, ports: impl Into<Ports>) -> Result<Self, NetError> {
+        self.allowed.push((cidr.parse()?, ports.into()));
+        Ok(self)
+    }
+
+    pub fn deny(mut self, cidr: &str, ports: impl Into<Ports>) -> Result<Self, NetError> {
+        self.denied.push((cidr.parse()?, ports.into()));
+        Ok(self)
+    }
+
+    pub fn permits(&self, dst: SocketAddr) -> bool {
+        let matches =
+            |(cidr, ports): &(Cidr, Ports)| cidr.contains(dst.ip()) && ports.contains(dst.port());
+        self.allowed.iter().any(matches) && !self.denied.iter().any(matches)
+    }
+
+    /// Whether the destination read by [`read_address`] is permitted, where an
+    /// unreadable one is not.
+    pub fn permits_at<M: GuestMemory + ?Sized>(
+        &self,
+        memory: &M,
+        addr_ptr: u32,
+        port: u32,
+    ) -> bool {
+        read_address(memory, addr_ptr, port).is_some_and(|dst| self.permits(dst))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use alloc::vec;
+
+    use super::*;
+
+    fn ip(s: &str) -> IpAddr {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn cidrs() {
+        let private: Cidr = "10.0.0.0/8".parse().unwrap();
+        assert!(private.contains(ip("10.1.2.3")));
+        assert!(!private.contains(ip("11.0.0.1")));
+        assert!(private.contains(ip("::ffff:10.0.0.1")));
+        assert!(!private.contains(ip("fd00::1")));
+
+        let odd: Cidr = "192.168.4.0/22".parse().unwrap();
+        assert!(odd.contains(ip("192.168.7.255")));
+        assert!(!odd.contains(ip("192.168.8.0")));
+
+        let ula: Cidr = "fd00::/8".parse().unwrap();
+        assert!(ula.contains(ip("fd12::1")));
+        assert!(!ula.contains(ip("fe80::1")));
+        assert!("0.0.0.0/0".parse::<Cidr>().unwrap().contains(ip("8.8.8.8")));
+        assert!("::1".parse::<Cidr>().unwrap().contains(ip("::1")));
+        assert_eq!(Cidr::v4([10, 0, 0, 0], 8), private);
+        assert_eq!(Cidr::v6([0xfd00, 0, 0, 0, 0, 0, 0, 0], 8), ula);
+
+        for invalid in ["10.0.0.0/33", "10.0.0/8", "fd00::/129", "10.0.0.0/"] {
+            assert_eq!(
+                invalid.parse::<Cidr>(),
+                Err(NetError::InvalidCidr(invalid.into()))
+            );
+        }
+    }
+
+    #[test]
+    fn read_addresses() {
+        // WasiAddress { buf: 16, buf_len: 4 } at 0, WasiAddress { buf: 32, buf_len: 16 } at 8
+        let mut memory = vec![0u8; 48];
+        memory[..8].copy_from_slice(&[16, 0, 0, 0, 4, 0, 0, 0]);
+        memory[8..16].copy_from_slice(&[32, 0, 0, 0, 16, 0, 0, 0]);
+        memory[16..20].copy_from_slice(&[10, 0, 0, 1]);
+        memory[32..48].copy_from_slice(&"fd00::1".parse::<Ipv6Addr>().unwrap().octets());
+
+        assert_eq!(
+            read_address(&memory, 0, 443),
+            Some(SocketAddr::new(ip("10.0.0.1"), 443))
+        );
+        assert_eq!(
+            read_address(&memory, 8, 80),
+            Some(SocketAddr::new(ip("fd00::1"), 80))
+        );
+        assert_eq!(read_address(&memory, 0, 65536), None);
+        assert_eq!(read_address(&memory, 44, 80), None);
+        memory[4] = 8;
+        assert_eq!(read_address(&memory, 0, 80), None);
+    }
+
+    #[test]
+    fn egress_rules() {
+        let rules = EgressRules::new()
+            .allow("10.0.0.0/8", 443)
+            .unwrap()
+            .allow("fd00::/8", 8000..=8999)
+            .unwrap()
+            .deny("10.1.0.0/16", Ports::ANY)
+            .unwrap();
+        assert!(rules.permits(SocketAddr::new(ip("10.2.0.1"), 443)));
+        assert!(!rules.permits(SocketAddr::new(ip("10.2.0.1"), 80)));
+        assert!(!rules.permits(SocketAddr::new(ip("10.1.0.1"), 443)));
+        assert!(rules.permits(SocketAddr::new(ip("fd00::2"), 8080)));
+        assert!(!rules.permits(SocketAddr::new(ip("8.8.8.8"), 443)));
+        assert!(!EgressRules::new().permits(SocketAddr::new(ip("10.0.0.1"), 443)));
+        assert!(EgressRules::new().deny("10.0.0.0/", 443).is_err());
+
+        const PRIVATE: Cidr = Cidr::v4([10, 0, 0, 0], 8);
+        assert!(dst_in(SocketAddr::new(ip("10.0.0.1"), 443), PRIVATE, 443));
+        assert!(!dst_in(SocketAddr::new(ip("10.0.0.1"), 80), PRIVATE, 443));
+    }
+
+    #[test]
+    #[should_panic(expected = "at most 32")]
+    fn invalid_prefix_len() {
+        Cidr::v4([10, 0, 0, 0], 33);
+    }
+}
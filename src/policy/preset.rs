@@ -0,0 +1,226 @@
+//! Ready-made policies, built at runtime as a [`PolicySet`].
+
+use wasi::{
+    args_get_params_raw_t, args_sizes_get_params_raw_t, clock_res_get_params_raw_t,
+    clock_time_get_params_raw_t, environ_get_params_raw_t, environ_sizes_get_params_raw_t,
+    fd_close_params_raw_t, fd_fdstat_get_params_raw_t, fd_prestat_dir_name_params_raw_t,
+    fd_prestat_get_params_raw_t, fd_write_params_raw_t, poll_oneoff_params_raw_t,
+    proc_exit_params_raw_t, random_get_params_raw_t, sched_yield_params_raw_t,
+};
+
+use super::{
+    action::{Action, Emulate, WasiErrno},
+    bound::PredicateParams,
+    emulate::{
+        ClockResolution, Emulator, SeededRandom, Strings, Succeed, Tick, VirtualClock, VirtualSleep,
+    },
+    stmt::Statement,
+    Mode, PolicySet, WasiGuard,
+};
+use crate::util::Tuple;
+
+/// What a [`Deterministic`] guest gets from a call which can not be made
+/// deterministic.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Nondeterministic {
+    /// The guest is killed.
+    Kill,
+    /// The call returns the errno, e.g., `NOTCAPABLE`.
+    Errno(WasiErrno),
+}
+impl Nondeterministic {
+    pub const fn action(self) -> Action {
+        match self {
+            Self::Kill => Action::Kill,
+            Self::Errno(errno) => Action::ReturnErrno(errno),
+        }
+    }
+}
+
+/// How a [`Deterministic`] guest sees time, randomness, its args and its environment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DeterministicConfig {
+    /// The seed of the stream of `random_get`.
+    pub seed: u64,
+    /// The time told by the first `clock_time_get`, in nanoseconds.
+    pub start: u64,
+    pub tick: Tick,
+    /// The resolution told by `clock_res_get`, in nanoseconds.
+    pub resolution: u64,
+    /// The args told by `args_get`, e.g., `["app.wasm", "--verbose"]`.
+    pub args: &'static [&'static str],
+    /// The environment told by `environ_get`, e.g., `["LANG=C"]`.
+    pub env: &'static [&'static str],
+    /// What the calls which can not be made deterministic get, e.g., those of the
+    /// filesystem or of sockets.
+    pub nondeterministic: Nondeterministic,
+}
+impl DeterministicConfig {
+    /// A clock from the epoch advancing by a millisecond per call, no args nor
+    /// environment, and a guest killed by any nondeterministic call.
+    pub const fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            start: 0,
+            tick: Tick::PerCall(1_000_000),
+            resolution: 1_000_000,
+            args: &[],
+            env: &[],
+            nondeterministic: Nondeterministic::Kill,
+        }
+    }
+}
+impl Default for DeterministicConfig {
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
+
+/// A policy making a guest deterministic, so that it can be replayed bit for bit.
+///
+/// `clock_time_get` tells a [`VirtualClock`], `random_get` reads a
+/// [`SeededRandom`] stream, `poll_oneoff` fires its timeouts at once and advances
+/// the clock past them, `sched_yield` does nothing, and the args and the
+/// environment are those of the config, all of them emulated. The preopens,
+/// `proc_exit`, and `fd_write` to `stdout` or `stderr` are allowed, and any other
+/// call is taken as nondeterministic.
+///
+/// ```ignore
+/// static GUEST: Deterministic = Deterministic::new(DeterministicConfig::new(42));
+/// let policy = ReloadablePolicy::new(GUEST.policy_set())?;
+/// // before a replay
+/// GUEST.reset();
+/// ```
+pub struct Deterministic {
+    config: DeterministicConfig,
+    /// The clock, advanced by the sleeps of the guest.
+    sleep: VirtualSleep,
+    resolution: ClockResolution,
+    random: SeededRandom,
+    args: Strings,
+    args_sizes: Strings,
+    env: Strings,
+    env_sizes: Strings,
+}
+
+impl Deterministic {
+    pub const fn new(config: DeterministicConfig) -> Self {
+        Self {
+            config,
+            sleep: VirtualSleep::new(VirtualClock::new(config.start, config.tick)),
+            resolution: ClockResolution(config.resolution),
+            random: SeededRandom::new(config.seed),
+            args: Strings::get(config.args),
+            args_sizes: Strings::sizes_get(config.args),
+            env: Strings::get(config.env),
+            env_sizes: Strings::sizes_get(config.env),
+        }
+    }
+
+    pub const fn config(&self) -> &DeterministicConfig {
+        &self.config
+    }
+
+    /// The virtual clock, e.g., to be told the fuel consumed by the guest.
+    pub const fn clock(&self) -> &VirtualClock {
+        self.sleep.clock()
+    }
+
+    /// Restarts the clock and the random stream, e.g., to replay the guest.
+    pub fn reset(&self) {
+        self.clock().reset();
+        self.random.reset(self.config.seed);
+    }
+
+    /// Builds the guards of the policy, answering calls by the state of `self`.
+    pub fn policy_set(&'static self) -> PolicySet {
+        let stdio = |fd: i32, _: i32, _: i32, _: i32| (0..=2).contains(&fd);
+
+        PolicySet::new(self.config.nondeterministic.action(), Mode::FirstMatch)
+            .with_guard(
+                "clock_time_get",
+                false,
+                emulate::<clock_time_get_params_raw_t>("virtual_clock", self.clock()),
+            )
+            .with_guard(
+                "clock_res_get",
+                false,
+                emulate::<clock_res_get_params_raw_t>("clock_resolution", &self.resolution),
+            )
+            .with_guard(
+                "random_get",
+                false,
+                emulate::<random_get_params_raw_t>("seeded_random", &self.random),
+            )
+            .with_guard(
+                "poll_oneoff",
+                false,
+                emulate::<poll_oneoff_params_raw_t>("virtual_sleep", &self.sleep),
+            )
+            .with_guard(
+                "sched_yield",
+                false,
+                emulate::<sched_yield_params_raw_t>("sched_yield", &Succeed),
+            )
+            .with_guard(
+                "args_get",
+                false,
+                emulate::<args_get_params_raw_t>("args", &self.args),
+            )
+            .with_guard(
+                "args_sizes_get",
+                false,
+                emulate::<args_sizes_get_params_raw_t>("args_sizes", &self.args_sizes),
+            )
+            .with_guard(
+                "environ_get",
+                false,
+                emulate::<environ_get_params_raw_t>("environ", &self.env),
+            )
+            .with_guard(
+                "environ_sizes_get",
+                false,
+                emulate::<environ_sizes_get_params_raw_t>("environ_sizes", &self.env_sizes),
+            )
+            .with_guard(
+                "fd_prestat_get",
+                false,
+                allow::<fd_prestat_get_params_raw_t>(),
+            )
+            .with_guard(
+                "fd_prestat_dir_name",
+                false,
+                allow::<fd_prestat_dir_name_params_raw_t>(),
+            )
+            .with_guard(
+                "fd_fdstat_get",
+                false,
+                allow::<fd_fdstat_get_params_raw_t>(),
+            )
+            .with_guard("fd_close", false, allow::<fd_close_params_raw_t>())
+            .with_guard(
+                "fd_write",
+                false,
+                WasiGuard::from_arr([
+                    Statement::<fd_write_params_raw_t>::new(Action::Allow).when(stdio)
+                ]),
+            )
+            .with_guard("proc_exit", false, allow::<proc_exit_params_raw_t>())
+    }
+}
+
+fn emulate<Raw>(name: &'static str, emulator: &'static dyn Emulator) -> WasiGuard<'static, Raw>
+where
+    Raw: Tuple + PredicateParams + Clone,
+{
+    WasiGuard::from_arr([Statement::new(Action::Emulate(Emulate::new(
+        name, emulator,
+    )))])
+}
+
+fn allow<Raw>() -> WasiGuard<'static, Raw>
+where
+    Raw: Tuple + PredicateParams + Clone,
+{
+    WasiGuard::from_arr([Statement::new(Action::Allow)])
+}
@@ -0,0 +1,127 @@
+use alloc::sync::Arc;
+use core::ops::Deref;
+
+use spin::RwLock;
+
+use super::set::{PolicyError, PolicySet};
+
+/// A policy checked before it takes effect, e.g., by [`ReloadablePolicy::reload`].
+pub trait Validate {
+    type Error;
+    fn validate(&self) -> Result<(), Self::Error>;
+}
+impl Validate for PolicySet {
+    type Error = PolicyError;
+    fn validate(&self) -> Result<(), PolicyError> {
+        PolicySet::validate(self)
+    }
+}
+
+/// A version of the policy held by a [`ReloadablePolicy`], counted from 0 by reloads.
+pub struct Versioned<P> {
+    version: u64,
+    policy: P,
+}
+impl<P> Versioned<P> {
+    pub const fn version(&self) -> u64 {
+        self.version
+    }
+}
+impl<P> Deref for Versioned<P> {
+    type Target = P;
+    fn deref(&self) -> &P {
+        &self.policy
+    }
+}
+
+/// What a version of a policy decided, e.g., the actions on the params of an ABI.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Decision<T> {
+    pub version: u64,
+    pub value: T,
+}
+
+/// A policy replaceable while running, e.g., that of a tenant of a long-running host.
+///
+/// The policy is held by an [`Arc`] swapped atomically on reload. A check loads
+/// the current version once and sees it as a whole, while the calls in flight keep
+/// the version they loaded until they are done.
+pub struct ReloadablePolicy<P = PolicySet> {
+    current: RwLock<Arc<Versioned<P>>>,
+}
+
+impl<P: Validate> ReloadablePolicy<P> {
+    /// Holds `policy` as the version 0, if it is valid.
+    pub fn new(policy: P) -> Result<Self, P::Error> {
+        policy.validate()?;
+        Ok(Self {
+            current: RwLock::new(Arc::new(Versioned { version: 0, policy })),
+        })
+    }
+
+    /// The current version of the policy, kept as is by the later reloads.
+    pub fn load(&self) -> Arc<Versioned<P>> {
+        self.current.read().clone()
+    }
+
+    pub fn version(&self) -> u64 {
+        self.current.read().version
+    }
+
+    /// Validates `policy`, then replaces the current one by it, returning its
+    /// version. The current policy is kept if `policy` is invalid.
+    pub fn reload(&self, policy: P) -> Result<u64, P::Error> {
+        policy.validate()?;
+        let mut current = self.current.write();
+        let version = current.version + 1;
+        let former = core::mem::replace(&mut *current, Arc::new(Versioned { version, policy }));
+        drop(current);
+        // The former version is dropped out of the lock, if no call holds it.
+        drop(former);
+        Ok(version)
+    }
+
+    /// Decides by `decide` on the current version of the policy, which is
+    /// reported along with the decision.
+    pub fn decide<T>(&self, decide: impl FnOnce(&P) -> T) -> Decision<T> {
+        let policy = self.load();
+        Decision {
+            version: policy.version,
+            value: decide(&policy),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    struct Limit(u32);
+    impl Validate for Limit {
+        type Error = ();
+        fn validate(&self) -> Result<(), ()> {
+            if self.0 > 0 {
+                Ok(())
+            } else {
+                Err(())
+            }
+        }
+    }
+
+    #[test]
+    fn reload_versions() {
+        assert!(ReloadablePolicy::new(Limit(0)).is_err());
+        let policy = ReloadablePolicy::new(Limit(1)).unwrap();
+        let loaded = policy.load();
+        assert_eq!(policy.reload(Limit(2)), Ok(1));
+        assert_eq!(policy.reload(Limit(0)), Err(()));
+        assert_eq!(
+            policy.decide(|limit| limit.0),
+            Decision {
+                version: 1,
+                value: 2
+            }
+        );
+        assert_eq!((loaded.version(), loaded.0), (0, 1));
+    }
+}
@@ -0,0 +1,354 @@
+use alloc::{boxed::Box, vec::Vec};
+use core::any::Any;
+
+use smallvec::{smallvec, SmallVec};
+use spin::{RwLock, RwLockReadGuard};
+use wasi_descriptor::{DefaultErrnoType, DefaultOutType, ErasedDescriptor, ValType};
+
+use super::{
+    action::Action,
+    bound::PredicateParams,
+    fd::{FdError, FdTable},
+    memory::GuestMemory,
+    GuardCheck, Mode, WasiGuard, ACTION_NUM,
+};
+use crate::util::Tuple;
+
+/// The guards of a policy built at runtime, e.g., by `policy_set()` of `policy!`,
+/// rather than held by statics, so that it can be replaced by
+/// [`ReloadablePolicy`][reload].
+///
+/// Each guard is held on the raw params of its ABI, e.g.,
+/// `wasi::fd_write_params_raw_t`, or `wasi::fd_write_post_params_raw_t` after
+/// the call, which are the same for the guards of any policy.
+///
+/// [reload]: super::reload::ReloadablePolicy
+pub struct PolicySet {
+    pub default_action: Action,
+    pub mode: Mode,
+    /// ABIs which must be killed whatever their arguments are.
+    pub must_be_killed: &'static [&'static str],
+    /// Descriptors of the host ABIs other than WASI guarded by the policy.
+    pub custom_descriptors: &'static [&'static [ErasedDescriptor<'static>]],
+    guards: Vec<ErasedGuard>,
+    /// The fds of the guest, if tracked by [`with_fd_table`](Self::with_fd_table).
+    fds: Option<RwLock<FdTable>>,
+}
+
+/// A [`WasiGuard`] with its params erased.
+struct ErasedGuard {
+    wasi: &'static str,
+    after: bool,
+    /// The sizes of the params of the guard.
+    sizes: &'static [usize],
+    guard: Box<dyn Any + Send + Sync>,
+}
+
+impl ErasedGuard {
+    /// Why the guard does not take the params of the ABI described by `desc`, if so.
+    fn mismatch(&self, desc: &ErasedDescriptor) -> Option<PolicyError> {
+        let args = desc.args.iter().map(|arg| match arg.val_type() {
+            ValType::I32 => core::mem::size_of::<i32>(),
+            ValType::I64 => core::mem::size_of::<i64>(),
+        });
+        let post = desc
+            .args
+            .iter()
+            .filter(|arg| arg.is_out())
+            .map(|_| core::mem::size_of::<DefaultOutType>());
+        let expected: Vec<usize> = match self.after {
+            false => args.collect(),
+            true => args
+                .chain([core::mem::size_of::<DefaultErrnoType>()])
+                .chain(post)
+                .collect(),
+        };
+        if expected.len() != self.sizes.len() {
+            return Some(PolicyError::Arity {
+                wasi: self.wasi,
+                after: self.after,
+                expected: expected.len(),
+                found: self.sizes.len(),
+            });
+        }
+        let param = expected.iter().zip(self.sizes).position(|(a, b)| a != b)?;
+        Some(PolicyError::Width {
+            wasi: self.wasi,
+            after: self.after,
+            param,
+            expected: expected[param],
+            found: self.sizes[param],
+        })
+    }
+}
+
+/// Whether `desc` is of the ABI named `wasi` in Rust, where a `-` or a `.` of its
+/// import name is a `_`, e.g., `thread_spawn` for `thread-spawn`, and the kind of
+/// a WASI 0.2 function is dropped but for a `drop_`, e.g., `descriptor_open_at`
+/// for `[method]descriptor.open-at` and `drop_descriptor` for `[resource-drop]descriptor`.
+fn is_named(desc: &ErasedDescriptor, wasi: &str) -> bool {
+    let (name, wasi) = match desc.name.strip_prefix("[resource-drop]") {
+        Some(resource) => match wasi.strip_prefix("drop_") {
+            Some(wasi) => (resource, wasi),
+            None => return false,
+        },
+        None => (
+            desc.name
+                .strip_prefix('[')
+                .and_then(|name| name.split_once(']'))
+                .map_or(desc.name, |(_, name)| name),
+            wasi,
+        ),
+    };
+    name.len() == wasi.len()
+        && name
+            .bytes()
+            .zip(wasi.bytes())
+            .all(|(import, rust)| import == rust || (matches!(import, b'-' | b'.') && rust == b'_'))
+}
+
+/// Why a [`PolicySet`] does not fit the enabled ABIs.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum PolicyError {
+    #[error("`{0}` is not a known ABI")]
+    UnknownAbi(&'static str),
+    #[error("the guard of `{wasi}` takes {found} params, but {expected} are passed")]
+    Arity {
+        wasi: &'static str,
+        after: bool,
+        expected: usize,
+        found: usize,
+    },
+    #[error(
+        "the param {param} of the guard of `{wasi}` is {found} bytes, but {expected} are passed"
+    )]
+    Width {
+        wasi: &'static str,
+        after: bool,
+        param: usize,
+        expected: usize,
+        found: usize,
+    },
+}
+
+impl PolicySet {
+    pub const fn new(default_action: Action, mode: Mode) -> Self {
+        Self {
+            default_action,
+            mode,
+            must_be_killed: &[],
+            custom_descriptors: &[],
+            guards: Vec::new(),
+            fds: None,
+        }
+    }
+
+    /// Tracks the fds of the guest from `fds`, e.g., [`FdTable::with_stdio`] with
+    /// the preopened directories, checked by [`before_call`](Self::before_call),
+    /// updated by [`after_call`](Self::after_call), and seen by the guards in the
+    /// context of a [`FdTable`] through [`check_tracked`](Self::check_tracked).
+    pub fn with_fd_table(mut self, fds: FdTable) -> Self {
+        self.fds = Some(RwLock::new(fds));
+        self
+    }
+
+    /// The fds of the guest, if tracked.
+    pub fn fd_table(&self) -> Option<RwLockReadGuard<'_, FdTable>> {
+        self.fds.as_ref().map(|fds| fds.read())
+    }
+
+    pub const fn with_must_be_killed(mut self, wasis: &'static [&'static str]) -> Self {
+        self.must_be_killed = wasis;
+        self
+    }
+
+    pub const fn with_custom_descriptors(
+        mut self,
+        descriptors: &'static [&'static [ErasedDescriptor<'static>]],
+    ) -> Self {
+        self.custom_descriptors = descriptors;
+        self
+    }
+
+    /// Guards the ABI `wasi` by `guard` on its raw params, checked after the call
+    /// if `after`, replacing its former guard if any.
+    ///
+    /// The bounds of the guard may see `Ctx`, the context of the host, passed to
+    /// [`check_in`](Self::check_in).
+    pub fn with_guard<Raw, Ctx>(
+        mut self,
+        wasi: &'static str,
+        after: bool,
+        guard: WasiGuard<'static, Raw, Ctx>,
+    ) -> Self
+    where
+        Raw: Tuple + PredicateParams + Clone + Send + Sync + 'static,
+        Ctx: 'static,
+    {
+        self.guards
+            .retain(|erased| erased.wasi != wasi || erased.after != after);
+        self.guards.push(ErasedGuard {
+            wasi,
+            after,
+            sizes: Raw::SIZES,
+            guard: Box::new(guard),
+        });
+        self
+    }
+
+    /// The guard of the ABI `wasi`, if it is held on `Raw` without the context.
+    pub fn guard<Raw>(&self, wasi: &str, after: bool) -> Option<&WasiGuard<'static, Raw>>
+    where
+        Raw: Tuple + PredicateParams + Clone + 'static,
+    {
+        self.guard_in(wasi, after)
+    }
+
+    /// The guard of the ABI `wasi`, if it is held on `Raw` in the context `Ctx`.
+    pub fn guard_in<Raw, Ctx>(
+        &self,
+        wasi: &str,
+        after: bool,
+    ) -> Option<&WasiGuard<'static, Raw, Ctx>>
+    where
+        Raw: Tuple + PredicateParams + Clone + 'static,
+        Ctx: 'static,
+    {
+        self.guards
+            .iter()
+            .find(|erased| erased.wasi == wasi && erased.after == after)
+            .and_then(|erased| erased.guard.downcast_ref())
+    }
+
+    /// Names of the guarded ABIs, and whether they are guarded after the call.
+    pub fn guarded(&self) -> impl Iterator<Item = (&'static str, bool)> + '_ {
+        self.guards.iter().map(|erased| (erased.wasi, erased.after))
+    }
+
+    /// Checks that every guard is of a known ABI, and takes the params passed to it,
+    /// as many and as wide: the args, then the errno and the out values after the
+    /// call.
+    ///
+    /// An ABI is named as in Rust, e.g., `thread_spawn` for the import `thread-spawn`,
+    /// and one declared in several shapes, e.g., `sock_accept` of the WasmEdge `v1`
+    /// sockets, fits any of them.
+    pub fn validate(&self) -> Result<(), PolicyError> {
+        for erased in &self.guards {
+            let mismatches: Vec<PolicyError> = match self
+                .custom_descriptors
+                .iter()
+                .flat_map(|descriptors| descriptors.iter())
+                .chain(wasi::DESCRIPTORS)
+                .filter(|desc| is_named(desc, erased.wasi))
+                .map(|desc| erased.mismatch(desc))
+                .collect::<Option<_>>()
+            {
+                Some(mismatches) => mismatches,
+                // fits one of the shapes
+                None => continue,
+            };
+            // a shape of the same arity tells more
+            let error = mismatches
+                .iter()
+                .find(|error| matches!(error, PolicyError::Width { .. }))
+                .or(mismatches.first())
+                .cloned()
+                .unwrap_or(PolicyError::UnknownAbi(erased.wasi));
+            return Err(error);
+        }
+        Ok(())
+    }
+
+    /// Checks before a call to the ABI described by `desc` that every fd among its
+    /// raw `args` is open, i.e., neither closed nor never opened, if the fds are
+    /// tracked.
+    pub fn before_call(&self, desc: &ErasedDescriptor, args: &[i64]) -> Result<(), FdError> {
+        match self.fd_table() {
+            Some(fds) => fds.check_fds(desc, args),
+            None => Ok(()),
+        }
+    }
+
+    /// Updates the tracked fds, if any, by a call to the ABI described by `desc`,
+    /// seen after it as `post`, i.e., the raw params passed to its guard after the
+    /// call, as [`FdTable::update_from`] does.
+    pub fn after_call<M: GuestMemory + ?Sized>(
+        &self,
+        desc: &ErasedDescriptor,
+        post: &[i64],
+        memory: &M,
+    ) -> Result<(), FdError> {
+        match &self.fds {
+            Some(fds) => fds.write().update_from(desc, post, memory),
+            None => Ok(()),
+        }
+    }
+
+    /// Like [`check_in`](Self::check_in), in the context of the tracked fds, or
+    /// like [`check`](Self::check) if they are not tracked.
+    pub fn check_tracked<Raw>(
+        &self,
+        wasi: &str,
+        after: bool,
+        params: Raw,
+    ) -> SmallVec<[Action; ACTION_NUM]>
+    where
+        Raw: Tuple + PredicateParams + Clone + 'static,
+        WasiGuard<'static, Raw, FdTable>: GuardCheck<Raw, FdTable>,
+        WasiGuard<'static, Raw>: GuardCheck<Raw>,
+    {
+        match self.fd_table() {
+            Some(fds) => self.check_in(wasi, after, &*fds, params),
+            None => self.check(wasi, after, params),
+        }
+    }
+
+    /// Checks the raw params of the ABI `wasi` by its guard, falling back to the
+    /// default action if it has no guard, or if no statement is satisfied in
+    /// [`Mode::FirstMatch`].
+    ///
+    /// A guard held on other params than `Raw`, or in another context, can not
+    /// check them, so the call is killed.
+    pub fn check<Raw>(&self, wasi: &str, after: bool, params: Raw) -> SmallVec<[Action; ACTION_NUM]>
+    where
+        Raw: Tuple + PredicateParams + Clone + 'static,
+        WasiGuard<'static, Raw>: GuardCheck<Raw>,
+    {
+        self.check_in(wasi, after, &(), params)
+    }
+
+    /// Like [`check`](Self::check), in `ctx`, the context of the host making the
+    /// call, which the guards without the context ignore.
+    pub fn check_in<Raw, Ctx>(
+        &self,
+        wasi: &str,
+        after: bool,
+        ctx: &Ctx,
+        params: Raw,
+    ) -> SmallVec<[Action; ACTION_NUM]>
+    where
+        Raw: Tuple + PredicateParams + Clone + 'static,
+        Ctx: 'static,
+        WasiGuard<'static, Raw, Ctx>: GuardCheck<Raw, Ctx>,
+        WasiGuard<'static, Raw>: GuardCheck<Raw>,
+    {
+        let first_match = self.mode == Mode::FirstMatch;
+        if let Some(guard) = self.guard_in::<Raw, Ctx>(wasi, after) {
+            return match first_match {
+                true => GuardCheck::check_or_in(guard, ctx, params, self.default_action),
+                false => GuardCheck::check_in(guard, ctx, params),
+            };
+        }
+        match self.guard::<Raw>(wasi, after) {
+            Some(guard) if first_match => {
+                GuardCheck::check_or_in(guard, &(), params, self.default_action)
+            }
+            Some(guard) => GuardCheck::check_in(guard, &(), params),
+            None if self.guarded().any(|guarded| guarded == (wasi, after)) => {
+                smallvec![Action::Kill]
+            }
+            None if after => SmallVec::new(),
+            None => smallvec![self.default_action],
+        }
+    }
+}
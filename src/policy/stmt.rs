@@ -3,23 +3,41 @@ use wasi_guard_macros::all_tuples;
 
 use super::{
     action::Action,
-    bound::{AbiArgBound, PredicateParams},
+    bound::{AbiArgBound, FromRawParams, PredicateParams},
 };
 use crate::util::Tuple;
 
 /// If `abi` [satisfies `bound`], then `action`.
-#[derive(Clone)]
-pub struct Statement<'desc, Params: Tuple + PredicateParams> {
-    bound: Option<AbiArgBound<'desc, Params>>,
+///
+/// The bound may also see `Ctx`, the context of the host making the call.
+pub struct Statement<'desc, Params: Tuple + PredicateParams, Ctx = ()> {
+    bound: Option<AbiArgBound<'desc, Params, Ctx>>,
     pub action: Action,
 }
+impl<Params: Tuple + PredicateParams, Ctx> Clone for Statement<'_, Params, Ctx> {
+    fn clone(&self) -> Self {
+        Self {
+            bound: self.bound.clone(),
+            action: self.action,
+        }
+    }
+}
+
+impl<'desc, Params: Tuple + PredicateParams, Ctx> Statement<'desc, Params, Ctx> {
+    /// A statement taking `action` on any `Params`, e.g., the post-call params of an
+    /// ABI which are not the arguments of its descriptor.
+    pub const fn new(action: Action) -> Self {
+        Statement {
+            bound: None,
+            action,
+        }
+    }
 
-impl<'desc, Params: Tuple + PredicateParams> Statement<'desc, Params> {
     // TODO: into const fn
     pub fn when<NewParams>(
         self,
-        bound: impl Into<AbiArgBound<'desc, NewParams>> + 'desc,
-    ) -> Statement<'desc, NewParams>
+        bound: impl Into<AbiArgBound<'desc, NewParams, Ctx>> + 'desc,
+    ) -> Statement<'desc, NewParams, Ctx>
     where
         NewParams: Tuple + PredicateParams,
     {
@@ -31,12 +49,13 @@ impl<'desc, Params: Tuple + PredicateParams> Statement<'desc, Params> {
     }
 
     // TODO: into const fn
-    pub fn and_when(self, other_bound: impl Into<AbiArgBound<'desc, Params>>) -> Self
+    pub fn and_when(self, other_bound: impl Into<AbiArgBound<'desc, Params, Ctx>>) -> Self
     where
         Params: Clone + 'desc,
+        Ctx: 'desc,
     {
         let Self { bound, action } = self;
-        let other_bound: AbiArgBound<Params> = other_bound.into();
+        let other_bound: AbiArgBound<Params, Ctx> = other_bound.into();
         let bound = match bound {
             None => other_bound,
             Some(b) => b.and(other_bound),
@@ -51,6 +70,36 @@ impl<'desc, Params: Tuple + PredicateParams> Statement<'desc, Params> {
         self.action = action;
         self
     }
+
+    /// The statement on `Raw`, the raw params of the ABI, which are converted into
+    /// `Params` before the bound is checked, so that statements typing the params
+    /// differently can be held by one [`WasiGuard`][guard] on `Raw`.
+    ///
+    /// [guard]: crate::policy::WasiGuard
+    pub fn into_raw<Raw>(self) -> Statement<'desc, Raw, Ctx>
+    where
+        Params: FromRawParams<Raw> + 'desc,
+        Raw: Tuple + PredicateParams + 'desc,
+        Ctx: 'desc,
+    {
+        let Self { bound, action } = self;
+        Statement {
+            bound: bound.map(AbiArgBound::into_raw),
+            action,
+        }
+    }
+}
+
+impl<'desc, Params: Tuple + PredicateParams + 'desc> Statement<'desc, Params> {
+    /// The statement checked in the context `Ctx`, which its bound ignores, e.g.,
+    /// to be held by a guard with other statements seeing the context.
+    pub fn in_context<Ctx>(self) -> Statement<'desc, Params, Ctx> {
+        let Self { bound, action } = self;
+        Statement {
+            bound: bound.map(AbiArgBound::in_context),
+            action,
+        }
+    }
 }
 
 macro_rules! impl_check_bound_for_statement {
@@ -67,9 +116,22 @@ macro_rules! impl_check_bound_for_statement {
                 self.bound.as_ref().map_or(true, |bound| bound.check(params))
             }
         }
+        impl<'desc, Ctx: 'desc, $($P,)*> Statement<'desc, ( $($P,)* ), Ctx>
+        where
+            ( $($P,)* ) : $crate::policy::bound::PredicateParams,
+            ( $($P,)* ) : $crate::util::Tuple + 'desc,
+        {
+            /// Checks if the bound is satisfied by the given parameters in `ctx`,
+            /// the context of the host making the call.
+            /// Returns `true` if there is no bound.
+            #[allow(unused)]
+            pub fn check_bound_in(&self, ctx: &Ctx, params: ( $($P,)* )) -> bool {
+                self.bound.as_ref().map_or(true, |bound| bound.check_in(ctx, params))
+            }
+        }
     };
 }
-all_tuples!(impl_check_bound_for_statement[0,10]: P);
+all_tuples!(impl_check_bound_for_statement[0,12]: P);
 
 macro_rules! replace_tt_with_dt {
     ($($tt:tt)*) => {
@@ -230,6 +292,48 @@ mod test {
         assert!(!statement.check_bound((0 - 1, false)));
     }
 
+    #[test]
+    fn post_call_statement() {
+        use crate::policy::stmt::Statement;
+
+        // arguments of `fd_write`, errno, and `nwritten`
+        let statement = Statement::<(u32, u32, u32, u32, u16, u64)>::new(Action::Log).when(
+            |fd: u32, _: u32, _: u32, _: u32, errno: u16, nwritten: u64| {
+                fd == 1 && errno == 0 && nwritten > 0
+            },
+        );
+        assert!(statement.check_bound((1, 0, 1, 0, 0, 8)));
+        assert!(!statement.check_bound((1, 0, 1, 0, 8, 0)));
+        assert!(Statement::<(u32, u16)>::new(Action::Kill).check_bound((3, 8)));
+    }
+
+    #[test]
+    fn raw_statements() {
+        use crate::policy::{stmt::Statement, WasiGuard};
+
+        // `fd_seek` takes `(fd: i32, offset: i64, whence: i32, newoffset_ptr: i32)`
+        type Raw = (i32, i64, i32, i32);
+        let guard: WasiGuard<Raw> = WasiGuard::from_arr([
+            Statement::<Raw>::new(Action::Kill)
+                .when(|fd: u32, off: i64, _: u8, _: u32| fd > 2 && off < 0)
+                .into_raw(),
+            Statement::<Raw>::new(Action::Log)
+                .when(|fd: i32, off: u64, _: i32, _: i32| fd == -1 || off > 1 << 32)
+                .into_raw(),
+            Statement::<Raw>::new(Action::Allow),
+        ]);
+        // -1 is a huge `u64` offset
+        assert_eq!(
+            guard.check((3, -1, 0, 0))[..],
+            [Action::Kill, Action::Log, Action::Allow]
+        );
+        assert_eq!(guard.check((3, 1, 0, 0))[..], [Action::Allow]);
+        assert_eq!(
+            guard.check((-1, 1 << 33, 0, 0))[..],
+            [Action::Log, Action::Allow]
+        );
+    }
+
     #[test]
     fn statement_macro() {
         use crate::statement;
@@ -3,16 +3,37 @@ use wasi_guard_macros::all_tuples;
 /// An extended Tuple trait of [`core::marker::Tuple`].
 pub trait Tuple: core::marker::Tuple {
     const LENGTH: usize;
+    /// The sizes in bytes of the elements.
+    const SIZES: &'static [usize];
 }
 macro_rules! impl_tuple_length {
     ($($t:ident),*) => {
         impl<$($t),*> Tuple for ($($t,)*) {
             const LENGTH: usize = $crate::__count_idents!($($t),*);
+            const SIZES: &'static [usize] = &[$(core::mem::size_of::<$t>()),*];
         }
     };
 }
 all_tuples!(impl_tuple_length[0, 20]: T);
 
+/// The type of the `I`-th element of a tuple, e.g., of an argument in the params of an ABI.
+pub trait Element<const I: usize> {
+    type Type;
+}
+macro_rules! impl_element {
+    ($($T:ident),*) => {
+        impl_element!(@each [$($T),*] [] $($T),*);
+    };
+    (@each [$($T:ident),*] [$($Before:ident,)*] $Current:ident $(, $After:ident)*) => {
+        impl<$($T),*> Element<{ $crate::__count_idents!($($Before),*) }> for ($($T,)*) {
+            type Type = $Current;
+        }
+        impl_element!(@each [$($T),*] [$($Before,)* $Current,] $($After),*);
+    };
+    (@each [$($T:ident),*] [$($Before:ident,)*]) => {};
+}
+all_tuples!(impl_element[1, 12]: T);
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -23,5 +44,13 @@ mod test {
         assert_eq!(<(i32,) as Tuple>::LENGTH, 1);
         struct Struct0;
         assert_eq!(<(i32, i64, Struct0) as Tuple>::LENGTH, 3);
+        assert_eq!(<(i32, i64, Struct0) as Tuple>::SIZES, [4, 8, 0]);
+    }
+
+    #[test]
+    fn tuple_element() {
+        let _: <(i32, i64, u8) as Element<0>>::Type = 0i32;
+        let _: <(i32, i64, u8) as Element<1>>::Type = 0i64;
+        let _: <(i32, i64, u8) as Element<2>>::Type = 0u8;
     }
 }
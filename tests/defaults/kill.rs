@@ -14,5 +14,35 @@ fn default() {
 
 #[test]
 fn got_killed_anyway() {
+    #[cfg(not(any(
+        feature = "wasmedge-sock-v1",
+        feature = "wasmedge-sock-v2",
+        feature = "wasix",
+        feature = "wasi-threads",
+        feature = "p2"
+    )))]
     assert_eq!(MUST_BE_KILLED_WASIS.len(), WASI_NAMES.len());
+    assert!(WASI_NAMES
+        .iter()
+        .all(|name| MUST_BE_KILLED_WASIS.contains(name)));
+    #[cfg(feature = "wasmedge-sock-v1")]
+    assert!(wasi_guard::wasi::wasmedge::v1::WASI_NAMES
+        .iter()
+        .all(|name| MUST_BE_KILLED_WASIS.contains(name)));
+    #[cfg(feature = "wasmedge-sock-v2")]
+    assert!(wasi_guard::wasi::wasmedge::v2::WASI_NAMES
+        .iter()
+        .all(|name| MUST_BE_KILLED_WASIS.contains(name)));
+    #[cfg(feature = "wasix")]
+    assert!(wasi_guard::wasi::wasix::WASI_NAMES
+        .iter()
+        .all(|name| MUST_BE_KILLED_WASIS.contains(name)));
+    #[cfg(feature = "wasi-threads")]
+    assert!(MUST_BE_KILLED_WASIS.contains(&"thread_spawn"));
+    #[cfg(feature = "p2")]
+    assert!(wasi_guard::wasi::p2::filesystem::types::WASI_NAMES
+        .iter()
+        // takes more args than a guard does
+        .filter(|name| **name != "descriptor_set_times_at")
+        .all(|name| MUST_BE_KILLED_WASIS.contains(name)));
 }
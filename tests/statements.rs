@@ -11,3 +11,14 @@ fn full_statement() {
     assert!(statement.check_bound((1, 233)));
     assert!(!statement.check_bound((0, 1 << 9)));
 }
+
+#[test]
+fn component_statement() {
+    use wasi_guard::wasi::p2::filesystem::types::descriptor_read_via_stream;
+    let statement = wasi_guard::statement!(descriptor_read_via_stream => Action::Kill);
+    let statement = statement.when(AbiArgBound::from(
+        |fd: u32, offset: u64, _ret_ptr: u32| -> bool { fd > 2 && offset > 0 },
+    ));
+    assert!(statement.check_bound((3, 1, 0)));
+    assert!(!statement.check_bound((1, 1, 0)));
+}
@@ -0,0 +1,59 @@
+use wasi_guard::{
+    policy::{action::Action, policy},
+    wasi::*,
+};
+
+policy! {
+    default = allow;
+    ret_errno(BADF) fd_write where |fd: u32, _: u32, _: u32, _: u32| fd == 0;
+    after log fd_write where |fd: u32, _: u32, _: u32, _: u32, errno: u16, nwritten: u64| {
+        fd > 2 && errno == 0 && nwritten > 4096
+    };
+    after kill fd_read;
+    after log @sock;
+    allow sock_recv;
+}
+
+#[test]
+fn post_call_guards() {
+    let guard = WASI_GUARD_AFTER_FD_WRITE.as_ref().unwrap();
+    assert_eq!(guard.check((3, 0, 1, 0, 0, 8192))[..], [Action::Log]);
+    assert!(guard.check((3, 0, 1, 0, ERRNO_IO.raw(), 8192)).is_empty());
+    assert!(guard.check((1, 0, 1, 0, 0, 8192)).is_empty());
+
+    // the guard before the call is kept apart
+    let guard = WASI_GUARD_FD_WRITE.as_ref().unwrap();
+    assert_eq!(
+        guard.check((0, 0, 1, 0))[..],
+        [Action::ReturnErrno(ERRNO_BADF.raw())]
+    );
+}
+
+#[test]
+fn default_post_params() {
+    // arguments of `fd_read`, the errno, and `nread`
+    let params: fd_read_guard_post_params_t = (3, 0, 1, 0, 0, 16);
+    let guard = WASI_GUARD_AFTER_FD_READ.as_ref().unwrap();
+    assert_eq!(guard.check(params)[..], [Action::Kill]);
+    assert!(WASI_GUARD_FD_READ.is_none());
+    // killed after the call, i.e., not before it
+    assert!(!MUST_BE_KILLED_WASIS.contains(&"fd_read"));
+}
+
+#[test]
+fn post_call_groups() {
+    // `sock_recv` has `nread` and `roflags` behind its out-pointers.
+    let params: sock_recv_guard_post_params_t = (3, 0, 1, 0, 0, 0, 0, 16, 0);
+    assert_eq!(
+        WASI_GUARD_AFTER_SOCK_RECV.as_ref().unwrap().check(params)[..],
+        [Action::Log]
+    );
+    assert_eq!(
+        WASI_GUARD_SOCK_RECV
+            .as_ref()
+            .unwrap()
+            .check((3, 0, 1, 0, 0, 0))[..],
+        [Action::Allow]
+    );
+    assert!(WASI_GUARD_AFTER_PATH_OPEN.is_none());
+}
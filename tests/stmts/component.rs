@@ -0,0 +1,28 @@
+#![cfg(feature = "p2")]
+use wasi_guard::policy::{action::Action, policy};
+
+policy! {
+    default = allow;
+    kill get_random_bytes where len > 4096;
+    ret_errno(ACCES) descriptor_read_via_stream where |this: u32, offset: u64, _ret_ptr: u32| {
+        this == 3 && offset > 0
+    };
+    log drop_descriptor;
+}
+
+#[test]
+fn component_guards() {
+    let guard = WASI_GUARD_GET_RANDOM_BYTES.as_ref().unwrap();
+    assert_eq!(guard.check((8192, 0))[0], Action::Kill);
+    assert!(guard.check((16, 0)).is_empty());
+    let guard = WASI_GUARD_DESCRIPTOR_READ_VIA_STREAM.as_ref().unwrap();
+    assert!(matches!(guard.check((3, 1, 0))[0], Action::ReturnErrno(_)));
+    assert!(guard.check((4, 1, 0)).is_empty());
+    assert!(WASI_GUARD_DROP_DESCRIPTOR.is_some());
+}
+
+#[test]
+fn component_policy_set() {
+    // `descriptor_read_via_stream` is imported as `[method]descriptor.read-via-stream`
+    assert_eq!(policy_set().validate(), Ok(()));
+}
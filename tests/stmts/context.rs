@@ -0,0 +1,68 @@
+use wasi_guard::{
+    policy::{action::Action, policy},
+    wasi::*,
+};
+
+pub struct GuestCtx {
+    pub tenant: &'static str,
+    pub allowed_fds: Vec<u32>,
+}
+
+policy! {
+    default = allow;
+    kill fd_write where |ctx: &GuestCtx, fd: u32, _: u32, _: u32, _: u32| !ctx.allowed_fds.contains(&fd);
+    // ignores the context, but is checked in it as well
+    log fd_write where |fd: u32, _: u32, _: u32, _: u32| fd > 2;
+    ret_errno(PERM) fd_read where |ctx: &GuestCtx, _: u32, _: u32, _: u32, _: u32| ctx.tenant == "guest",
+        fd > 2 && !ctx.allowed_fds.contains(&fd);
+    after log fd_close where |ctx: &GuestCtx, _: u32, errno: u16| ctx.tenant == "guest" && errno != 0;
+    kill proc_exit where |code: u32| code != 0;
+}
+
+fn guest() -> GuestCtx {
+    GuestCtx {
+        tenant: "guest",
+        allowed_fds: vec![0, 1, 2, 5],
+    }
+}
+
+#[test]
+fn statements_in_context() {
+    let guard = WASI_GUARD_FD_WRITE.as_ref().unwrap();
+    assert!(guard.check_in(&guest(), (1, 0, 0, 0)).is_empty());
+    assert_eq!(guard.check_in(&guest(), (5, 0, 0, 0))[..], [Action::Log]);
+    assert_eq!(
+        guard.check_in(&guest(), (4, 0, 0, 0))[..],
+        [Action::Kill, Action::Log]
+    );
+
+    let guard = WASI_GUARD_FD_READ.as_ref().unwrap();
+    assert_eq!(
+        guard.check_in(&guest(), (4, 0, 0, 0))[..],
+        [Action::ReturnErrno(ERRNO_PERM.raw())]
+    );
+    assert!(guard.check_in(&guest(), (5, 0, 0, 0)).is_empty());
+    let host = GuestCtx {
+        tenant: "host",
+        allowed_fds: Vec::new(),
+    };
+    assert!(guard.check_in(&host, (4, 0, 0, 0)).is_empty());
+
+    let guard = WASI_GUARD_AFTER_FD_CLOSE.as_ref().unwrap();
+    assert_eq!(guard.check_in(&guest(), (3, 8))[..], [Action::Log]);
+    assert!(guard.check_in(&host, (3, 8)).is_empty());
+
+    // guards without the context are checked as before
+    let guard = WASI_GUARD_PROC_EXIT.as_ref().unwrap();
+    assert_eq!(guard.check((1,))[..], [Action::Kill]);
+}
+
+#[test]
+fn policy_set_in_context() {
+    let set = policy_set();
+    let actions =
+        set.check_in::<fd_write_params_raw_t, _>("fd_write", false, &guest(), (4, 0, 0, 0));
+    assert_eq!(actions[..], [Action::Kill, Action::Log]);
+    let actions = set.check_in::<proc_exit_params_raw_t, _>("proc_exit", false, &guest(), (1,));
+    assert_eq!(actions[..], [Action::Kill]);
+}
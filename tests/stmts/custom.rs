@@ -0,0 +1,52 @@
+use wasi_guard::{
+    policy::{action::Action, policy},
+    wasi::*,
+};
+
+mod host {
+    pub mod abi {
+        wasi_guard::wasi::declare_host_abis!("env";
+            host_http_fetch(url_ptr: u32, url_len: u32, resp_ptr: u32);
+            kv_get(key_ptr, key_len, val_ptr);
+            kv_put(key_ptr, key_len, val_ptr, val_len);
+        );
+    }
+}
+
+policy! {
+    use descriptors host::abi;
+    default = allow;
+    kill host_http_fetch where |_url_ptr: u32, url_len: u32, _resp_ptr: u32| url_len > 1024;
+    ret_errno(PERM) kv_put;
+    kill kv_get;
+    log fd_write;
+}
+
+#[test]
+fn custom_guards() {
+    let guard = WASI_GUARD_HOST_HTTP_FETCH.as_ref().unwrap();
+    assert!(guard.check((0, 64, 0)).is_empty());
+    assert_eq!(guard.check((0, 4096, 0))[..], [Action::Kill]);
+
+    let params: kv_put_guard_params_t = (0, 0, 0, 0);
+    assert_eq!(
+        WASI_GUARD_KV_PUT.as_ref().unwrap().check(params)[..],
+        [Action::ReturnErrno(ERRNO_PERM.raw())]
+    );
+    // WASI ABIs are still resolved in `wasi_guard::wasi`
+    let params: fd_write_guard_params_t = fd_write_params_default_t::default();
+    assert_eq!(
+        WASI_GUARD_FD_WRITE.as_ref().unwrap().check(params)[..],
+        [Action::Log]
+    );
+}
+
+#[test]
+fn custom_descriptors() {
+    assert!(MUST_BE_KILLED_WASIS.contains(&"kv_get"));
+    assert!(!MUST_BE_KILLED_WASIS.contains(&"host_http_fetch"));
+    assert_eq!(CUSTOM_DESCRIPTORS.len(), 1);
+    assert!(CUSTOM_DESCRIPTORS[0]
+        .iter()
+        .any(|desc| desc.is("env", "kv_put")));
+}
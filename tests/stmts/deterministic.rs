@@ -0,0 +1,123 @@
+use wasi_guard::{
+    policy::{
+        action::Action,
+        emulate::Tick,
+        preset::{Deterministic, DeterministicConfig, Nondeterministic},
+        PolicySet,
+    },
+    wasi::*,
+};
+
+static GUEST: Deterministic = Deterministic::new(DeterministicConfig::new(42));
+
+/// Runs a guest reading the time twice and 16 random bytes, as a host answering
+/// the emulated calls does, returning its memory.
+fn run(set: &PolicySet) -> Vec<u8> {
+    let mut memory = vec![0u8; 32];
+    let mut answer = |actions: &[Action], params: &[i64]| {
+        let [Action::Emulate(emulate)] = actions else {
+            panic!("expected an emulation, got {actions:?}");
+        };
+        assert_eq!(emulate.apply(params, &mut memory), ERRNO_SUCCESS.raw());
+    };
+    answer(
+        &set.check::<clock_time_get_params_raw_t>("clock_time_get", false, (0, 0, 0)),
+        &[0, 0, 0],
+    );
+    answer(
+        &set.check::<clock_time_get_params_raw_t>("clock_time_get", false, (1, 0, 8)),
+        &[1, 0, 8],
+    );
+    answer(
+        &set.check::<random_get_params_raw_t>("random_get", false, (16, 16)),
+        &[16, 16],
+    );
+    answer(
+        &set.check::<sched_yield_params_raw_t>("sched_yield", false, ()),
+        &[],
+    );
+    memory
+}
+
+#[test]
+fn replay_bit_for_bit() {
+    let set = GUEST.policy_set();
+    assert_eq!(set.validate(), Ok(()));
+    let first = run(&set);
+    assert_eq!(first[..8], 0u64.to_le_bytes());
+    assert_eq!(first[8..16], 1_000_000u64.to_le_bytes());
+    GUEST.reset();
+    assert_eq!(run(&set), first);
+    assert_ne!(run(&set), first);
+}
+
+#[test]
+fn nondeterministic_calls() {
+    static ERRNO: Deterministic = Deterministic::new(DeterministicConfig {
+        nondeterministic: Nondeterministic::Errno(ERRNO_NOTCAPABLE.raw()),
+        tick: Tick::PerFuel(10),
+        ..DeterministicConfig::new(7)
+    });
+    let set = ERRNO.policy_set();
+    let denied = [Action::ReturnErrno(ERRNO_NOTCAPABLE.raw())];
+    assert_eq!(
+        set.check::<fd_write_params_raw_t>("fd_write", false, (1, 0, 1, 0))[..],
+        [Action::Allow]
+    );
+    assert_eq!(
+        set.check::<fd_write_params_raw_t>("fd_write", false, (3, 0, 1, 0))[..],
+        denied
+    );
+    assert_eq!(
+        set.check::<fd_read_params_raw_t>("fd_read", false, (0, 0, 1, 0))[..],
+        denied
+    );
+    assert_eq!(
+        set.check::<proc_exit_params_raw_t>("proc_exit", false, (0,))[..],
+        [Action::Allow]
+    );
+
+    ERRNO.clock().set_fuel(5);
+    assert_eq!(ERRNO.clock().now(), 50);
+
+    let set = GUEST.policy_set();
+    assert_eq!(
+        set.check::<path_open_params_raw_t>("path_open", false, (3, 0, 0, 0, 0, 0, 0, 0, 0))[..],
+        [Action::Kill]
+    );
+}
+
+#[test]
+fn args_env_and_sleep() {
+    static APP: Deterministic = Deterministic::new(DeterministicConfig {
+        args: &["app"],
+        env: &["LANG=C"],
+        ..DeterministicConfig::new(1)
+    });
+    let set = APP.policy_set();
+    let mut memory = vec![0u8; 120];
+    // A subscription to a relative timeout of 5ms, at 24.
+    memory[24 + 24..24 + 32].copy_from_slice(&5_000_000u64.to_le_bytes());
+    let mut answer = |actions: &[Action], params: &[i64]| {
+        let [Action::Emulate(emulate)] = actions else {
+            panic!("expected an emulation, got {actions:?}");
+        };
+        assert_eq!(emulate.apply(params, &mut memory), ERRNO_SUCCESS.raw());
+    };
+    answer(
+        &set.check::<args_sizes_get_params_raw_t>("args_sizes_get", false, (0, 4)),
+        &[0, 4],
+    );
+    answer(
+        &set.check::<environ_get_params_raw_t>("environ_get", false, (8, 12)),
+        &[8, 12],
+    );
+    answer(
+        &set.check::<poll_oneoff_params_raw_t>("poll_oneoff", false, (24, 72, 1, 112)),
+        &[24, 72, 1, 112],
+    );
+    assert_eq!(memory[..8], [1, 0, 0, 0, 4, 0, 0, 0]);
+    assert_eq!(memory[8..12], 12u32.to_le_bytes());
+    assert_eq!(memory[12..19], *b"LANG=C\0");
+    assert!(APP.clock().now() >= 5_000_000);
+}
@@ -0,0 +1,30 @@
+#![cfg(all(feature = "wasix", feature = "wasi-threads"))]
+use wasi_guard::{
+    policy::{action::Action, policy},
+    wasi::*,
+};
+
+policy! {
+    default = allow;
+    kill proc_fork;
+    ret_errno(NOTSUP) thread_spawn where |start_arg: u32| start_arg == 0;
+    log fd_pipe;
+}
+
+#[test]
+fn dialect_guards() {
+    assert!(MUST_BE_KILLED_WASIS.contains(&"proc_fork"));
+    let guard = WASI_GUARD_THREAD_SPAWN.as_ref().unwrap();
+    assert_eq!(
+        guard.check((0,))[0],
+        Action::ReturnErrno(ERRNO_NOTSUP.raw())
+    );
+    assert!(WASI_GUARD_FD_PIPE.is_some());
+    assert!(WASI_GUARD_FUTEX_WAIT.is_none());
+}
+
+#[test]
+fn dialect_policy_set() {
+    // `thread_spawn` is imported as `thread-spawn`
+    assert_eq!(policy_set().validate(), Ok(()));
+}
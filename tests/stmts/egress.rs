@@ -0,0 +1,64 @@
+#![cfg(feature = "wasmedge-sock-v1")]
+use wasi_guard::{
+    policy::{
+        action::Action,
+        net::{dst_in, read_address, Cidr, EgressRules},
+        policy,
+    },
+    wasi::*,
+};
+
+pub struct Guest {
+    pub memory: Vec<u8>,
+    pub egress: EgressRules,
+}
+
+const PRIVATE: Cidr = Cidr::v4([10, 0, 0, 0], 8);
+
+policy! {
+    default = allow;
+    ret_errno(ACCES) sock_connect where |guest: &Guest, _: u32, addr_ptr: u32, port_num: u32| {
+        !guest.egress.permits_at(&guest.memory, addr_ptr, port_num)
+    };
+    log sock_connect where |guest: &Guest, _: u32, addr_ptr: u32, port_num: u32| {
+        read_address(&guest.memory, addr_ptr, port_num).is_some_and(|dst| dst_in(dst, PRIVATE, 443))
+    };
+    kill sock_bind where port_num < 1024;
+}
+
+/// A guest with the `WasiAddress`es of `10.0.0.1` at 0, and of `fd00::1` at 8.
+fn guest() -> Guest {
+    let mut memory = vec![0; 48];
+    memory[..8].copy_from_slice(&[16, 0, 0, 0, 4, 0, 0, 0]);
+    memory[8..16].copy_from_slice(&[32, 0, 0, 0, 16, 0, 0, 0]);
+    memory[16..20].copy_from_slice(&[10, 0, 0, 1]);
+    memory[32] = 0xfd;
+    memory[47] = 1;
+    let egress = EgressRules::new()
+        .allow("10.0.0.0/8", 443)
+        .unwrap()
+        .allow("fd00::/8", 8000..=8999)
+        .unwrap();
+    Guest { memory, egress }
+}
+
+#[test]
+fn egress_allowlists() {
+    let guest = guest();
+    let guard = WASI_GUARD_SOCK_CONNECT.as_ref().unwrap();
+    assert_eq!(guard.check_in(&guest, (3, 0, 443))[..], [Action::Log]);
+    assert_eq!(
+        guard.check_in(&guest, (3, 0, 80))[..],
+        [Action::ReturnErrno(ERRNO_ACCES.raw())]
+    );
+    assert!(guard.check_in(&guest, (3, 8, 8080)).is_empty());
+    // out of the memory
+    assert_eq!(
+        guard.check_in(&guest, (3, 44, 443))[..],
+        [Action::ReturnErrno(ERRNO_ACCES.raw())]
+    );
+
+    let guard = WASI_GUARD_SOCK_BIND.as_ref().unwrap();
+    assert_eq!(guard.check((3, 0, 80))[..], [Action::Kill]);
+    assert!(guard.check((3, 0, 8080)).is_empty());
+}
@@ -0,0 +1,64 @@
+use wasi_guard::{
+    policy::{
+        action::{Action, Emulate, WasiErrno},
+        emulate::{FixedClock, SeededRandom, Strings},
+        memory::GuestMemory,
+        policy,
+    },
+    wasi::*,
+};
+
+static CLOCK: FixedClock = FixedClock(1_700_000_000_000_000_000);
+static RANDOM: SeededRandom = SeededRandom::new(42);
+const ENV: Strings = Strings::get(&["LANG=C"]);
+const ENV_SIZES: Strings = Strings::sizes_get(&["LANG=C"]);
+
+policy! {
+    default = allow;
+    emulate(Emulate::new("clock", &CLOCK)) clock_time_get;
+    emulate(Emulate::new("random", &RANDOM)) random_get where buf_len <= 32;
+    emulate(Emulate::new("environ", &ENV)) environ_get;
+    emulate(Emulate::new("environ_sizes", &ENV_SIZES)) environ_sizes_get;
+}
+
+/// Answers the call by the emulation among `actions` as a host does, if any.
+fn emulate(actions: &[Action], params: &[i64], mut memory: &mut [u8]) -> Option<WasiErrno> {
+    actions.iter().find_map(|action| match action {
+        Action::Emulate(emulate) => Some(emulate.apply(params, &mut memory)),
+        _ => None,
+    })
+}
+
+#[test]
+fn emulate_calls() {
+    let mut memory = vec![0u8; 64];
+
+    let actions = WASI_GUARD_CLOCK_TIME_GET.as_ref().unwrap().check((0, 0, 8));
+    assert_eq!(emulate(&actions, &[0, 0, 8], &mut memory), Some(0));
+    assert_eq!(
+        memory.read(8, 8),
+        Some(&1_700_000_000_000_000_000u64.to_le_bytes()[..])
+    );
+
+    let guard = WASI_GUARD_RANDOM_GET.as_ref().unwrap();
+    assert_eq!(
+        emulate(&guard.check((16, 16)), &[16, 16], &mut memory),
+        Some(0)
+    );
+    assert!(memory[16..32].iter().any(|&byte| byte != 0));
+    assert!(guard.check((0, 64)).is_empty());
+
+    let actions = WASI_GUARD_ENVIRON_SIZES_GET
+        .as_ref()
+        .unwrap()
+        .check((32, 36));
+    assert_eq!(emulate(&actions, &[32, 36], &mut memory), Some(0));
+    assert_eq!(memory.read(32, 8), Some(&[1, 0, 0, 0, 7, 0, 0, 0][..]));
+    let actions = WASI_GUARD_ENVIRON_GET.as_ref().unwrap().check((40, 44));
+    assert_eq!(emulate(&actions, &[40, 44], &mut memory), Some(0));
+    assert_eq!(memory.read(40, 11), Some(&b"\x2c\0\0\0LANG=C\0"[..]));
+    assert_eq!(
+        emulate(&actions, &[40, 60], &mut memory),
+        Some(ERRNO_FAULT.raw())
+    );
+}
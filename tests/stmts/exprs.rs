@@ -0,0 +1,65 @@
+use wasi_guard::{
+    policy::{action::Action, policy},
+    wasi::*,
+};
+
+policy! {
+    default = allow;
+    ret_errno(ACCES) fd_write where fd > 2 && iovs_len < 16;
+    log fd_write where fd == 1;
+    kill path_open where oflags.contains(Oflags::CREAT), fd != 3;
+    after log fd_read where errno == Errno::SUCCESS.raw() && size > 1 << 20;
+    after kill path_open where fd_out > 1024;
+}
+
+#[test]
+fn expression_bounds() {
+    let params: fd_write_guard_params_t = (3, 0, 1, 0);
+    let _: fd_write_params_t = params;
+    let guard = WASI_GUARD_FD_WRITE.as_ref().unwrap();
+    assert_eq!(
+        guard.check(params)[..],
+        [Action::ReturnErrno(ERRNO_ACCES.raw())]
+    );
+    assert_eq!(guard.check((1, 0, 1, 0))[..], [Action::Log]);
+    assert!(guard.check((3, 0, 16, 0)).is_empty());
+
+    let guard = WASI_GUARD_PATH_OPEN.as_ref().unwrap();
+    let params = (
+        4,
+        Lookupflags::empty(),
+        0,
+        0,
+        Oflags::CREAT,
+        Rights::empty(),
+        Rights::empty(),
+        Fdflags::empty(),
+        0,
+    );
+    assert_eq!(guard.check(params)[..], [Action::Kill]);
+}
+
+#[test]
+fn post_call_expression_bounds() {
+    let guard = WASI_GUARD_AFTER_FD_READ.as_ref().unwrap();
+    assert_eq!(guard.check((3, 0, 1, 0, 0, 2 << 20))[..], [Action::Log]);
+    assert!(guard
+        .check((3, 0, 1, 0, ERRNO_IO.raw(), 2 << 20))
+        .is_empty());
+
+    let guard = WASI_GUARD_AFTER_PATH_OPEN.as_ref().unwrap();
+    let params: path_open_guard_post_params_t = (
+        3,
+        Lookupflags::empty(),
+        0,
+        0,
+        Oflags::empty(),
+        Rights::empty(),
+        Rights::empty(),
+        Fdflags::empty(),
+        0,
+        0,
+        2048,
+    );
+    assert_eq!(guard.check(params)[..], [Action::Kill]);
+}
@@ -0,0 +1,154 @@
+use wasi_guard::{
+    policy::{
+        action::Action,
+        fd::{FdError, FdKind, FdTable},
+        policy,
+    },
+    wasi::*,
+};
+
+policy! {
+    default = allow;
+    ret_errno(BADF) fd_write where |fds: &FdTable, fd: u32, _: u32, _: u32, _: u32| !fds.is_open(fd);
+    kill fd_write where |fds: &FdTable, fd: u32, _: u32, _: u32, _: u32| {
+        fds.get(fd).is_ok_and(|entry| entry.kind() == FdKind::Socket)
+    };
+    ret_errno(ACCES) path_open where |fds: &FdTable, fd: u32, _: u32, _: u32, _: u32, _: u16, _: u64, _: u64, _: u16, _: u32| {
+        fds.get(fd).is_ok_and(|dir| !dir.path().starts_with("/tmp"))
+    };
+    log fd_read where |fds: &FdTable, fd: u32, _: u32, _: u32, _: u32| {
+        fds.get(fd).is_ok_and(|entry| entry.kind() == FdKind::File)
+    };
+    after log path_open where |_: i32, _: i32, _: i32, _: i32, _: i32, _: i64, _: i64, _: i32, _: i32, errno: u16, _: u64| {
+        errno == 0
+    };
+}
+
+#[test]
+fn guard_by_fds() {
+    let mut fds = FdTable::with_stdio();
+    fds.preopen(3, "/tmp");
+    fds.preopen(4, "/data");
+    fds.path_open(3, "out.log", false, 5).unwrap();
+    fds.sock_open(6);
+
+    let guard = WASI_GUARD_FD_WRITE.as_ref().unwrap();
+    assert!(guard.check_in(&fds, (1, 0, 0, 0)).is_empty());
+    assert!(guard.check_in(&fds, (5, 0, 0, 0)).is_empty());
+    assert_eq!(guard.check_in(&fds, (6, 0, 0, 0))[..], [Action::Kill]);
+    fds.close(5).unwrap();
+    assert_eq!(
+        guard.check_in(&fds, (5, 0, 0, 0))[..],
+        [Action::ReturnErrno(ERRNO_BADF.raw())]
+    );
+
+    let guard = WASI_GUARD_PATH_OPEN.as_ref().unwrap();
+    assert!(guard.check_in(&fds, (3, 0, 0, 0, 0, 0, 0, 0, 0)).is_empty());
+    assert_eq!(
+        guard.check_in(&fds, (4, 0, 0, 0, 0, 0, 0, 0, 0))[..],
+        [Action::ReturnErrno(ERRNO_ACCES.raw())]
+    );
+}
+
+#[test]
+fn closed_fds_in_args() {
+    let mut fds = FdTable::with_stdio();
+    fds.preopen(3, "/tmp");
+    let renumber = descriptor_by_name("wasi_snapshot_preview1", "fd_renumber").unwrap();
+    assert_eq!(fds.check_fds(renumber, &[1, 3]), Ok(()));
+    assert_eq!(
+        fds.check_fds(renumber, &[1, 7]),
+        Err(FdError::NeverOpened(7))
+    );
+    fds.close(3).unwrap();
+    assert_eq!(fds.check_fds(renumber, &[3, 1]), Err(FdError::Closed(3)));
+    // out-pointers are not fds
+    let open = descriptor_by_name("wasi_snapshot_preview1", "path_open").unwrap();
+    assert_eq!(fds.check_fds(open, &[1, 0, 0, 0, 0, 0, 0, 0, 42]), Ok(()));
+}
+
+#[test]
+fn update_after_calls() {
+    let mut fds = FdTable::with_stdio();
+    fds.preopen(3, "/tmp");
+    let mut memory = vec![0; 32];
+    memory[16..23].copy_from_slice(b"out.log");
+
+    // `path_open(3, _, "out.log", ...)` opening the fd 5
+    let open = descriptor_by_name("wasi_snapshot_preview1", "path_open").unwrap();
+    let params: path_open_post_params_raw_t = (3, 0, 16, 7, 0, 0, 0, 0, 8, 0, 5);
+    let guard = WASI_GUARD_AFTER_PATH_OPEN.as_ref().unwrap();
+    assert_eq!(guard.check(params)[..], [Action::Log]);
+    fds.update_from(open, &[3, 0, 16, 7, 0, 0, 0, 0, 8, 0, 5], &memory)
+        .unwrap();
+    assert_eq!(fds.get(5).unwrap().path(), "/tmp/out.log");
+    assert_eq!(fds.get(5).unwrap().kind(), FdKind::File);
+    // a failed call opens nothing
+    let noent = ERRNO_NOENT.raw();
+    assert!(guard
+        .check((3, 0, 16, 7, 0, 0, 0, 0, 8, noent, 6))
+        .is_empty());
+    fds.update_from(
+        open,
+        &[3, 0, 16, 7, 0, 0, 0, 0, 8, noent.into(), 6],
+        &memory,
+    )
+    .unwrap();
+    assert_eq!(fds.get(6), Err(FdError::NeverOpened(6)));
+    assert_eq!(
+        fds.update_from(open, &[3, 0, 30, 7, 0, 0, 0, 0, 8, 0, 6], &memory),
+        Err(FdError::InvalidPath(6))
+    );
+    fds.update_from(open, &[3, 0, 16, 3, 0, 0, 0, 0, 8, 0, 6], &memory)
+        .unwrap();
+    assert_eq!(fds.get(6).unwrap().path(), "/tmp/out");
+
+    let renumber = descriptor_by_name("wasi_snapshot_preview1", "fd_renumber").unwrap();
+    fds.update_from(renumber, &[5, 6, 0], &memory).unwrap();
+    assert_eq!(fds.get(5), Err(FdError::Closed(5)));
+    assert_eq!(fds.get(6).unwrap().path(), "/tmp/out.log");
+    let close = descriptor_by_name("wasi_snapshot_preview1", "fd_close").unwrap();
+    fds.update_from(close, &[6, 0], &memory).unwrap();
+    assert_eq!(fds.get(6), Err(FdError::Closed(6)));
+}
+
+#[test]
+fn track_fds_in_policy_set() {
+    let mut fds = FdTable::with_stdio();
+    fds.preopen(3, "/tmp");
+    let set = policy_set().with_fd_table(fds);
+    let mut memory = vec![0; 32];
+    memory[16..23].copy_from_slice(b"out.log");
+
+    // `path_open(3, _, "out.log", ...)` opening the fd 5
+    let open = descriptor_by_name("wasi_snapshot_preview1", "path_open").unwrap();
+    let args = [3, 0, 16, 7, 0, 0, 0, 0, 8];
+    assert_eq!(set.before_call(open, &args), Ok(()));
+    assert!(set
+        .check_tracked::<path_open_params_raw_t>("path_open", false, (3, 0, 16, 7, 0, 0, 0, 0, 8))
+        .is_empty());
+    let read = descriptor_by_name("wasi_snapshot_preview1", "fd_read").unwrap();
+    assert_eq!(
+        set.before_call(read, &[5, 0, 0, 0]),
+        Err(FdError::NeverOpened(5))
+    );
+    set.after_call(open, &[3, 0, 16, 7, 0, 0, 0, 0, 8, 0, 5], &memory)
+        .unwrap();
+
+    assert_eq!(set.before_call(read, &[5, 0, 0, 0]), Ok(()));
+    assert_eq!(
+        set.check_tracked::<fd_read_params_raw_t>("fd_read", false, (5, 0, 0, 0))[..],
+        [Action::Log]
+    );
+    assert!(set
+        .check_tracked::<fd_read_params_raw_t>("fd_read", false, (3, 0, 0, 0))
+        .is_empty());
+
+    let close = descriptor_by_name("wasi_snapshot_preview1", "fd_close").unwrap();
+    set.after_call(close, &[5, 0], &memory).unwrap();
+    assert_eq!(
+        set.before_call(read, &[5, 0, 0, 0]),
+        Err(FdError::Closed(5))
+    );
+    assert_eq!(set.fd_table().unwrap().get(5), Err(FdError::Closed(5)));
+}
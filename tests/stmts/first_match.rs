@@ -0,0 +1,54 @@
+use wasi_guard::{
+    policy::{action::Action, policy, Mode},
+    wasi::*,
+};
+
+policy! {
+    mode = first_match;
+    default = log;
+    allow fd_write where |fd: u32, _: u32, _: u32, _: u32| fd <= 2;
+    ret_errno(ACCES) fd_write;
+    kill fd_read where |fd: u32, _: u32, _: u32, _: u32| fd == 0;
+    after log fd_read;
+    after allow fd_read;
+    allow fd_sync where |fd: u32| fd <= 2;
+    kill fd_sync;
+    kill fd_datasync;
+    allow fd_datasync where |fd: u32| fd <= 2;
+}
+
+#[test]
+fn first_satisfied_statement() {
+    assert_eq!(MODE, Mode::FirstMatch);
+    let guard = WASI_GUARD_FD_WRITE.as_ref().unwrap();
+    assert_eq!(guard.mode(), Mode::FirstMatch);
+    assert_eq!(guard.check((1, 0, 0, 0))[..], [Action::Allow]);
+    assert_eq!(
+        guard.check((3, 0, 0, 0))[..],
+        [Action::ReturnErrno(ERRNO_ACCES.raw())]
+    );
+
+    let guard = WASI_GUARD_AFTER_FD_READ.as_ref().unwrap();
+    assert_eq!(guard.check((0, 0, 0, 0, 0, 0))[..], [Action::Log]);
+}
+
+#[test]
+fn fall_back_to_default() {
+    let guard = WASI_GUARD_FD_READ.as_ref().unwrap();
+    assert_eq!(guard.check((0, 0, 0, 0))[..], [Action::Kill]);
+    assert!(guard.check((3, 0, 0, 0)).is_empty());
+    assert_eq!(
+        guard.check_or((3, 0, 0, 0), DEFUALT_ACTION)[..],
+        [Action::Log]
+    );
+}
+
+#[test]
+fn killed_by_first_statement() {
+    // `kill fd_sync` is only reached when `fd > 2`
+    assert!(!MUST_BE_KILLED_WASIS.contains(&"fd_sync"));
+    assert!(MUST_BE_KILLED_WASIS.contains(&"fd_datasync"));
+    let guard = WASI_GUARD_FD_SYNC.as_ref().unwrap();
+    assert_eq!(guard.check((1,))[..], [Action::Allow]);
+    assert_eq!(guard.check((3,))[..], [Action::Kill]);
+}
@@ -0,0 +1,54 @@
+use wasi_guard::{
+    policy::{action::Action, policy},
+    wasi::*,
+};
+
+policy! {
+    default = allow;
+    ret_errno(PERM) @fs;
+    kill @sock;
+    log path_*;
+    // explicit statements take precedence over the groups
+    allow fd_close;
+    ret_errno(ACCES) fd_write where |fd: u32, _: u32, _: u32, _: u32| fd > 2;
+    kill sock_shutdown where |_fd: u32, how: Sdflags| how.contains(Sdflags::WR);
+}
+
+#[test]
+fn group_statements() {
+    let actions = WASI_GUARD_FD_READ.as_ref().unwrap().check((3, 0, 0, 0));
+    assert_eq!(actions[..], [Action::ReturnErrno(ERRNO_PERM.raw())]);
+
+    let actions = WASI_GUARD_PATH_UNLINK_FILE
+        .as_ref()
+        .unwrap()
+        .check((3, 0, 0));
+    assert_eq!(
+        actions[..],
+        [Action::ReturnErrno(ERRNO_PERM.raw()), Action::Log]
+    );
+    assert!(WASI_GUARD_PROC_EXIT.is_none());
+    assert!(WASI_GUARD_POLL_ONEOFF.is_none());
+}
+
+#[test]
+fn explicit_statements_first() {
+    assert_eq!(
+        WASI_GUARD_FD_CLOSE.as_ref().unwrap().check((3,))[..],
+        [Action::Allow]
+    );
+    let guard = WASI_GUARD_FD_WRITE.as_ref().unwrap();
+    assert!(guard.check((1, 0, 0, 0)).is_empty());
+    assert_eq!(
+        guard.check((3, 0, 0, 0))[..],
+        [Action::ReturnErrno(ERRNO_ACCES.raw())]
+    );
+}
+
+#[test]
+fn killed_groups() {
+    assert!(MUST_BE_KILLED_WASIS.contains(&"sock_accept"));
+    assert!(MUST_BE_KILLED_WASIS.contains(&"sock_recv"));
+    assert!(!MUST_BE_KILLED_WASIS.contains(&"sock_shutdown"));
+    assert!(!MUST_BE_KILLED_WASIS.contains(&"fd_read"));
+}
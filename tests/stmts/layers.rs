@@ -0,0 +1,132 @@
+use wasi_guard::policy::{action::Action, GuardLayers, Mode};
+
+mod baseline {
+    use wasi_guard::policy::policy;
+
+    policy! {
+        layer baseline;
+        default = kill;
+        allow fd_write where fd <= 2;
+        ret_errno(PERM) @sock;
+        log fd_read;
+    }
+}
+
+mod team {
+    use wasi_guard::policy::policy;
+
+    policy! {
+        extends super::baseline::baseline;
+        layer team;
+        mode = first_match;
+        ret_errno(ACCES) fd_write where fd > 2;
+    }
+}
+
+mod service {
+    use wasi_guard::policy::policy;
+
+    policy! {
+        extends super::team::team;
+        default = allow;
+        override allow sock_shutdown;
+        override kill fd_read where fd == 0;
+    }
+}
+
+fn layers_of(layers: &[GuardLayers], wasi: &str, after: bool) -> &'static [&'static str] {
+    layers
+        .iter()
+        .find(|layers| layers.wasi == wasi && layers.after == after)
+        .unwrap()
+        .layers
+}
+
+#[test]
+fn layer_itself() {
+    assert_eq!(baseline::DEFAULT_ACTION_LAYER, "baseline");
+    assert_eq!(
+        layers_of(baseline::GUARD_LAYERS, "fd_write", false),
+        ["baseline"]
+    );
+    assert!(baseline::MUST_BE_KILLED_WASIS.contains(&"proc_exit"));
+}
+
+#[test]
+fn extend_layers() {
+    assert_eq!(team::DEFUALT_ACTION, Action::Kill);
+    assert_eq!(team::DEFAULT_ACTION_LAYER, "baseline");
+    assert_eq!(team::MODE, Mode::FirstMatch);
+    assert_eq!(
+        layers_of(team::GUARD_LAYERS, "fd_write", false),
+        ["baseline", "team"]
+    );
+
+    let guard = service::WASI_GUARD_FD_WRITE.as_ref().unwrap();
+    assert_eq!(guard.mode(), Mode::FirstMatch);
+    assert_eq!(guard.check((1, 0, 0, 0))[..], [Action::Allow]);
+    assert_eq!(
+        guard.check((3, 0, 0, 0))[..],
+        [Action::ReturnErrno(wasi_guard::wasi::ERRNO_ACCES.raw())]
+    );
+    assert_eq!(service::DEFUALT_ACTION, Action::Allow);
+    assert_eq!(service::DEFAULT_ACTION_LAYER, "policy");
+    assert_eq!(service::MUST_BE_KILLED_WASIS.len(), 0);
+}
+
+#[test]
+fn override_statements() {
+    let guard = service::WASI_GUARD_SOCK_SHUTDOWN.as_ref().unwrap();
+    assert_eq!(guard.check((3, 0))[..], [Action::Allow]);
+    assert_eq!(
+        layers_of(service::GUARD_LAYERS, "sock_shutdown", false),
+        ["policy"]
+    );
+    assert_eq!(
+        layers_of(service::GUARD_LAYERS, "sock_accept", false),
+        ["baseline"]
+    );
+
+    let guard = service::WASI_GUARD_FD_READ.as_ref().unwrap();
+    assert_eq!(guard.check((0, 0, 0, 0))[..], [Action::Kill]);
+    assert!(guard.check((3, 0, 0, 0)).is_empty());
+}
+
+/// The widest fd the guest may write to under [`absolute`].
+pub const MAX_STDIO: u32 = 2;
+
+mod absolute {
+    use wasi_guard::policy::policy;
+
+    policy! {
+        layer absolute;
+        default = allow;
+        kill fd_write where |fd: u32, _: u32, _: u32, _: u32| fd > crate::stmts::layers::MAX_STDIO;
+        ret_errno(SPIPE) fd_seek where |_: u32, _: i64, whence: wasi_guard::wasi::Whence, _: u32| {
+            whence == wasi_guard::wasi::Whence::END
+        };
+    }
+}
+
+mod bare {
+    // nothing of the layer it extends is imported
+    use wasi_guard::policy::policy;
+
+    policy! {
+        extends super::absolute::absolute;
+        log fd_read;
+    }
+}
+
+#[test]
+fn extend_by_absolute_paths() {
+    let guard = bare::WASI_GUARD_FD_WRITE.as_ref().unwrap();
+    assert!(guard.check((2, 0, 0, 0)).is_empty());
+    assert_eq!(guard.check((3, 0, 0, 0))[..], [Action::Kill]);
+    let guard = bare::WASI_GUARD_FD_SEEK.as_ref().unwrap();
+    assert_eq!(
+        guard.check((3, 0, wasi_guard::wasi::Whence::END, 0))[..],
+        [Action::ReturnErrno(wasi_guard::wasi::ERRNO_SPIPE.raw())]
+    );
+    assert_eq!(layers_of(bare::GUARD_LAYERS, "fd_read", false), ["policy"]);
+}
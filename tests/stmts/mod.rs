@@ -1,3 +1,21 @@
+mod after;
+mod component;
+mod context;
+mod custom;
+mod deterministic;
+mod dialects;
+mod egress;
+mod emulate;
+mod exprs;
+mod fds;
+mod first_match;
+mod groups;
 mod kill;
+mod layers;
+mod raw;
+mod reload;
+mod rewrite;
 mod simple;
+mod typed;
+mod wasmedge;
 mod without_bounds;
@@ -0,0 +1,56 @@
+use wasi_guard::{
+    policy::{action::Action, policy},
+    wasi::*,
+};
+
+policy! {
+    default = allow;
+    kill fd_seek where |fd: u32, offset: i64, _whence: Whence, _ptr: u32| fd > 2 && offset < 0;
+    log fd_seek where |fd: i32, offset: u64, _whence: u8, _ptr: i32| fd < 0 || offset > 1 << 32;
+    ret_errno(SPIPE) fd_seek;
+    after log fd_read where |_fd: u32, _iovs: u32, _len: u32, _ptr: u32, errno: Errno, _nread: u64| errno != Errno::SUCCESS;
+    after kill fd_read where |_fd: i32, _iovs: i32, _len: i32, _ptr: i32, _errno: u16, nread: u64| nread > 1 << 20;
+    // statements typing the params alike keep them typed
+    allow fd_close where |fd: u32| fd > 2;
+    log fd_close where |fd: u32| fd == 1;
+}
+
+#[test]
+fn raw_guards() {
+    let guard = WASI_GUARD_FD_SEEK.as_ref().unwrap();
+    let params: fd_seek_params_raw_t = (3, -1, 0, 0);
+    let _: fd_seek_guard_params_t = params;
+    // -1 is a huge `u64` offset
+    assert_eq!(
+        guard.check(params)[..],
+        [
+            Action::Kill,
+            Action::Log,
+            Action::ReturnErrno(ERRNO_SPIPE.raw())
+        ]
+    );
+    assert_eq!(
+        guard.check((3, 8, 0, 0))[..],
+        [Action::ReturnErrno(ERRNO_SPIPE.raw())]
+    );
+}
+
+#[test]
+fn raw_post_guards() {
+    let guard = WASI_GUARD_AFTER_FD_READ.as_ref().unwrap();
+    let _: fd_read_guard_post_params_t = (3i32, 0i32, 1i32, 0i32, 0u16, 16u64);
+    assert!(guard.check((3, 0, 1, 0, 0, 16)).is_empty());
+    assert_eq!(
+        guard.check((3, 0, 1, 0, ERRNO_IO.raw(), 2 << 20))[..],
+        [Action::Log, Action::Kill]
+    );
+}
+
+#[test]
+fn typed_guards() {
+    let params: fd_close_guard_params_t = (1u32,);
+    assert_eq!(
+        WASI_GUARD_FD_CLOSE.as_ref().unwrap().check(params)[..],
+        [Action::Log]
+    );
+}
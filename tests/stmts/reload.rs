@@ -0,0 +1,156 @@
+use std::{sync::Arc, thread};
+
+use wasi_guard::{
+    policy::{
+        action::Action,
+        reload::Decision,
+        set::{PolicyError, PolicySet},
+        stmt::Statement,
+        Mode, ReloadablePolicy, WasiGuard,
+    },
+    wasi::*,
+};
+
+mod v1 {
+    use wasi_guard::policy::policy;
+
+    policy! {
+        default = allow;
+        kill fd_write where fd > 2;
+        after log fd_read where |_: u32, _: u32, _: u32, _: u32, errno: u16, _: u64| errno != 0;
+    }
+}
+
+mod v2 {
+    use wasi_guard::policy::policy;
+
+    policy! {
+        mode = first_match;
+        default = log;
+        ret_errno(ACCES) fd_write where fd > 2;
+        ret_errno(PERM) @sock;
+    }
+}
+
+fn fd_write(policy: &PolicySet, fd: i32) -> Vec<Action> {
+    policy
+        .check::<fd_write_params_raw_t>("fd_write", false, (fd, 0, 0, 0))
+        .to_vec()
+}
+
+#[test]
+fn policy_sets() {
+    let set = v1::policy_set();
+    assert_eq!(set.validate(), Ok(()));
+    assert_eq!(set.mode, Mode::AllMatch);
+    assert_eq!(fd_write(&set, 3), [Action::Kill]);
+    assert!(fd_write(&set, 1).is_empty());
+    let actions = set.check::<fd_read_post_params_raw_t>("fd_read", true, (0, 0, 0, 0, 8, 0));
+    assert_eq!(actions[..], [Action::Log]);
+    // ABIs without guards take the default action.
+    let actions = set.check::<proc_exit_params_raw_t>("proc_exit", false, (0,));
+    assert_eq!(actions[..], [Action::Allow]);
+    // a guard held on other params fails closed
+    let actions = set.check::<(i64, i64)>("fd_write", false, (1, 0));
+    assert_eq!(actions[..], [Action::Kill]);
+    let actions = set.check_in::<fd_write_params_raw_t, u32>("fd_write", false, &0, (1, 0, 0, 0));
+    assert!(actions.is_empty());
+
+    let set = v2::policy_set();
+    assert_eq!(fd_write(&set, 1), [Action::Log]);
+    assert!(set
+        .guarded()
+        .any(|guarded| guarded == ("sock_accept", false)));
+}
+
+#[test]
+fn reload_policies() {
+    let policy = ReloadablePolicy::new(v1::policy_set()).unwrap();
+    assert_eq!(policy.version(), 0);
+    let in_flight = policy.load();
+
+    assert_eq!(policy.reload(v2::policy_set()), Ok(1));
+    assert_eq!(
+        policy.decide(|set| fd_write(set, 3)),
+        Decision {
+            version: 1,
+            value: vec![Action::ReturnErrno(ERRNO_ACCES.raw())]
+        }
+    );
+    // The calls in flight keep the version they loaded.
+    assert_eq!(in_flight.version(), 0);
+    assert_eq!(fd_write(&in_flight, 3), [Action::Kill]);
+}
+
+#[test]
+fn reject_invalid_policies() {
+    let policy = ReloadablePolicy::new(v1::policy_set()).unwrap();
+    let guard = WasiGuard::from_arr([Statement::<(i32, i32)>::new(Action::Kill)]);
+    let invalid =
+        PolicySet::new(Action::Allow, Mode::AllMatch).with_guard("fd_write", false, guard);
+    assert_eq!(
+        policy.reload(invalid).err(),
+        Some(PolicyError::Arity {
+            wasi: "fd_write",
+            after: false,
+            expected: 4,
+            found: 2
+        })
+    );
+    let guard = WasiGuard::from_arr([Statement::<(i32, i32, i64, i32)>::new(Action::Kill)]);
+    let invalid =
+        PolicySet::new(Action::Allow, Mode::AllMatch).with_guard("fd_write", false, guard);
+    assert_eq!(
+        policy.reload(invalid).err(),
+        Some(PolicyError::Width {
+            wasi: "fd_write",
+            after: false,
+            param: 2,
+            expected: 4,
+            found: 8
+        })
+    );
+    let guard = WasiGuard::from_arr([Statement::<(i32,)>::new(Action::Kill)]);
+    let unknown =
+        PolicySet::new(Action::Allow, Mode::AllMatch).with_guard("fd_frobnicate", false, guard);
+    assert!(matches!(
+        policy.reload(unknown),
+        Err(PolicyError::UnknownAbi("fd_frobnicate"))
+    ));
+    assert_eq!(policy.version(), 0);
+    assert_eq!(policy.decide(|set| fd_write(set, 3)).value, [Action::Kill]);
+}
+
+#[test]
+fn consistent_versions() {
+    let policy = Arc::new(ReloadablePolicy::new(v1::policy_set()).unwrap());
+    let reloader = {
+        let policy = policy.clone();
+        thread::spawn(move || {
+            for i in 0..100 {
+                let set = if i % 2 == 0 {
+                    v2::policy_set()
+                } else {
+                    v1::policy_set()
+                };
+                policy.reload(set).unwrap();
+            }
+        })
+    };
+    for _ in 0..1000 {
+        // Odd versions are of `v2`, even ones of `v1`.
+        let Decision { version, value } = policy.decide(|set| (set.mode, fd_write(set, 3)));
+        match version % 2 {
+            0 => assert_eq!(value, (Mode::AllMatch, vec![Action::Kill])),
+            _ => assert_eq!(
+                value,
+                (
+                    Mode::FirstMatch,
+                    vec![Action::ReturnErrno(ERRNO_ACCES.raw())]
+                )
+            ),
+        }
+    }
+    reloader.join().unwrap();
+    assert_eq!(policy.version(), 100);
+}
@@ -0,0 +1,61 @@
+use wasi_guard::{
+    policy::{
+        action::{Action, Rewrite},
+        policy,
+    },
+    wasi::*,
+};
+
+const CLAMP_RANDOM: Rewrite = Rewrite::new("clamp_random", |params, _| {
+    params[1] = params[1].min(256);
+    true
+});
+const READ_ONLY: Rewrite = Rewrite::new("read_only", |params, _| {
+    params[5] &= !(RIGHTS_FD_WRITE.raw() as i64);
+    true
+});
+
+policy! {
+    default = allow;
+    rewrite(CLAMP_RANDOM) random_get where buf_len > 256;
+    rewrite(READ_ONLY) path_open where fs_rights_base.contains(Rights::FD_WRITE);
+    log path_open where oflags.contains(Oflags::CREAT);
+}
+
+/// Applies the rewrites among `actions` to `params` as a host does.
+fn rewrite(actions: &[Action], params: &mut [i64]) -> bool {
+    actions.iter().all(|action| match action {
+        Action::Rewrite(rewrite) => rewrite.apply(params, None),
+        _ => true,
+    })
+}
+
+#[test]
+fn rewrite_calls() {
+    let guard = WASI_GUARD_RANDOM_GET.as_ref().unwrap();
+    let actions = guard.check((0, 4096));
+    assert_eq!(actions[..], [Action::Rewrite(CLAMP_RANDOM)]);
+    let mut params = [0, 4096];
+    assert!(rewrite(&actions, &mut params));
+    assert_eq!(params, [0, 256]);
+    assert!(guard.check((0, 16)).is_empty());
+
+    let guard = WASI_GUARD_PATH_OPEN.as_ref().unwrap();
+    let rights = Rights::FD_READ | Rights::FD_WRITE;
+    let actions = guard.check((
+        3,
+        Lookupflags::empty(),
+        0,
+        0,
+        Oflags::CREAT,
+        rights,
+        Rights::empty(),
+        Fdflags::empty(),
+        0,
+    ));
+    assert_eq!(actions[..], [Action::Rewrite(READ_ONLY), Action::Log]);
+    let mut params = [3, 0, 0, 0, 1, rights.raw() as i64, 0, 0, 0];
+    assert!(rewrite(&actions, &mut params));
+    assert_eq!(params[5], RIGHTS_FD_READ.raw() as i64);
+    assert!(Action::Log < actions[0] && actions[0] < Action::Kill);
+}
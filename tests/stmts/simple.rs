@@ -1,6 +1,5 @@
 #[allow(unused_imports)]
 use wasi_guard::policy::{action::Action::Kill, policy};
-use wasi_guard::wasi::*;
 
 policy! {
     default = Kill;
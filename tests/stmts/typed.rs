@@ -0,0 +1,54 @@
+use wasi_guard::{
+    policy::{action::Action, policy},
+    wasi::*,
+};
+
+policy! {
+    default = allow;
+    ret_errno(PERM) path_open where
+        |_fd: u32, _dirflags: Lookupflags, _path_ptr: u32, _path_len: u32, oflags: Oflags,
+         _base: Rights, _inheriting: Rights, _fdflags: Fdflags, _fd_ptr: u32| oflags.contains(Oflags::CREAT);
+    ret_errno(Errno::NOTSUP) fd_seek where |_fd: u32, _offset: i64, whence: Whence, _ptr: u32| whence == Whence::END;
+}
+
+#[test]
+fn errno_by_name() {
+    let rights = Rights::FD_READ | Rights::FD_WRITE;
+    let params = (
+        3,
+        Lookupflags::empty(),
+        0,
+        0,
+        Oflags::CREAT | Oflags::TRUNC,
+        rights,
+        rights,
+        Fdflags::empty(),
+        0,
+    );
+    let actions = WASI_GUARD_PATH_OPEN.as_ref().unwrap().check(params);
+    assert_eq!(actions[0], Action::ReturnErrno(ERRNO_PERM.raw()));
+
+    let params = (
+        3,
+        Lookupflags::empty(),
+        0,
+        0,
+        Oflags::TRUNC,
+        rights,
+        rights,
+        Fdflags::empty(),
+        0,
+    );
+    let actions = WASI_GUARD_PATH_OPEN.as_ref().unwrap().check(params);
+    assert!(actions.is_empty());
+}
+
+#[test]
+fn typed_enum_bound() {
+    let guard = WASI_GUARD_FD_SEEK.as_ref().unwrap();
+    assert_eq!(
+        guard.check((3, 0, Whence::END, 0))[0],
+        Action::ReturnErrno(ERRNO_NOTSUP.raw())
+    );
+    assert!(guard.check((3, 0, Whence::from_raw(0), 0)).is_empty());
+}
@@ -0,0 +1,18 @@
+#![cfg(feature = "wasmedge-sock-v1")]
+use wasi_guard::{
+    policy::{action::Action, policy},
+    wasi::wasmedge::v1::sock_accept_params_raw_t,
+};
+
+policy! {
+    default = allow;
+    kill sock_accept where |fd: i32, _: i32| fd == 0;
+}
+
+#[test]
+fn v1_sock_accept() {
+    let guard = WASI_GUARD_SOCK_ACCEPT.as_ref().unwrap();
+    let params: sock_accept_params_raw_t = (0, 64);
+    assert_eq!(guard.check(params)[..], [Action::Kill]);
+    assert!(guard.check((3, 64)).is_empty());
+}
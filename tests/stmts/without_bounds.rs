@@ -17,6 +17,13 @@ fn proc_exit_exists() {
 
 #[test]
 fn got_killed() {
+    #[cfg(not(any(
+        feature = "wasmedge-sock-v1",
+        feature = "wasmedge-sock-v2",
+        feature = "wasix",
+        feature = "wasi-threads",
+        feature = "p2"
+    )))]
     assert_eq!(MUST_BE_KILLED_WASIS.len(), WASI_NAMES.len() - 1);
     assert!(!MUST_BE_KILLED_WASIS
         .iter()
@@ -0,0 +1,668 @@
+//! Generates WASI ABI descriptors from the vendored `witx` files.
+//!
+//! Every `witx/<dir>/<file>.witx` that declares a `(module $name ...)` is
+//! lowered into `$OUT_DIR/<name>.rs`, which contains
+//! - type aliases for the integer and `handle` typenames,
+//! - typed `enum`s and `flags` (see [`declare_witx_enum!`] and [`declare_witx_flags!`]),
+//!   along with `PREFIX_MEMBER` constants of their members,
+//! - a [`declare_wasi_abis!`] invocation for the functions of the module,
+//! - `WASI_NAMES`, the names of these functions, and
+//! - `MODULE` and `DESCRIPTORS`, the type-erased descriptors of these functions.
+//!
+//! Functions are lowered to their core wasm signatures:
+//! - `string`s and `list`s are passed as a `(*_ptr, *_len)` pair;
+//! - results other than the errno are written through trailing `*_ptr`s of
+//!   `ArgKind::Out`, which are named after the results, or after their witx types
+//!   if unnamed.
+
+use std::{
+    env,
+    fmt::Write as _,
+    fs,
+    path::{Path, PathBuf},
+};
+
+fn main() {
+    let manifest_dir = PathBuf::from(env::var_os("CARGO_MANIFEST_DIR").unwrap());
+    let out_dir = PathBuf::from(env::var_os("OUT_DIR").unwrap());
+    let witx_dir = manifest_dir.join("witx");
+    println!("cargo:rerun-if-changed={}", witx_dir.display());
+
+    for path in witx_files(&witx_dir) {
+        println!("cargo:rerun-if-changed={}", path.display());
+        let witx = Witx::load(&path);
+        if let Some(module) = &witx.module {
+            let relative = path.strip_prefix(&manifest_dir).unwrap_or(&path);
+            let code = witx.generate(module, relative);
+            fs::write(out_dir.join(format!("{}.rs", module.name)), code).unwrap();
+        }
+    }
+}
+
+fn witx_files(dir: &Path) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    let Ok(entries) = fs::read_dir(dir) else {
+        return files;
+    };
+    for entry in entries {
+        let path = entry.unwrap().path();
+        if path.is_dir() {
+            files.extend(witx_files(&path));
+        } else if path.extension().is_some_and(|ext| ext == "witx") {
+            files.push(path);
+        }
+    }
+    files.sort();
+    files
+}
+
+/// An s-expression with the `;;;` doc comments in front of it.
+#[derive(Debug, Clone)]
+struct Node {
+    docs: Vec<String>,
+    expr: Expr,
+}
+
+#[derive(Debug, Clone)]
+enum Expr {
+    Atom(String),
+    Str(String),
+    List(Vec<Node>),
+}
+
+impl Node {
+    fn atom(&self) -> Option<&str> {
+        match &self.expr {
+            Expr::Atom(atom) => Some(atom),
+            _ => None,
+        }
+    }
+    fn id(&self) -> Option<&str> {
+        self.atom().and_then(|atom| atom.strip_prefix('$'))
+    }
+    fn list(&self) -> Option<&[Node]> {
+        match &self.expr {
+            Expr::List(items) => Some(items),
+            _ => None,
+        }
+    }
+    /// Returns the items of a list starting with the `head` atom(s), e.g.,
+    /// `(@witx pointer u8)` is a list headed by `["@witx", "pointer"]`.
+    fn headed_by(&self, head: &[&str]) -> Option<&[Node]> {
+        let items = self.list()?;
+        let matched = items.len() >= head.len()
+            && items
+                .iter()
+                .zip(head)
+                .all(|(item, h)| item.atom() == Some(h));
+        matched.then(|| &items[head.len()..])
+    }
+}
+
+fn parse(src: &str) -> Vec<Node> {
+    let mut stack: Vec<(Vec<String>, Vec<Node>)> = vec![(Vec::new(), Vec::new())];
+    let mut docs = Vec::new();
+    let bytes = src.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b';' if bytes.get(i + 1) == Some(&b';') => {
+                let end = src[i..].find('\n').map_or(src.len(), |n| i + n);
+                if let Some(doc) = src[i..end].strip_prefix(";;;") {
+                    docs.push(doc.strip_prefix(' ').unwrap_or(doc).trim_end().to_string());
+                }
+                i = end;
+            }
+            b'(' => {
+                stack.push((std::mem::take(&mut docs), Vec::new()));
+                i += 1;
+            }
+            b')' => {
+                let (docs_of_list, items) = stack.pop().expect("unbalanced `)` in witx");
+                let parent = stack.last_mut().expect("unbalanced `)` in witx");
+                parent.1.push(Node {
+                    docs: docs_of_list,
+                    expr: Expr::List(items),
+                });
+                docs.clear();
+                i += 1;
+            }
+            b'"' => {
+                let end = i + 1 + src[i + 1..].find('"').expect("unterminated string in witx");
+                stack.last_mut().unwrap().1.push(Node {
+                    docs: std::mem::take(&mut docs),
+                    expr: Expr::Str(src[i + 1..end].to_string()),
+                });
+                i = end + 1;
+            }
+            c if c.is_ascii_whitespace() => i += 1,
+            _ => {
+                let end = src[i..]
+                    .find(|c: char| c.is_ascii_whitespace() || c == '(' || c == ')')
+                    .map_or(src.len(), |n| i + n);
+                stack.last_mut().unwrap().1.push(Node {
+                    docs: std::mem::take(&mut docs),
+                    expr: Expr::Atom(src[i..end].to_string()),
+                });
+                i = end;
+            }
+        }
+    }
+    assert_eq!(stack.len(), 1, "unbalanced `(` in witx");
+    stack.pop().unwrap().1
+}
+
+#[derive(Debug, Clone)]
+enum Type {
+    /// `u8`, `s64`, `string`, ...
+    Builtin(String),
+    /// `$typename`
+    Ref(String),
+    Enum {
+        repr: String,
+        variants: Vec<(String, Vec<String>)>,
+    },
+    Flags {
+        repr: String,
+        flags: Vec<(String, Vec<String>)>,
+    },
+    Handle,
+    /// `record`s, `union`s and `variant`s, which are never passed by value.
+    Aggregate,
+    /// A `list` or `array` of the named type.
+    List(String),
+    /// A `pointer` or `const_pointer` to the named type.
+    Pointer(String),
+    Tuple(Vec<Type>),
+    Expected(Option<Box<Type>>),
+}
+
+impl Type {
+    fn parse(node: &Node) -> Type {
+        if let Some(id) = node.id() {
+            return Type::Ref(id.to_string());
+        }
+        if let Some(atom) = node.atom() {
+            return Type::Builtin(atom.to_string());
+        }
+        if let Some(rest) = node.headed_by(&["enum"]) {
+            let (repr, variants) = Self::parse_repr(rest, "tag");
+            return Type::Enum { repr, variants };
+        }
+        if let Some(rest) = node.headed_by(&["flags"]) {
+            let (repr, flags) = Self::parse_repr(rest, "repr");
+            return Type::Flags { repr, flags };
+        }
+        if node.headed_by(&["handle"]).is_some() {
+            return Type::Handle;
+        }
+        if ["record", "union", "variant", "struct"]
+            .iter()
+            .any(|head| node.headed_by(&[head]).is_some())
+        {
+            return Type::Aggregate;
+        }
+        if let Some(rest) = node
+            .headed_by(&["list"])
+            .or_else(|| node.headed_by(&["array"]))
+        {
+            return Type::List(type_name(&rest[0]));
+        }
+        if let Some(rest) = node
+            .headed_by(&["@witx", "pointer"])
+            .or_else(|| node.headed_by(&["@witx", "const_pointer"]))
+        {
+            return Type::Pointer(type_name(&rest[0]));
+        }
+        if node.headed_by(&["@witx", "usize"]).is_some() {
+            return Type::Builtin("u32".to_string());
+        }
+        if let Some(rest) = node.headed_by(&["tuple"]) {
+            return Type::Tuple(rest.iter().map(Type::parse).collect());
+        }
+        if let Some(rest) = node.headed_by(&["expected"]) {
+            let ok = rest
+                .iter()
+                .find(|item| item.headed_by(&["error"]).is_none())
+                .map(|ok| Box::new(Type::parse(ok)));
+            return Type::Expected(ok);
+        }
+        panic!("unsupported witx type: {node:?}");
+    }
+
+    /// Parses `(@witx tag u8) $a $b ...` as well as the older `u8 $a $b ...`.
+    fn parse_repr(items: &[Node], keyword: &str) -> (String, Vec<(String, Vec<String>)>) {
+        let (repr, members) = match items.first() {
+            Some(first) if first.headed_by(&["@witx", keyword]).is_some() => {
+                let repr = first.headed_by(&["@witx", keyword]).unwrap()[0].atom();
+                (repr.unwrap().to_string(), &items[1..])
+            }
+            Some(first) if first.id().is_none() && first.atom().is_some() => {
+                (first.atom().unwrap().to_string(), &items[1..])
+            }
+            _ => ("u32".to_string(), items),
+        };
+        let members = members
+            .iter()
+            .map(|member| {
+                let name = member.id().expect("expected a `$name` in enum or flags");
+                (name.to_string(), member.docs.clone())
+            })
+            .collect();
+        (repr, members)
+    }
+}
+
+struct TypeDef {
+    name: String,
+    docs: Vec<String>,
+    ty: Type,
+}
+
+struct Func {
+    name: String,
+    docs: Vec<String>,
+    params: Vec<(String, Type)>,
+    /// Results other than the errno, with their names if given.
+    results: Vec<(Option<String>, Type)>,
+}
+
+struct Module {
+    name: String,
+    funcs: Vec<Func>,
+}
+
+struct Witx {
+    types: Vec<TypeDef>,
+    module: Option<Module>,
+}
+
+impl Witx {
+    fn load(path: &Path) -> Witx {
+        let mut witx = Witx {
+            types: Vec::new(),
+            module: None,
+        };
+        witx.load_file(path);
+        witx
+    }
+
+    fn load_file(&mut self, path: &Path) {
+        let src = fs::read_to_string(path)
+            .unwrap_or_else(|err| panic!("failed to read {}: {err}", path.display()));
+        for node in parse(&src) {
+            if let Some(rest) = node.headed_by(&["use"]) {
+                let Some(Expr::Str(used)) = rest.first().map(|node| &node.expr) else {
+                    panic!("expected a file name in `use`");
+                };
+                self.load_file(&path.parent().unwrap().join(used));
+            } else if let Some(rest) = node.headed_by(&["typename"]) {
+                self.types.push(TypeDef {
+                    name: rest[0]
+                        .id()
+                        .expect("expected a `$name` in typename")
+                        .to_string(),
+                    docs: node.docs.clone(),
+                    ty: Type::parse(&rest[1]),
+                });
+            } else if let Some(rest) = node.headed_by(&["module"]) {
+                self.module = Some(Module {
+                    name: rest[0]
+                        .id()
+                        .expect("expected a `$name` in module")
+                        .to_string(),
+                    funcs: rest[1..].iter().filter_map(Func::parse).collect(),
+                });
+            }
+        }
+    }
+
+    fn typedef(&self, name: &str) -> &TypeDef {
+        self.types
+            .iter()
+            .find(|def| def.name == name)
+            .unwrap_or_else(|| panic!("unknown witx type `${name}`"))
+    }
+
+    /// Lowers a parameter into core wasm arguments.
+    ///
+    /// `params` are the names of all the parameters of the function, which link
+    /// a pointer `$x` to its length `$x_len`.
+    fn lower_param(&self, name: &str, ty: &Type, params: &[&str]) -> Vec<Arg> {
+        match ty {
+            Type::Builtin(builtin) if builtin == "string" => ptr_len(name, "u8"),
+            Type::Builtin(builtin) => vec![Arg::new(name, prim(builtin), int_kind(builtin))],
+            Type::Pointer(pointee) => {
+                let len = format!("{name}_len");
+                let len_arg = if params.contains(&len.as_str()) {
+                    format!("Some({len:?})")
+                } else {
+                    "None".to_string()
+                };
+                vec![Arg::new(
+                    &format!("{name}_ptr"),
+                    "Waddr",
+                    format!("ArgKind::Pointer {{ pointee: {pointee:?}, len_arg: {len_arg} }}"),
+                )]
+            }
+            Type::List(elem) => ptr_len(name, elem),
+            Type::Ref(referred) => match &self.typedef(referred).ty {
+                Type::List(elem) => ptr_len(name, elem),
+                Type::Builtin(builtin) if builtin == "string" => ptr_len(name, "u8"),
+                Type::Aggregate => panic!("`${referred}` can not be passed by value"),
+                _ => vec![Arg::new(
+                    name,
+                    &camel_case(referred),
+                    self.kind_of(referred),
+                )],
+            },
+            _ => panic!("unsupported parameter type of `{name}`: {ty:?}"),
+        }
+    }
+
+    /// The `ArgKind` of a scalar typename.
+    fn kind_of(&self, typename: &str) -> String {
+        if typename == "timestamp" {
+            return "ArgKind::Timestamp".to_string();
+        }
+        match &self.typedef(typename).ty {
+            Type::Builtin(builtin) => int_kind(builtin),
+            Type::Ref(referred) => self.kind_of(referred),
+            Type::Handle => "ArgKind::Fd".to_string(),
+            Type::Enum { .. } | Type::Flags { .. } => format!("{}::KIND", camel_case(typename)),
+            ty => panic!("`${typename}` is not a scalar: {ty:?}"),
+        }
+    }
+
+    fn generate(&self, module: &Module, source: &Path) -> String {
+        let mut code = String::new();
+        writeln!(
+            code,
+            "// @generated by `build.rs` from `{}`, do not edit.\n",
+            source.display()
+        )
+        .unwrap();
+        code.push_str("use wasi_descriptor::ArgKind;\n\n");
+        code.push_str(
+            "/// Address type for WASM linear memory, i.e.,\n\
+             /// the offset from the base address of the WASM linear memory.\n\
+             pub type Waddr = u32;\n\n",
+        );
+        for def in &self.types {
+            self.generate_typedef(&mut code, def);
+        }
+
+        code.push_str("crate::declare_wasi_abis!(\n");
+        for func in &module.funcs {
+            write_docs(&mut code, "    ", &func.docs);
+            let params: Vec<&str> = func.params.iter().map(|(name, _)| name.as_str()).collect();
+            let mut args = Vec::new();
+            for (name, ty) in &func.params {
+                args.extend(self.lower_param(name, ty, &params));
+            }
+            args.extend(result_ptrs(&func.results));
+            let args: Vec<String> = args
+                .into_iter()
+                .map(|Arg { name, ty, kind }| format!("{name}: {ty} @ {kind}"))
+                .collect();
+            if args.is_empty() {
+                writeln!(code, "    {}();", func.name).unwrap();
+            } else {
+                writeln!(code, "    {}(", func.name).unwrap();
+                for arg in args {
+                    writeln!(code, "        {arg},").unwrap();
+                }
+                code.push_str("    );\n");
+            }
+        }
+        code.push_str(");\n\n");
+
+        writeln!(
+            code,
+            "/// Names of the ABIs in `{}`.\npub const WASI_NAMES: [&str; {}] = [",
+            module.name,
+            module.funcs.len()
+        )
+        .unwrap();
+        for func in &module.funcs {
+            writeln!(code, "    {:?},", func.name).unwrap();
+        }
+        code.push_str("];\n\n");
+
+        writeln!(
+            code,
+            "/// Name of the module which the ABIs are imported from.\n\
+             pub const MODULE: &str = {:?};\n\n\
+             /// Type-erased descriptors of the ABIs in [`MODULE`].\n\
+             pub const DESCRIPTORS: [wasi_descriptor::ErasedDescriptor<'static>; {}] = [",
+            module.name,
+            module.funcs.len()
+        )
+        .unwrap();
+        for func in &module.funcs {
+            writeln!(
+                code,
+                "    wasi_descriptor::erase_desc!(MODULE, {}),",
+                func.name
+            )
+            .unwrap();
+        }
+        code.push_str("];\n");
+        code
+    }
+
+    fn generate_typedef(&self, code: &mut String, def: &TypeDef) {
+        let name = camel_case(&def.name);
+        let (repr, members, is_flags) = match &def.ty {
+            Type::Builtin(builtin) if builtin != "string" => {
+                write_docs(code, "", &def.docs);
+                writeln!(code, "pub type {name} = {};\n", prim(builtin)).unwrap();
+                return;
+            }
+            Type::Ref(referred) if !matches!(self.typedef(referred).ty, Type::Aggregate) => {
+                write_docs(code, "", &def.docs);
+                writeln!(code, "pub type {name} = {};\n", camel_case(referred)).unwrap();
+                return;
+            }
+            Type::Handle => {
+                write_docs(code, "", &def.docs);
+                writeln!(code, "pub type {name} = u32;\n").unwrap();
+                return;
+            }
+            Type::Enum { repr, variants } => (repr, variants, false),
+            Type::Flags { repr, flags } => (repr, flags, true),
+            _ => return,
+        };
+
+        writeln!(
+            code,
+            "crate::declare_witx_{}! {{",
+            if is_flags { "flags" } else { "enum" }
+        )
+        .unwrap();
+        write_docs(code, "    ", &def.docs);
+        writeln!(code, "    {name}({}) {{", prim(repr)).unwrap();
+        let members: Vec<(String, String)> = members
+            .iter()
+            .enumerate()
+            .map(|(i, (member, docs))| {
+                write_docs(code, "        ", docs);
+                let member = member.to_uppercase();
+                let value = if is_flags {
+                    format!("1 << {i}")
+                } else {
+                    i.to_string()
+                };
+                // e.g., `2big`
+                let ident = if member.starts_with(|c: char| c.is_ascii_digit()) {
+                    format!("_{member}")
+                } else {
+                    member.clone()
+                };
+                writeln!(code, "        {ident} = {value} => {member:?},").unwrap();
+                (ident, member)
+            })
+            .collect();
+        code.push_str("    }\n}\n");
+        let prefix = def.name.to_uppercase();
+        for (ident, member) in members {
+            writeln!(
+                code,
+                "pub const {prefix}_{member}: {name} = {name}::{ident};"
+            )
+            .unwrap();
+        }
+        code.push('\n');
+    }
+}
+
+impl Func {
+    fn parse(node: &Node) -> Option<Func> {
+        let rest = node.headed_by(&["@interface", "func"])?;
+        let name = rest[0]
+            .headed_by(&["export"])
+            .and_then(|export| match &export[0].expr {
+                Expr::Str(name) => Some(name.clone()),
+                _ => None,
+            })
+            .expect("expected `(export \"name\")` in func");
+        let mut params = Vec::new();
+        let mut results = Vec::new();
+        for item in &rest[1..] {
+            if let Some(param) = item.headed_by(&["param"]) {
+                let name = param[0].id().expect("expected a `$name` in param");
+                params.push((name.to_string(), Type::parse(&param[1])));
+            } else if let Some(result) = item.headed_by(&["result"]) {
+                let result_name = result[0].id().expect("expected a `$name` in result");
+                match Type::parse(&result[1]) {
+                    // (result $error (expected (tuple $a $b) (error $errno)))
+                    Type::Expected(Some(ok)) => match *ok {
+                        Type::Tuple(elems) => results.extend(elems.into_iter().map(|e| (None, e))),
+                        ok => results.push((None, ok)),
+                    },
+                    Type::Expected(None) => {}
+                    // (result $error $errno)
+                    Type::Ref(ty) if ty == "errno" => {}
+                    // (result $nwritten $size)
+                    ty => results.push((Some(result_name.to_string()), ty)),
+                }
+            }
+        }
+        Some(Func {
+            name,
+            docs: node.docs.clone(),
+            params,
+            results,
+        })
+    }
+}
+
+/// A lowered argument, with its Rust type and an expression of its `ArgKind`.
+struct Arg {
+    name: String,
+    ty: String,
+    kind: String,
+}
+impl Arg {
+    fn new(name: &str, ty: &str, kind: String) -> Self {
+        Arg {
+            name: name.to_string(),
+            ty: ty.to_string(),
+            kind,
+        }
+    }
+}
+
+/// Names the out-pointers of results, indexing the ones named after the same type.
+fn result_ptrs(results: &[(Option<String>, Type)]) -> Vec<Arg> {
+    let pointees: Vec<&String> = results
+        .iter()
+        .map(|(_, ty)| match ty {
+            Type::Ref(ty) | Type::Builtin(ty) => ty,
+            ty => panic!("unsupported result type: {ty:?}"),
+        })
+        .collect();
+    let names: Vec<&String> = results
+        .iter()
+        .zip(&pointees)
+        .map(|((name, _), pointee)| name.as_ref().unwrap_or(pointee))
+        .collect();
+    names
+        .iter()
+        .enumerate()
+        .map(|(i, name)| {
+            let same_names: Vec<usize> = (0..names.len()).filter(|&j| &names[j] == name).collect();
+            let name = if same_names.len() > 1 {
+                let nth = same_names.iter().position(|&j| j == i).unwrap();
+                format!("{name}{nth}_ptr")
+            } else {
+                format!("{name}_ptr")
+            };
+            let kind = format!("ArgKind::Out {{ pointee: {:?} }}", pointees[i]);
+            Arg::new(&name, "Waddr", kind)
+        })
+        .collect()
+}
+
+fn ptr_len(name: &str, pointee: &str) -> Vec<Arg> {
+    let len = format!("{name}_len");
+    let kind = format!("ArgKind::Pointer {{ pointee: {pointee:?}, len_arg: Some({len:?}) }}");
+    vec![
+        Arg::new(&format!("{name}_ptr"), "Waddr", kind),
+        Arg::new(&len, "Size", int_kind("u32")),
+    ]
+}
+
+fn int_kind(witx: &str) -> String {
+    format!("ArgKind::Int {{ signed: {} }}", witx.starts_with('s'))
+}
+
+/// The name of a type in pointers and lists, e.g., `iovec`, `u8`, or `*u8` for a pointer to `u8`.
+fn type_name(node: &Node) -> String {
+    if let Some(rest) = node
+        .headed_by(&["@witx", "pointer"])
+        .or_else(|| node.headed_by(&["@witx", "const_pointer"]))
+    {
+        return format!("*{}", type_name(&rest[0]));
+    }
+    node.id()
+        .or(node.atom())
+        .map(str::to_string)
+        .unwrap_or_else(|| panic!("expected a typename: {node:?}"))
+}
+
+fn prim(witx: &str) -> &str {
+    match witx {
+        "u8" => "u8",
+        "u16" => "u16",
+        "u32" => "u32",
+        "u64" => "u64",
+        "s8" => "i8",
+        "s16" => "i16",
+        "s32" => "i32",
+        "s64" => "i64",
+        _ => panic!("unsupported witx builtin type `{witx}`"),
+    }
+}
+
+fn camel_case(snake_case: &str) -> String {
+    snake_case
+        .split('_')
+        .map(|word| {
+            let mut chars = word.chars();
+            chars.next().map_or(String::new(), |first| {
+                first.to_ascii_uppercase().to_string() + chars.as_str()
+            })
+        })
+        .collect()
+}
+
+fn write_docs(code: &mut String, indent: &str, docs: &[String]) {
+    for doc in docs {
+        if doc.is_empty() {
+            writeln!(code, "{indent}///").unwrap();
+        } else {
+            writeln!(code, "{indent}/// {doc}").unwrap();
+        }
+    }
+}
@@ -0,0 +1,168 @@
+//! Categories of WASI ABIs, so that policies can select a whole group of ABIs,
+//! e.g., all the filesystem ones, at once.
+
+use wasi_descriptor::ErasedDescriptor;
+
+/// What an ABI works on, told by its name.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Category {
+    /// `args_*`.
+    Args,
+    /// `environ_*`.
+    Env,
+    /// `clock_*`.
+    Clock,
+    /// `random_*`.
+    Random,
+    /// `proc_*`, `sched_yield`, and threads, e.g., `thread_spawn` and `futex_wait`.
+    Proc,
+    /// `fd_*` and `tty_*`.
+    Fd,
+    /// `path_*`, `getcwd` and `chdir`.
+    Path,
+    /// `poll_*`.
+    Poll,
+    /// `sock_*` and `resolve`.
+    Sock,
+}
+
+impl Category {
+    /// All the categories, in the order of their declarations.
+    pub const ALL: [Category; 9] = [
+        Category::Args,
+        Category::Env,
+        Category::Clock,
+        Category::Random,
+        Category::Proc,
+        Category::Fd,
+        Category::Path,
+        Category::Poll,
+        Category::Sock,
+    ];
+
+    /// Groups of categories that can be selected by name, e.g., `fs` for [`Category::Fd`] and [`Category::Path`].
+    pub const GROUPS: [(&'static str, &'static [Category]); 10] = [
+        ("args", &[Category::Args]),
+        ("env", &[Category::Env]),
+        ("clock", &[Category::Clock]),
+        ("random", &[Category::Random]),
+        ("proc", &[Category::Proc]),
+        ("fd", &[Category::Fd]),
+        ("path", &[Category::Path]),
+        ("poll", &[Category::Poll]),
+        ("sock", &[Category::Sock]),
+        ("fs", &[Category::Fd, Category::Path]),
+    ];
+
+    /// The lowercase name of the category, e.g., `"fd"`.
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Category::Args => "args",
+            Category::Env => "env",
+            Category::Clock => "clock",
+            Category::Random => "random",
+            Category::Proc => "proc",
+            Category::Fd => "fd",
+            Category::Path => "path",
+            Category::Poll => "poll",
+            Category::Sock => "sock",
+        }
+    }
+
+    /// Looks up the categories of a group by its name, e.g., `"fs"`.
+    pub fn group(name: &str) -> Option<&'static [Category]> {
+        Self::GROUPS
+            .iter()
+            .find(|(group, _)| group.eq_ignore_ascii_case(name))
+            .map(|(_, categories)| *categories)
+    }
+
+    /// The category of the ABI named `wasi_name`, by either its Rust name like
+    /// `thread_spawn` or its import name like `thread-spawn`.
+    ///
+    /// Returns `None` for names out of the snapshots and their dialects, e.g., the
+    /// functions of preview 2.
+    pub fn of(wasi_name: &str) -> Option<Category> {
+        const PREFIXES: [(&str, Category); 14] = [
+            ("args_", Category::Args),
+            ("environ_", Category::Env),
+            ("clock_", Category::Clock),
+            ("random_", Category::Random),
+            ("proc_", Category::Proc),
+            ("thread_", Category::Proc),
+            ("thread-", Category::Proc),
+            ("futex_", Category::Proc),
+            ("stack_", Category::Proc),
+            ("fd_", Category::Fd),
+            ("tty_", Category::Fd),
+            ("path_", Category::Path),
+            ("poll_", Category::Poll),
+            ("sock_", Category::Sock),
+        ];
+        if let Some((_, category)) = PREFIXES
+            .iter()
+            .find(|(prefix, _)| wasi_name.starts_with(prefix))
+        {
+            return Some(*category);
+        }
+        match wasi_name {
+            "sched_yield" | "callback_signal" => Some(Category::Proc),
+            "getcwd" | "chdir" => Some(Category::Path),
+            "resolve" => Some(Category::Sock),
+            _ => None,
+        }
+    }
+
+    /// The category of the ABI described by `desc`.
+    pub fn of_descriptor(desc: &ErasedDescriptor<'_>) -> Option<Category> {
+        Self::of(desc.name)
+    }
+
+    /// Whether the ABI named `wasi_name` is in this category.
+    pub fn contains(&self, wasi_name: &str) -> bool {
+        Self::of(wasi_name) == Some(*self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn categories_of_names() {
+        assert_eq!(Category::of("fd_write"), Some(Category::Fd));
+        assert_eq!(Category::of("path_open"), Some(Category::Path));
+        assert_eq!(Category::of("environ_sizes_get"), Some(Category::Env));
+        assert_eq!(Category::of("sched_yield"), Some(Category::Proc));
+        assert_eq!(Category::of("thread-spawn"), Some(Category::Proc));
+        assert_eq!(Category::of("resolve"), Some(Category::Sock));
+        assert_eq!(Category::of("get-arguments"), None);
+        assert!(Category::Sock.contains("sock_accept"));
+        assert!(!Category::Sock.contains("fd_read"));
+    }
+
+    #[test]
+    fn groups() {
+        assert_eq!(
+            Category::group("FS"),
+            Some(&[Category::Fd, Category::Path][..])
+        );
+        assert_eq!(Category::group("nope"), None);
+        for category in Category::ALL {
+            assert_eq!(Category::group(category.name()), Some(&[category][..]));
+        }
+    }
+
+    #[test]
+    fn every_abi_categorized() {
+        for name in crate::p0::WASI_NAMES.iter().chain(&crate::p1::WASI_NAMES) {
+            assert!(Category::of(name).is_some(), "{name}");
+        }
+        for desc in crate::DESCRIPTORS
+            .iter()
+            .filter(|desc| !desc.module.starts_with("wasi:"))
+        {
+            assert!(Category::of_descriptor(desc).is_some(), "{}", desc.name);
+        }
+    }
+}
@@ -1,20 +1,40 @@
 #![no_std]
 
+use wasi_descriptor::{ErasedDescriptor, ValType};
+// Re-exported for the macros used out of this crate, e.g., `declare_host_abis!`.
+#[doc(hidden)]
+pub use {paste, wasi_descriptor};
+
+pub mod category;
+pub use category::Category;
+
+pub mod p0;
+
 pub mod p1;
 #[cfg(feature = "p1")]
 pub use p1::*;
 
-pub mod p2 {
-    // TODO
-}
+pub mod p2;
 #[cfg(feature = "p2")]
 pub use p2::*;
 
+#[cfg(any(feature = "wasmedge-sock-v1", feature = "wasmedge-sock-v2"))]
+pub mod wasmedge;
+
+#[cfg(feature = "wasix")]
+pub mod wasix;
+
+#[cfg(feature = "wasi-threads")]
+pub mod threads;
+
+mod witx;
+
 #[doc(hidden)]
 #[macro_export]
 macro_rules! __count_args {
     () => { 0usize };
     (, $($remain:tt)*) => { $crate::__count_args!($($remain)*) }; // drop heading ','s
+    (@ $kind:expr $(, $($remain:tt)*)?) => { $crate::__count_args!($($($remain)*)?) }; // drop kinds
     ($head:ident) => { 1usize };
     ($head:ident, $($tail:tt)*) => { 1usize + $crate::__count_args!($($tail)*) };
     ($head:ident : $head_ty:tt $($tail:tt)*) => { 1usize + $crate::__count_args!($($tail)*) };
@@ -23,7 +43,7 @@ macro_rules! __count_args {
 #[macro_export]
 macro_rules! type_from_arg {
     ($arg:ident) => {
-        wasi_descriptor::DefaultAbiArgType
+        $crate::wasi_descriptor::DefaultAbiArgType
     };
     ($arg:ident : $ty:ty) => {
         $ty
@@ -43,6 +63,11 @@ macro_rules! _param_type_from_args {
         $crate::_param_type_from_args!(@accum ( $($rest)* ) -> ( $($res)* ))
     };
 
+    // drop kinds of args
+    (@accum ($arg:ident : $ty:tt @ $kind:expr $(, $($rest:tt)*)?) -> ( $($res:tt)* )) => {
+        $crate::_param_type_from_args!(@accum ($($($rest)*)?) -> ( $($res)* $crate::type_from_arg!($arg:$ty), ))
+    };
+
     (@accum ($arg:ident : $ty:ty) -> ( $($res:tt)* )) => {
         $crate::_param_type_from_args!(@accum () -> ( $($res)* $crate::type_from_arg!($arg:$ty), ))
     };
@@ -77,6 +102,11 @@ macro_rules! _default_param_type_from_args {
         $crate::_default_param_type_from_args!(@accum ( $($rest)* ) -> ( $($res)* ))
     };
 
+    // drop kinds of args
+    (@accum ($arg:ident : $ty:tt @ $kind:expr $(, $($rest:tt)*)?) -> ( $($res:tt)* )) => {
+        $crate::_default_param_type_from_args!(@accum ($($($rest)*)?) -> ( $($res)* $crate::type_from_arg!($arg), ))
+    };
+
     (@accum ($arg:ident : $ty:ty) -> ( $($res:tt)* )) => {
         $crate::_default_param_type_from_args!(@accum () -> ( $($res)* $crate::type_from_arg!($arg), ))
     };
@@ -100,24 +130,394 @@ macro_rules! default_param_type_from_args {
     };
 }
 
+#[macro_export]
+macro_rules! raw_type_from_arg {
+    ($arg:ident) => {
+        $crate::wasi_descriptor::RawArgType<{ core::mem::size_of::<$crate::wasi_descriptor::DefaultAbiArgType>() }>
+    };
+    ($arg:ident : $ty:ty) => {
+        $crate::wasi_descriptor::RawArgType<{ core::mem::size_of::<$ty>() }>
+    };
+    ($arg:ident [ $size:expr ]) => {
+        $crate::wasi_descriptor::RawArgType<{ $size }>
+    };
+}
+#[macro_export]
+macro_rules! _raw_param_type_from_args {
+    (@expr $($expr:tt)*) => { ( $($expr)* ) };
+    (@accum () -> ( $($res:tt)* )) => {
+        $crate::_raw_param_type_from_args!(@expr $($res)* )
+    };
+    // drop heading commas in accum input
+    (@accum (, $($rest:tt)*) -> ( $($res:tt)* )) => {
+        $crate::_raw_param_type_from_args!(@accum ( $($rest)* ) -> ( $($res)* ))
+    };
+
+    // drop kinds of args
+    (@accum ($arg:ident : $ty:tt @ $kind:expr $(, $($rest:tt)*)?) -> ( $($res:tt)* )) => {
+        $crate::_raw_param_type_from_args!(@accum ($($($rest)*)?) -> ( $($res)* $crate::raw_type_from_arg!($arg:$ty), ))
+    };
+
+    (@accum ($arg:ident : $ty:ty) -> ( $($res:tt)* )) => {
+        $crate::_raw_param_type_from_args!(@accum () -> ( $($res)* $crate::raw_type_from_arg!($arg:$ty), ))
+    };
+    (@accum ($arg:ident : $ty:ty, $($rest:tt)*) -> ( $($res:tt)* )) => {
+        $crate::_raw_param_type_from_args!(@accum ($($rest)*) -> ( $($res)* $crate::raw_type_from_arg!($arg:$ty), ))
+    };
+    (@accum ($arg:ident[ $size:expr ]  $($rest:tt)*) -> ( $($res:tt)* )) => {
+        $crate::_raw_param_type_from_args!(@accum ($($rest)*) -> ( $($res)* $crate::raw_type_from_arg!($arg[$size]), ))
+    };
+    (@accum ($arg:ident) -> ( $($res:tt)* )) => {
+        $crate::_raw_param_type_from_args!(@accum () -> ( $($res)* $crate::raw_type_from_arg!($arg), ))
+    };
+    (@accum ($arg:ident, $($rest:tt)*) -> ( $($res:tt)* )) => {
+        $crate::_raw_param_type_from_args!(@accum ($($rest)*) -> ( $($res)* $crate::raw_type_from_arg!($arg), ))
+    };
+}
+/// The types of the raw values of the arguments, i.e., the params of the core signature.
+#[macro_export]
+macro_rules! raw_param_type_from_args {
+    ($($token:tt)*) => {
+        $crate::_raw_param_type_from_args!(@accum ($($token)*) -> ())
+    };
+}
+
 #[macro_export]
 macro_rules! declare_wasi_abis {
     () => {};
     (; $( $rest:tt )*) => {
         $crate::declare_wasi_abis!($($rest)*);
     };
-    ($wasi_name:ident $(( $($arg:tt)* ))? ) => {
-        paste::paste! {
+    // `rust_name = "abi-name"(args)` for ABIs whose names are not Rust identifiers.
+    ($(#[$attr:meta])* $rust_name:ident = $wasi_name:literal ( $($arg:tt)* ) ) => {
+        $crate::paste::paste! {
+            #[allow(non_camel_case_types)]
+            pub type [<$rust_name _params_t>] = $crate::param_type_from_args!($($arg)*);
+            #[allow(non_camel_case_types)]
+            pub type [<$rust_name _params_default_t>] = $crate::default_param_type_from_args!($($arg)*);
+            #[allow(non_camel_case_types)]
+            pub type [<$rust_name _post_params_t>] =
+                <[<$rust_name _params_t>] as $crate::wasi_descriptor::PostCallParams<{ $rust_name.out_count() }>>::Output;
+            #[allow(non_camel_case_types)]
+            pub type [<$rust_name _post_params_default_t>] =
+                <[<$rust_name _params_default_t>] as $crate::wasi_descriptor::PostCallParams<{ $rust_name.out_count() }>>::Output;
+            #[allow(non_camel_case_types)]
+            pub type [<$rust_name _params_raw_t>] = $crate::raw_param_type_from_args!($($arg)*);
+            #[allow(non_camel_case_types)]
+            pub type [<$rust_name _post_params_raw_t>] =
+                <[<$rust_name _params_raw_t>] as $crate::wasi_descriptor::PostCallParams<{ $rust_name.out_count() }>>::Output;
+        }
+        $(#[$attr])*
+        #[allow(non_upper_case_globals)]
+        pub const $rust_name: $crate::wasi_descriptor::WasiAbiDescriptor<{$crate::__count_args!($($arg)*)}> = $crate::wasi_descriptor::desc_wasi_abi!($wasi_name ($($arg)*));
+    };
+    ($(#[$attr:meta])* $rust_name:ident = $wasi_name:literal ( $($arg:tt)* ) ; $( $rest:tt )*) => {
+        $crate::declare_wasi_abis!($(#[$attr])* $rust_name = $wasi_name ( $($arg)* ));
+        $crate::declare_wasi_abis!($($rest)*);
+    };
+    ($(#[$attr:meta])* $wasi_name:ident $(( $($arg:tt)* ))? ) => {
+        $crate::paste::paste! {
             #[allow(non_camel_case_types)]
             pub type [<$wasi_name _params_t>] = $crate::param_type_from_args!($($($arg)*)*);
             #[allow(non_camel_case_types)]
             pub type [<$wasi_name _params_default_t>] = $crate::default_param_type_from_args!($($($arg)*)*);
+            #[allow(non_camel_case_types)]
+            pub type [<$wasi_name _post_params_t>] =
+                <[<$wasi_name _params_t>] as $crate::wasi_descriptor::PostCallParams<{ $wasi_name.out_count() }>>::Output;
+            #[allow(non_camel_case_types)]
+            pub type [<$wasi_name _post_params_default_t>] =
+                <[<$wasi_name _params_default_t>] as $crate::wasi_descriptor::PostCallParams<{ $wasi_name.out_count() }>>::Output;
+            #[allow(non_camel_case_types)]
+            pub type [<$wasi_name _params_raw_t>] = $crate::raw_param_type_from_args!($($($arg)*)*);
+            #[allow(non_camel_case_types)]
+            pub type [<$wasi_name _post_params_raw_t>] =
+                <[<$wasi_name _params_raw_t>] as $crate::wasi_descriptor::PostCallParams<{ $wasi_name.out_count() }>>::Output;
         }
+        $(#[$attr])*
         #[allow(non_upper_case_globals)]
-        pub const $wasi_name: wasi_descriptor::WasiAbiDescriptor<{$crate::__count_args!($($($arg)*)*)}> = wasi_descriptor::desc_wasi_abi!($wasi_name $(($($arg)*))*);
+        pub const $wasi_name: $crate::wasi_descriptor::WasiAbiDescriptor<{$crate::__count_args!($($($arg)*)*)}> = $crate::wasi_descriptor::desc_wasi_abi!($wasi_name $(($($arg)*))*);
     };
-    ($wasi_name:ident $(( $($arg:tt)* ))? ; $( $rest:tt )*) => {
-        $crate::declare_wasi_abis!($wasi_name $(( $($arg)* ))?);
+    ($(#[$attr:meta])* $wasi_name:ident $(( $($arg:tt)* ))? ; $( $rest:tt )*) => {
+        $crate::declare_wasi_abis!($(#[$attr])* $wasi_name $(( $($arg)* ))?);
         $crate::declare_wasi_abis!($($rest)*);
     };
 }
+
+/// Declares the descriptors of the ABIs that a host exports from `module`, e.g., `env`,
+/// along with `MODULE`, `WASI_NAMES`, `DESCRIPTORS` and `descriptor_by_name` like
+/// those of the WASI versions.
+///
+/// Such a module can be guarded by `policy!` with `use descriptors path::to::module;`.
+///
+/// ```ignore
+/// pub mod abi {
+///     wasi_guard::wasi::declare_host_abis!("env";
+///         host_http_fetch(url_ptr: u32, url_len: u32, resp_ptr: u32);
+///         kv_get(key_ptr, key_len, val_ptr);
+///     );
+/// }
+/// ```
+#[macro_export]
+macro_rules! declare_host_abis {
+    ($module:literal; $( $(#[$attr:meta])* $name:ident ( $($arg:tt)* ) );* $(;)?) => {
+        $crate::declare_wasi_abis!($( $(#[$attr])* $name ( $($arg)* ); )*);
+
+        /// Name of the module which the ABIs are imported from.
+        pub const MODULE: &str = $module;
+
+        /// Names of the ABIs in [`MODULE`].
+        pub const WASI_NAMES: [&str; $crate::__count_args!($($name),*)] = [$(stringify!($name)),*];
+
+        /// Type-erased descriptors of the ABIs in [`MODULE`].
+        pub const DESCRIPTORS: [$crate::wasi_descriptor::ErasedDescriptor<'static>; WASI_NAMES.len()] =
+            [$($crate::wasi_descriptor::erase_desc!(MODULE, $name)),*];
+
+        /// Looks up the descriptor of the ABI `name` imported from `module`.
+        pub fn descriptor_by_name(
+            module: &str,
+            name: &str,
+        ) -> Option<&'static $crate::wasi_descriptor::ErasedDescriptor<'static>> {
+            let descriptors: &'static [$crate::wasi_descriptor::ErasedDescriptor<'static>] = &DESCRIPTORS;
+            descriptors.iter().find(|desc| desc.is(module, name))
+        }
+    };
+}
+
+/// Concatenates two arrays in a const context.
+#[doc(hidden)]
+pub const fn concat<T: Copy, const M: usize, const N: usize, const L: usize>(
+    head: [T; M],
+    tail: [T; N],
+) -> [T; L] {
+    assert!(M + N == L && M > 0);
+    let mut items = [head[0]; L];
+    let mut i = 0;
+    while i < M {
+        items[i] = head[i];
+        i += 1;
+    }
+    while i < L {
+        items[i] = tail[i - M];
+        i += 1;
+    }
+    items
+}
+
+/// Concatenates array constants in a const context.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! concat_consts {
+    ($head:expr $(,)?) => { $head };
+    ($head:expr, $($tail:expr),+ $(,)?) => {
+        $crate::concat::<_, { $head.len() }, { 0 $(+ $tail.len())+ }, { $head.len() $(+ $tail.len())+ }>(
+            $head,
+            $crate::concat_consts!($($tail),+),
+        )
+    };
+}
+
+/// Replaces the descriptor of the same ABI in `descriptors` with `replacement`.
+#[doc(hidden)]
+pub const fn replace_descriptor<const N: usize>(
+    mut descriptors: [ErasedDescriptor<'static>; N],
+    replacement: ErasedDescriptor<'static>,
+) -> [ErasedDescriptor<'static>; N] {
+    let mut i = 0;
+    while i < N {
+        if descriptors[i].is(replacement.module, replacement.name) {
+            descriptors[i] = replacement;
+        }
+        i += 1;
+    }
+    descriptors
+}
+
+/// Concatenates slices of descriptors in a const context.
+const fn flatten<const L: usize>(
+    parts: &[&[ErasedDescriptor<'static>]],
+) -> [ErasedDescriptor<'static>; L] {
+    const EMPTY: ErasedDescriptor<'static> = ErasedDescriptor {
+        module: "",
+        name: "",
+        args: &[],
+    };
+    let mut items = [EMPTY; L];
+    let (mut i, mut part) = (0, 0);
+    while part < parts.len() {
+        let mut j = 0;
+        while j < parts[part].len() {
+            items[i] = parts[part][j];
+            i += 1;
+            j += 1;
+        }
+        part += 1;
+    }
+    assert!(i == L);
+    items
+}
+
+const P0_DESCRIPTORS: &[ErasedDescriptor<'static>] = if cfg!(feature = "p0") {
+    &p0::DESCRIPTORS
+} else {
+    &[]
+};
+const P1_DESCRIPTORS: &[ErasedDescriptor<'static>] = if cfg!(feature = "p1") {
+    &p1::DESCRIPTORS
+} else {
+    &[]
+};
+const P2_DESCRIPTORS: &[ErasedDescriptor<'static>] = if cfg!(feature = "p2") {
+    &p2::DESCRIPTORS
+} else {
+    &[]
+};
+#[cfg(feature = "wasmedge-sock-v1")]
+const WASMEDGE_V1_DESCRIPTORS: &[ErasedDescriptor<'static>] = &wasmedge::v1::DESCRIPTORS;
+#[cfg(not(feature = "wasmedge-sock-v1"))]
+const WASMEDGE_V1_DESCRIPTORS: &[ErasedDescriptor<'static>] = &[];
+#[cfg(feature = "wasmedge-sock-v2")]
+const WASMEDGE_V2_DESCRIPTORS: &[ErasedDescriptor<'static>] = &wasmedge::v2::DESCRIPTORS;
+#[cfg(not(feature = "wasmedge-sock-v2"))]
+const WASMEDGE_V2_DESCRIPTORS: &[ErasedDescriptor<'static>] = &[];
+#[cfg(feature = "wasix")]
+const WASIX_DESCRIPTORS: &[ErasedDescriptor<'static>] = &wasix::DESCRIPTORS;
+#[cfg(not(feature = "wasix"))]
+const WASIX_DESCRIPTORS: &[ErasedDescriptor<'static>] = &[];
+#[cfg(feature = "wasi-threads")]
+const THREADS_DESCRIPTORS: &[ErasedDescriptor<'static>] = &threads::DESCRIPTORS;
+#[cfg(not(feature = "wasi-threads"))]
+const THREADS_DESCRIPTORS: &[ErasedDescriptor<'static>] = &[];
+const ALL_DESCRIPTORS: [ErasedDescriptor<'static>;
+    P0_DESCRIPTORS.len()
+        + P1_DESCRIPTORS.len()
+        + P2_DESCRIPTORS.len()
+        + WASMEDGE_V1_DESCRIPTORS.len()
+        + WASMEDGE_V2_DESCRIPTORS.len()
+        + WASIX_DESCRIPTORS.len()
+        + THREADS_DESCRIPTORS.len()] = flatten(&[
+    P0_DESCRIPTORS,
+    P1_DESCRIPTORS,
+    P2_DESCRIPTORS,
+    WASMEDGE_V1_DESCRIPTORS,
+    WASMEDGE_V2_DESCRIPTORS,
+    WASIX_DESCRIPTORS,
+    THREADS_DESCRIPTORS,
+]);
+/// Descriptors of the ABIs of all the enabled WASI versions.
+pub const DESCRIPTORS: &[ErasedDescriptor<'static>] = &ALL_DESCRIPTORS;
+
+/// Looks up the descriptor of the ABI `name` imported from `module`,
+/// e.g., `("wasi_snapshot_preview1", "fd_write")`, among the enabled WASI versions.
+#[allow(unused_variables)]
+pub fn descriptor_by_name(module: &str, name: &str) -> Option<&'static ErasedDescriptor<'static>> {
+    #[cfg(feature = "p0")]
+    if let Some(desc) = p0::descriptor_by_name(module, name) {
+        return Some(desc);
+    }
+    #[cfg(feature = "p1")]
+    if let Some(desc) = p1::descriptor_by_name(module, name) {
+        return Some(desc);
+    }
+    #[cfg(feature = "p2")]
+    if let Some(desc) = p2::descriptor_by_name(module, name) {
+        return Some(desc);
+    }
+    #[cfg(feature = "wasmedge-sock-v1")]
+    if let Some(desc) = wasmedge::v1::descriptor_by_name(module, name) {
+        return Some(desc);
+    }
+    #[cfg(feature = "wasmedge-sock-v2")]
+    if let Some(desc) = wasmedge::v2::descriptor_by_name(module, name) {
+        return Some(desc);
+    }
+    #[cfg(feature = "wasix")]
+    if let Some(desc) = wasix::descriptor_by_name(module, name) {
+        return Some(desc);
+    }
+    #[cfg(feature = "wasi-threads")]
+    if let Some(desc) = threads::descriptor_by_name(module, name) {
+        return Some(desc);
+    }
+    None
+}
+
+/// Looks up the descriptor of the ABI `name` imported from `module` whose core
+/// signature takes `params`, among the enabled WASI versions.
+///
+/// Unlike [`descriptor_by_name`], this tells apart ABIs sharing a module and a name,
+/// e.g., the standard `sock_accept` and that of [`wasmedge::v1`].
+pub fn descriptor_by_signature(
+    module: &str,
+    name: &str,
+    params: &[ValType],
+) -> Option<&'static ErasedDescriptor<'static>> {
+    DESCRIPTORS
+        .iter()
+        .find(|desc| desc.is(module, name) && desc.has_signature(params))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[allow(dead_code)]
+    mod host {
+        crate::declare_host_abis!("env";
+            /// Fetches `url` into `resp_ptr`.
+            host_http_fetch(url_ptr: u32, url_len: u32, resp_ptr: u32);
+            kv_get(key_ptr, key_len, val_ptr: u64);
+            now();
+        );
+    }
+
+    #[test]
+    fn host_abis() {
+        assert_eq!(host::MODULE, "env");
+        assert_eq!(host::WASI_NAMES, ["host_http_fetch", "kv_get", "now"]);
+        assert_eq!(host::DESCRIPTORS.len(), 3);
+        let desc = host::descriptor_by_name("env", "kv_get").unwrap();
+        assert!(desc.has_signature(&[ValType::I32, ValType::I32, ValType::I64]));
+        assert!(host::descriptor_by_name("env", "fd_write").is_none());
+        let _: host::host_http_fetch_params_default_t = (0, 0, 0);
+        assert_eq!(host::now.args.len(), 0);
+    }
+
+    #[test]
+    fn descriptors() {
+        #[cfg(feature = "p1")]
+        assert!(descriptor_by_name("wasi_snapshot_preview1", "fd_write").is_some());
+        #[cfg(feature = "p0")]
+        assert!(descriptor_by_name("wasi_unstable", "fd_write").is_some());
+        #[cfg(feature = "p2")]
+        assert!(descriptor_by_name("wasi:cli/environment@0.2.0", "get-arguments").is_some());
+        #[cfg(feature = "wasix")]
+        assert!(descriptor_by_name("wasix_32v1", "proc_fork").is_some());
+        #[cfg(feature = "wasmedge-sock-v2")]
+        assert!(descriptor_by_name("wasi_snapshot_preview1", "sock_getaddrinfo").is_some());
+        #[cfg(feature = "p1")]
+        {
+            use ValType::I32;
+            let accept = descriptor_by_name("wasi_snapshot_preview1", "sock_accept").unwrap();
+            assert_eq!(accept.args.len(), 3);
+            assert_eq!(
+                descriptor_by_signature("wasi_snapshot_preview1", "sock_accept", &[I32; 3]),
+                Some(accept)
+            );
+            let wasmedge =
+                descriptor_by_signature("wasi_snapshot_preview1", "sock_accept", &[I32; 2]);
+            assert_eq!(wasmedge.is_some(), cfg!(feature = "wasmedge-sock-v1"));
+        }
+        #[cfg(feature = "wasi-threads")]
+        assert!(descriptor_by_name("wasi", "thread-spawn").is_some());
+        assert!(descriptor_by_name("wasi_snapshot_preview1", "get-arguments").is_none());
+        for desc in DESCRIPTORS {
+            let params: [ValType; 16] = core::array::from_fn(|i| {
+                desc.args.get(i).map_or(ValType::I32, |arg| arg.val_type())
+            });
+            assert_eq!(
+                descriptor_by_signature(desc.module, desc.name, &params[..desc.args.len()]),
+                Some(desc)
+            );
+        }
+    }
+}
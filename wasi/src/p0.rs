@@ -0,0 +1,145 @@
+//! Descriptors for WASI snapshot 0, i.e., `wasi_unstable`.
+//!
+//! The ABIs of `wasi_unstable` are generated from the vendored
+//! `witx/snapshot0/*.witx` by `build.rs`. Their core signatures match those
+//! of preview1, but not all of their meanings do; see [`translate`].
+
+mod generated {
+    include!(concat!(env!("OUT_DIR"), "/wasi_unstable.rs"));
+}
+pub use generated::*;
+use wasi_descriptor::ErasedDescriptor;
+
+/// Looks up the descriptor of the ABI `name` imported from `module`.
+pub fn descriptor_by_name(module: &str, name: &str) -> Option<&'static ErasedDescriptor<'static>> {
+    let descriptors: &'static [ErasedDescriptor<'static>] = &DESCRIPTORS;
+    descriptors.iter().find(|desc| desc.is(module, name))
+}
+
+/// Translation of snapshot-0 calls into preview1 ones, so that policies
+/// written against the preview1 ABIs apply to `wasi_unstable` imports.
+///
+/// Only arguments passed by value are translated. Whatever is read or written
+/// through pointers keeps its snapshot-0 layout, e.g., the 32-bit `nlink` of
+/// `filestat` and the leading `identifier` of `subscription_clock`.
+pub mod translate {
+    use wasi_descriptor::ErasedDescriptor;
+
+    use super::{fd_seek_params_t, Whence};
+    use crate::p1;
+
+    /// Maps a snapshot-0 `whence` onto preview1, where `set`, `cur` and `end` are reordered.
+    ///
+    /// Values out of range are kept as they are.
+    pub const fn whence(whence: Whence) -> p1::Whence {
+        match whence {
+            Whence::CUR => p1::Whence::CUR,
+            Whence::END => p1::Whence::END,
+            Whence::SET => p1::Whence::SET,
+            _ => p1::Whence::from_raw(whence.raw()),
+        }
+    }
+    impl From<Whence> for p1::Whence {
+        fn from(value: Whence) -> Self {
+            whence(value)
+        }
+    }
+
+    /// Translates the parameters of [`fd_seek`](super::fd_seek).
+    pub const fn fd_seek(params: fd_seek_params_t) -> p1::fd_seek_params_t {
+        let (fd, offset, whence, newoffset_ptr) = params;
+        (fd, offset, self::whence(whence), newoffset_ptr)
+    }
+
+    /// An argument whose meaning differs between snapshot 0 and preview1.
+    #[derive(Debug, Clone, Copy)]
+    pub struct ArgRemap {
+        pub abi: &'static str,
+        pub arg: &'static str,
+        /// Maps the raw value in snapshot 0 to that in preview1.
+        pub remap: fn(u64) -> u64,
+    }
+
+    /// Arguments to be remapped by [`args`].
+    ///
+    /// A raw value out of the range of the type of the argument is kept as it is,
+    /// e.g., a `whence` of `0x102` is not taken for `set`.
+    pub const ARG_REMAPS: [ArgRemap; 1] = [ArgRemap {
+        abi: "fd_seek",
+        arg: "whence",
+        remap: |raw| match u8::try_from(raw) {
+            Ok(raw) => whence(Whence::from_raw(raw)).raw() as u64,
+            Err(_) => raw,
+        },
+    }];
+
+    /// Translates the raw core arguments of the snapshot-0 ABI `name` in place,
+    /// and returns the descriptor of the preview1 ABI to check them against.
+    ///
+    /// Returns `None`, leaving `args` untouched, if there is no such ABI in preview1
+    /// or `args` does not match its arity.
+    pub fn args(name: &str, args: &mut [u64]) -> Option<&'static ErasedDescriptor<'static>> {
+        let desc = p1::descriptor_by_name(p1::MODULE, name)?;
+        if desc.args.len() != args.len() {
+            return None;
+        }
+        for remap in ARG_REMAPS.iter().filter(|remap| remap.abi == name) {
+            if let Some(i) = desc.args.iter().position(|arg| arg.name == remap.arg) {
+                args[i] = (remap.remap)(args[i]);
+            }
+        }
+        Some(desc)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::p1;
+
+    #[test]
+    fn descriptors() {
+        assert_eq!(MODULE, "wasi_unstable");
+        assert_eq!(WASI_NAMES.len(), 45);
+        assert!(!WASI_NAMES.contains(&"sock_accept"));
+        assert!(descriptor_by_name("wasi_unstable", "fd_seek").is_some());
+        assert!(descriptor_by_name("wasi_snapshot_preview1", "fd_seek").is_none());
+
+        // Same core signatures as preview1.
+        for desc in &DESCRIPTORS {
+            let preview1 = p1::descriptor_by_name(p1::MODULE, desc.name).unwrap();
+            let sizes = |desc: &'static ErasedDescriptor| desc.args.iter().map(|arg| arg.size);
+            assert!(sizes(desc).eq(sizes(preview1)), "`{}`", desc.name);
+        }
+    }
+
+    #[test]
+    fn translate_whence() {
+        assert_eq!(Whence::SET.raw(), 2);
+        assert_eq!(translate::whence(Whence::SET), p1::Whence::SET);
+        assert_eq!(p1::Whence::from(Whence::CUR), p1::Whence::CUR);
+        assert_eq!(translate::whence(Whence::from_raw(7)).raw(), 7);
+        assert_eq!(
+            translate::fd_seek((3, -1, Whence::END, 8)),
+            (3, -1, p1::Whence::END, 8)
+        );
+    }
+
+    #[test]
+    fn translate_args() {
+        let mut args = [3, 0, Whence::SET.raw() as u64, 8];
+        let desc = translate::args("fd_seek", &mut args).unwrap();
+        assert!(desc.is(p1::MODULE, "fd_seek"));
+        assert_eq!(args, [3, 0, p1::Whence::SET.raw() as u64, 8]);
+
+        let mut args = [3, 0, 0x100 | Whence::SET.raw() as u64, 8];
+        assert!(translate::args("fd_seek", &mut args).is_some());
+        assert_eq!(args[2], 0x102);
+
+        let mut args = [1, 2];
+        assert!(translate::args("args_get", &mut args).is_some());
+        assert_eq!(args, [1, 2]);
+        assert!(translate::args("fd_seek", &mut [0; 3]).is_none());
+        assert!(translate::args("fd_fork", &mut [0; 2]).is_none());
+    }
+}
@@ -1,185 +1,18 @@
 //! Descriptors for WASI preview 1.
+//!
+//! The ABIs of `wasi_snapshot_preview1` are generated from the vendored
+//! `witx/preview1/*.witx` by `build.rs`.
 
-#[allow(unused_imports)]
-use wasi_descriptor::{desc_wasi_abi, WasiAbiDescriptor};
-
-use crate::declare_wasi_abis;
-
-/// Address type for WASM linear memory, i.e.,
-/// the offset from the base address of the WASM linear memory.
-pub type Waddr = u32;
-
-/// Size type for items in WASM linear memory.
-pub type Size = u32;
-
-/// File Descriptor.
-pub type Fd = u32;
-
-declare_wasi_abis!(
-    args_get(arg0, arg1);
-    args_sizes_get(arg0, arg1);
-
-    clock_res_get(arg0, arg1);
-    clock_time_get(clockid: u32, precision: u64, ret_addr: Waddr);
-
-    environ_get(env_addr: Waddr, env_buf_addr: Waddr);
-    environ_sizes_get(env_count_addr: Waddr, env_buf_size_addr: Waddr);
-
-    proc_exit(exitcode: u32);
-    proc_raise(sig);
-    sched_yield;
-
-    random_get(buf_addr: Waddr, buf_len: Size);
-
-    // FS
-    poll_oneoff(
-        in_ptr: Waddr,
-        out_ptr: Waddr,
-        nsubscriptions: Size,
-        nevents_ptr: Waddr,
-    );
-    fd_advise(arg0, arg1: i64, arg2: i64, arg3);
-    fd_allocate(arg0, arg1: i64, arg2: i64);
-    fd_close(fd: Fd);
-    fd_datasync(arg0);
-    fd_fdstat_get(fd: Fd, metadata_ptr: Waddr);
-    fd_fdstat_set_flags(fd: Fd, flags: u16);
-    fd_fdstat_set_rights(arg0, arg1: i64, arg2: i64);
-    fd_filestat_get(arg0, arg1);
-    fd_filestat_set_size(arg0, arg1: i64);
-    fd_filestat_set_times(arg0, arg1, arg2, arg3);
-    fd_pread(arg0, arg1, arg2, arg3: i64, arg4);
-    fd_prestat_get(fd: Fd, prestat_ptr: Waddr);
-    fd_prestat_dir_name(arg0, arg1, arg2);
-    fd_pwrite(arg0, arg1, arg2, arg3: i64, arg4);
-    fd_read(arg0, arg1, arg2, arg3);
-    fd_readdir(arg0, arg1, arg2, arg3: i64, arg4);
-    fd_renumber(arg0, arg1);
-    fd_seek(arg0, arg1: i64, arg2, arg3);
-    fd_sync(fd: Fd);
-    fd_tell(arg0, arg1);
-    fd_write(fd: Fd, iovs_addr: Waddr, iovs_len: Size, bytes_written_ptr: Waddr);
-    path_create_directory(arg0, arg1, arg2);
-    path_filestat_get(arg0, arg1, arg2, arg3, arg4);
-    path_filestat_set_times(arg0, arg1, arg2, arg3, arg4: i64, arg5: i64, arg6);
-    path_link(arg0, arg1, arg2, arg3, arg4, arg5, arg6);
-    path_open(arg0, arg1, arg2, arg3, arg4, arg5: i64, arg6: i64, arg7, arg8);
-    path_readlink(arg0, arg1, arg2, arg3, arg4, arg5);
-    path_remove_directory(arg0, arg1, arg2);
-    path_rename(arg0, arg1, arg2, arg3, arg4, arg5);
-    path_symlink(arg0, arg1, arg2, arg3, arg4);
-    path_unlink_file(arg0, arg1, arg2);
-);
-
-// Network
-declare_wasi_abis!(
-    sock_recv(
-        fd: Fd,
-        ri_data_ptr: Waddr,
-        ri_data_len: Size,
-        ri_flags,
-        ro_data_len_ptr: Waddr,
-        ro_flags_ptr: Waddr,
-    );
-    sock_send(
-        fd: Fd,
-        si_data_ptr: Waddr,
-        si_data_len: Size,
-        si_flags,
-        so_data_len_ptr: Waddr,
-    );
-    sock_shutdown(fd: Fd, how);
-);
-
-macro_rules! add_wasi_names {
-    ( $($wasi_name:ident),* ) => {{
-        [
-            "args_get","args_sizes_get",
-            "clock_res_get","clock_time_get",
-            "environ_get","environ_sizes_get",
-            "proc_exit","proc_raise","sched_yield",
-            "random_get",
-
-            "poll_oneoff","fd_advise","fd_allocate","fd_close","fd_datasync",
-            "fd_fdstat_get","fd_fdstat_set_flags","fd_fdstat_set_rights",
-            "fd_filestat_get","fd_filestat_set_size","fd_filestat_set_times",
-            "fd_pread","fd_prestat_get","fd_prestat_dir_name","fd_pwrite",
-            "fd_read","fd_readdir","fd_renumber","fd_seek","fd_sync","fd_tell",
-            "fd_write","path_create_directory","path_filestat_get",
-            "path_filestat_set_times","path_link","path_open","path_readlink",
-            "path_remove_directory","path_rename","path_symlink","path_unlink_file",
-
-            "sock_recv","sock_send","sock_shutdown","sock_accept",
-            $(stringify!($wasi_name)),*
-        ]
-    }};
+mod generated {
+    include!(concat!(env!("OUT_DIR"), "/wasi_snapshot_preview1.rs"));
 }
+pub use generated::*;
+use wasi_descriptor::ErasedDescriptor;
 
-cfg_if::cfg_if! {
-    if #[cfg(feature = "wasmedge-sock")] {
-        declare_wasi_abis!(
-            sock_listen(fd: Fd, backlog: Size);
-            sock_accept(fd: Fd, accepted_fd_ptr: Waddr);
-            sock_bind(fd: Fd, addr_buf_ptr: Waddr, port_num: u32);
-            sock_connect(fd: Fd, addr_ptr: Waddr, port_num: u32);
-            sock_open(addr_family: u8, sock_type: u8, fd_ptr: Waddr);
-            sock_recv_from(
-                fd: Fd,
-                ri_data_ptr: Waddr,
-                ri_data_len: Size,
-                src_addr_ptr: Waddr,
-                ri_flags,
-                src_port_ptr: Waddr,
-                ro_data_len_ptr: Waddr,
-                ro_flags_ptr: Waddr,
-            );
-            sock_send_to(
-                fd: Fd,
-                si_data_ptr: Waddr,
-                si_data_len: Size,
-                dst_addr_ptr: Waddr,
-                dst_port: u32,
-                si_flags,
-                so_data_len_ptr: Waddr,
-            );
-            sock_getpeeraddr(
-                fd: Fd,
-                peeraddr_ptr: Waddr,
-                peeraddr_type_ptr: Waddr,
-                peerport_ptr: Waddr,
-            );
-            sock_getlocaladdr(
-                fd: Fd,
-                localaddr_ptr: Waddr,
-                localaddr_type_ptr: Waddr,
-                localport_ptr: Waddr,
-            );
-            sock_getsockopt(
-                fd: Fd,
-                level,
-                name,
-                flag_ptr: Waddr,
-                flag_size_ptr: Waddr,
-            );
-            sock_setsockopt(
-                fd: Fd,
-                level,
-                name,
-                flag_ptr: Waddr,
-                flag_size: Size,
-            );
-        );
-        pub const WASI_NAMES: [&str; 42 + 4 + 10] = add_wasi_names!(
-            sock_listen, sock_bind,sock_connect, sock_open,
-            sock_recv_from, sock_send_to, sock_getpeeraddr, sock_getlocaladdr,
-            sock_getsockopt, sock_setsockopt
-        );
-    } else {
-        declare_wasi_abis!(
-            sock_accept(fd: Fd, flags, accepted_fd_ptr: Waddr);
-        );
-        pub const WASI_NAMES: [&str; 42 + 4] = add_wasi_names!();
-    }
+/// Looks up the descriptor of the ABI `name` imported from `module`.
+pub fn descriptor_by_name(module: &str, name: &str) -> Option<&'static ErasedDescriptor<'static>> {
+    let descriptors: &'static [ErasedDescriptor<'static>] = &DESCRIPTORS;
+    descriptors.iter().find(|desc| desc.is(module, name))
 }
 
 #[cfg(test)]
@@ -194,17 +27,103 @@ mod tests {
     }
 
     #[test]
-    fn number() {
-        cfg_if::cfg_if! {
-            if #[cfg(feature = "wasmedge-sock")] {
-                assert_eq!(WASI_NAMES.len(), 42 + 4 + 10);
-                assert!(WASI_NAMES.iter().any(|&name| name == "sock_connect"));
-            } else {
-                assert_eq!(WASI_NAMES.len(), 42 + 4);
-                const LAST_IDX:usize = 42 + 4 - 1;
-                assert_eq!(WASI_NAMES[LAST_IDX], "sock_accept");
+    fn arg_kinds() {
+        use wasi_descriptor::{ArgKind, ValType};
+
+        assert_eq!(path_open.arg("fd").unwrap().kind, ArgKind::Fd);
+        assert_eq!(path_open.arg_index("oflags"), Some(4));
+        assert_eq!(path_open.args[4].kind, Oflags::KIND);
+        assert_eq!(
+            path_open.arg("path_ptr").unwrap().kind,
+            ArgKind::Pointer {
+                pointee: "u8",
+                len_arg: Some("path_len")
             }
+        );
+        assert!(path_open.arg("fd_ptr").unwrap().is_pointer());
+        assert_eq!(
+            path_open.arg("fd_ptr").unwrap().kind,
+            ArgKind::Out { pointee: "fd" }
+        );
+        assert_eq!(fd_write.out_count(), 1);
+        assert_eq!(sock_recv.out_count(), 2);
+        let _: fd_write_post_params_default_t = (1, 0, 1, 0, 0, 8);
+        let _: fd_seek_params_raw_t = (3i32, -1i64, 0i32, 0i32);
+        let _: fd_write_post_params_t = (1u32, 0u32, 1u32, 0u32, 0u16, 8u64);
+        let _: fd_write_post_params_raw_t = (1i32, 0i32, 1i32, 0i32, 0u16, 8u64);
+        assert_eq!(path_open.arg("nonexistent"), None);
+        assert_eq!(
+            fd_seek.val_types(),
+            [ValType::I32, ValType::I64, ValType::I32, ValType::I32]
+        );
+        assert_eq!(fd_seek.args[1].kind, ArgKind::Int { signed: true });
+        assert_eq!(fd_seek.args[2].kind, ArgKind::Enum(&["SET", "CUR", "END"]));
+        assert_eq!(clock_time_get.args[1].kind, ArgKind::Timestamp);
+        assert_eq!(
+            fd_readdir.arg("buf_ptr").unwrap().kind,
+            ArgKind::Pointer {
+                pointee: "u8",
+                len_arg: Some("buf_len")
+            }
+        );
+        assert_eq!(
+            args_get.args[0].kind,
+            ArgKind::Pointer {
+                pointee: "*u8",
+                len_arg: None
+            }
+        );
+    }
+
+    #[test]
+    fn descriptors() {
+        use wasi_descriptor::AbiDescriptor;
+
+        assert_eq!(DESCRIPTORS.len(), WASI_NAMES.len());
+        for (desc, name) in DESCRIPTORS.iter().zip(WASI_NAMES) {
+            assert_eq!(desc.module, "wasi_snapshot_preview1");
+            assert_eq!(desc.name, name);
         }
+        let desc = descriptor_by_name("wasi_snapshot_preview1", "fd_write").unwrap();
+        assert_eq!(desc.args, fd_write.args);
+        assert_eq!(desc.arg_index("iovs_len"), Some(2));
+        assert!(descriptor_by_name("wasi_unstable", "fd_write").is_none());
+        assert!(descriptor_by_name("wasi_snapshot_preview1", "fd_wirte").is_none());
+
+        let desc = descriptor_by_name("wasi_snapshot_preview1", "sock_accept").unwrap();
+        assert_eq!(desc.args, sock_accept.args);
+        assert!(descriptor_by_name("wasi_snapshot_preview1", "sock_listen").is_none());
+    }
+
+    #[test]
+    fn witx_constants() {
+        assert_eq!((OFLAGS_CREAT | OFLAGS_TRUNC).raw(), 0b1001);
+        assert_eq!(RIGHTS_FD_WRITE.raw(), 1 << 6);
+        assert_eq!(WHENCE_END, Whence::END);
+        assert_eq!(
+            Errno::NAMES[ERRNO_PERM.raw() as usize],
+            ("PERM", ERRNO_PERM)
+        );
+        assert_eq!(Errno::from_name("2BIG"), Some(ERRNO_2BIG));
+        assert_eq!(Errno::from_raw(u16::MAX).name(), None);
+        assert_eq!(
+            Filetype::KIND,
+            wasi_descriptor::ArgKind::Enum(&Filetype::NAMES.map(|(name, _)| name))
+        );
+
+        let oflags = Oflags::from_raw(0b0101);
+        assert!(oflags.contains(Oflags::CREAT));
+        assert!(!oflags.contains(Oflags::CREAT | Oflags::TRUNC));
+        assert!((Rights::FD_READ | Rights::FD_WRITE).intersects(Rights::FD_WRITE));
+        assert_eq!(Fdflags::from_name("append"), Some(Fdflags::APPEND));
+        assert_eq!(Lookupflags::all(), LOOKUPFLAGS_SYMLINK_FOLLOW);
+    }
+
+    #[test]
+    fn number() {
+        assert_eq!(WASI_NAMES.len(), 42 + 4);
+        const LAST_IDX: usize = 42 + 4 - 1;
+        assert_eq!(WASI_NAMES[LAST_IDX], "sock_shutdown");
     }
 
     fn type_equals<A: 'static, B: 'static>(_a: &A, _b: &B) -> bool {
@@ -225,10 +144,126 @@ mod tests {
     }
     #[test]
     fn param_type_2() {
-        let a0 = (0u32, 1u32);
-        let a1 = (1i32, 2i32);
-        let b: clock_res_get_params_t = (1, 2);
-        assert!(!type_equals(&a0, &b));
-        assert!(type_equals(&a1, &b));
+        let a0 = (Clockid::REALTIME, 1u32);
+        let a1 = (1u32, 2u32);
+        let b: clock_res_get_params_t = (Clockid::MONOTONIC, 2);
+        assert!(type_equals(&a0, &b));
+        assert!(!type_equals(&a1, &b));
+    }
+    #[test]
+    fn param_type_i64() {
+        let a = (Clockid::REALTIME, 0u64, 0u32);
+        let b: clock_time_get_params_t = (Clockid::MONOTONIC, 2, 3);
+        assert!(type_equals(&a, &b));
+        let b: fd_seek_params_t = (1, -2, Whence::SET, 3);
+        assert!(type_equals(&(0u32, 0i64, Whence::CUR, 0u32), &b));
+    }
+
+    /// Asserts that a descriptor matches the lowered core signature in the witx,
+    /// i.e., the names and the wasm value types (`I32` or `I64`) of its arguments.
+    macro_rules! assert_witx_signature {
+        ($abi:ident) => {
+            assert_witx_signature!($abi());
+        };
+        ($abi:ident ( $($arg:ident : $val_ty:ident),* )) => {{
+            let expected: &[(&str, usize)] = &[$((stringify!($arg), $val_ty)),*];
+            assert_eq!($abi.name, stringify!($abi));
+            assert_eq!($abi.args.len(), expected.len(), "arity of `{}`", $abi.name);
+            for (arg, (name, val_ty)) in $abi.args.iter().zip(expected) {
+                assert_eq!(arg.name, *name, "arg name of `{}`", $abi.name);
+                let arg_val_ty = if arg.size <= I32 { I32 } else { I64 };
+                assert_eq!(arg_val_ty, *val_ty, "`{}` of `{}`", arg.name, $abi.name);
+            }
+        }};
+    }
+    const I32: usize = size_of::<i32>();
+    const I64: usize = size_of::<i64>();
+
+    #[test]
+    fn witx_signatures() {
+        assert_witx_signature!(args_get(argv_ptr: I32, argv_buf_ptr: I32));
+        assert_witx_signature!(args_sizes_get(size0_ptr: I32, size1_ptr: I32));
+        assert_witx_signature!(clock_res_get(id: I32, timestamp_ptr: I32));
+        assert_witx_signature!(clock_time_get(id: I32, precision: I64, timestamp_ptr: I32));
+        assert_witx_signature!(environ_get(environ_ptr: I32, environ_buf_ptr: I32));
+        assert_witx_signature!(environ_sizes_get(size0_ptr: I32, size1_ptr: I32));
+        assert_witx_signature!(proc_exit(rval: I32));
+        assert_witx_signature!(proc_raise(sig: I32));
+        assert_witx_signature!(sched_yield);
+        assert_witx_signature!(random_get(buf_ptr: I32, buf_len: I32));
+
+        assert_witx_signature!(poll_oneoff(
+            in_ptr: I32, out_ptr: I32, nsubscriptions: I32, size_ptr: I32
+        ));
+        assert_witx_signature!(fd_advise(fd: I32, offset: I64, len: I64, advice: I32));
+        assert_witx_signature!(fd_allocate(fd: I32, offset: I64, len: I64));
+        assert_witx_signature!(fd_close(fd: I32));
+        assert_witx_signature!(fd_datasync(fd: I32));
+        assert_witx_signature!(fd_fdstat_get(fd: I32, fdstat_ptr: I32));
+        assert_witx_signature!(fd_fdstat_set_flags(fd: I32, flags: I32));
+        assert_witx_signature!(fd_fdstat_set_rights(
+            fd: I32, fs_rights_base: I64, fs_rights_inheriting: I64
+        ));
+        assert_witx_signature!(fd_filestat_get(fd: I32, filestat_ptr: I32));
+        assert_witx_signature!(fd_filestat_set_size(fd: I32, size: I64));
+        assert_witx_signature!(fd_filestat_set_times(
+            fd: I32, atim: I64, mtim: I64, fst_flags: I32
+        ));
+        assert_witx_signature!(fd_pread(
+            fd: I32, iovs_ptr: I32, iovs_len: I32, offset: I64, size_ptr: I32
+        ));
+        assert_witx_signature!(fd_prestat_get(fd: I32, prestat_ptr: I32));
+        assert_witx_signature!(fd_prestat_dir_name(fd: I32, path_ptr: I32, path_len: I32));
+        assert_witx_signature!(fd_pwrite(
+            fd: I32, iovs_ptr: I32, iovs_len: I32, offset: I64, size_ptr: I32
+        ));
+        assert_witx_signature!(fd_read(fd: I32, iovs_ptr: I32, iovs_len: I32, size_ptr: I32));
+        assert_witx_signature!(fd_readdir(
+            fd: I32, buf_ptr: I32, buf_len: I32, cookie: I64, size_ptr: I32
+        ));
+        assert_witx_signature!(fd_renumber(fd: I32, to: I32));
+        assert_witx_signature!(fd_seek(fd: I32, offset: I64, whence: I32, filesize_ptr: I32));
+        assert_witx_signature!(fd_sync(fd: I32));
+        assert_witx_signature!(fd_tell(fd: I32, filesize_ptr: I32));
+        assert_witx_signature!(fd_write(fd: I32, iovs_ptr: I32, iovs_len: I32, size_ptr: I32));
+
+        assert_witx_signature!(path_create_directory(fd: I32, path_ptr: I32, path_len: I32));
+        assert_witx_signature!(path_filestat_get(
+            fd: I32, flags: I32, path_ptr: I32, path_len: I32, filestat_ptr: I32
+        ));
+        assert_witx_signature!(path_filestat_set_times(
+            fd: I32, flags: I32, path_ptr: I32, path_len: I32,
+            atim: I64, mtim: I64, fst_flags: I32
+        ));
+        assert_witx_signature!(path_link(
+            old_fd: I32, old_flags: I32, old_path_ptr: I32, old_path_len: I32,
+            new_fd: I32, new_path_ptr: I32, new_path_len: I32
+        ));
+        assert_witx_signature!(path_open(
+            fd: I32, dirflags: I32, path_ptr: I32, path_len: I32, oflags: I32,
+            fs_rights_base: I64, fs_rights_inheriting: I64, fdflags: I32, fd_ptr: I32
+        ));
+        assert_witx_signature!(path_readlink(
+            fd: I32, path_ptr: I32, path_len: I32, buf_ptr: I32, buf_len: I32, size_ptr: I32
+        ));
+        assert_witx_signature!(path_remove_directory(fd: I32, path_ptr: I32, path_len: I32));
+        assert_witx_signature!(path_rename(
+            fd: I32, old_path_ptr: I32, old_path_len: I32,
+            new_fd: I32, new_path_ptr: I32, new_path_len: I32
+        ));
+        assert_witx_signature!(path_symlink(
+            old_path_ptr: I32, old_path_len: I32, fd: I32, new_path_ptr: I32, new_path_len: I32
+        ));
+        assert_witx_signature!(path_unlink_file(fd: I32, path_ptr: I32, path_len: I32));
+
+        assert_witx_signature!(sock_recv(
+            fd: I32, ri_data_ptr: I32, ri_data_len: I32, ri_flags: I32,
+            size_ptr: I32, roflags_ptr: I32
+        ));
+        assert_witx_signature!(sock_send(
+            fd: I32, si_data_ptr: I32, si_data_len: I32, si_flags: I32, size_ptr: I32
+        ));
+        assert_witx_signature!(sock_shutdown(fd: I32, how: I32));
+        assert_witx_signature!(sock_accept(fd: I32, flags: I32, fd_ptr: I32));
     }
 }
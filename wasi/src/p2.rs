@@ -0,0 +1,846 @@
+//! Descriptors for WASI 0.2, i.e., the interfaces of the component model.
+//!
+//! Components import the functions of an interface, e.g.,
+//! `wasi:filesystem/types@0.2.0`, by their names in WIT, where resource methods
+//! are named like `[method]descriptor.read-via-stream` and the destructors of
+//! resources are named like `[resource-drop]descriptor`. Each interface is a
+//! module here, whose [`INTERFACE`][filesystem::types::INTERFACE] is the name of
+//! the interface and whose descriptors are named after the functions in
+//! snake case, e.g., `descriptor_read_via_stream`.
+//!
+//! Functions are lowered to their core wasm signatures by the canonical ABI:
+//! - `self` and other resource handles are passed as [`Handle`][canonical::Handle]s;
+//! - `string`s and `list`s are passed as a `(*_ptr, *_len)` pair;
+//! - `record`s and `variant`s are flattened into their fields;
+//! - results that can not be returned as a single core value are written
+//!   through a trailing `ret_ptr`.
+
+use wasi_descriptor::ErasedDescriptor;
+
+/// Core types of the canonical ABI.
+pub mod canonical {
+    /// Address type for WASM linear memory, i.e.,
+    /// the offset from the base address of the WASM linear memory.
+    pub type Waddr = u32;
+
+    /// Length of a `string` or `list` in WASM linear memory.
+    pub type Size = u32;
+
+    /// Index of an `own<T>` or `borrow<T>` resource in the handle table.
+    pub type Handle = u32;
+}
+
+/// Declares the descriptors of an interface, with its `INTERFACE` name, `FUNC_NAMES` and `DESCRIPTORS`.
+macro_rules! declare_interface {
+    (
+        $interface:literal;
+        $( $(#[$attr:meta])* $rust_name:ident = $func:literal ( $($arg:tt)* ); )*
+    ) => {
+        /// Name of the interface, including its package and version.
+        pub const INTERFACE: &str = $interface;
+
+        crate::declare_wasi_abis!( $( $(#[$attr])* $rust_name = $func ( $($arg)* ); )* );
+
+        /// Names of the functions imported from [`INTERFACE`].
+        pub const FUNC_NAMES: [&str; crate::__count_args!($($rust_name),*)] = [$($func),*];
+
+        /// Rust names of the [`FUNC_NAMES`], e.g., `descriptor_open_at` for
+        /// `[method]descriptor.open-at`.
+        pub const WASI_NAMES: [&str; FUNC_NAMES.len()] = [$(stringify!($rust_name)),*];
+
+        /// Type-erased descriptors of the functions imported from [`INTERFACE`].
+        pub const DESCRIPTORS: [wasi_descriptor::ErasedDescriptor<'static>; FUNC_NAMES.len()] =
+            [$(wasi_descriptor::erase_desc!(INTERFACE, $rust_name)),*];
+    };
+}
+
+pub mod cli {
+    pub mod environment {
+        use super::super::canonical::*;
+
+        declare_interface!(
+            "wasi:cli/environment@0.2.0";
+            /// Get the POSIX-style environment variables.
+            get_environment = "get-environment"(ret_ptr: Waddr);
+            /// Get the POSIX-style arguments to the program.
+            get_arguments = "get-arguments"(ret_ptr: Waddr);
+            /// Return a path that programs should use as their initial current working
+            /// directory, interpreting `.` as shorthand for this.
+            initial_cwd = "initial-cwd"(ret_ptr: Waddr);
+        );
+    }
+}
+
+pub mod clocks {
+    pub mod monotonic_clock {
+        /// An instant in time, in nanoseconds.
+        pub type Instant = u64;
+
+        /// A duration of time, in nanoseconds.
+        pub type Duration = u64;
+
+        declare_interface!(
+            "wasi:clocks/monotonic-clock@0.2.0";
+            /// Read the current value of the clock.
+            now = "now"();
+            /// Query the resolution of the clock.
+            resolution = "resolution"();
+            /// Create a `pollable` which will resolve once the specified instant occured.
+            subscribe_instant = "subscribe-instant"(when: Instant);
+            /// Create a `pollable` which will resolve once the given duration has elapsed.
+            subscribe_duration = "subscribe-duration"(when: Duration);
+        );
+    }
+
+    pub mod wall_clock {
+        use super::super::canonical::*;
+
+        declare_interface!(
+            "wasi:clocks/wall-clock@0.2.0";
+            /// Read the current value of the clock.
+            now = "now"(ret_ptr: Waddr);
+            /// Query the resolution of the clock.
+            resolution = "resolution"(ret_ptr: Waddr);
+        );
+    }
+}
+
+pub mod filesystem {
+    pub mod types {
+        use super::super::canonical::*;
+
+        /// A handle to a `descriptor` resource.
+        pub type Descriptor = Handle;
+
+        /// File size or length of a region within a file.
+        pub type Filesize = u64;
+
+        /// File or memory access pattern advisory information.
+        pub type Advice = u8;
+
+        /// Flags determining the method of how paths are resolved.
+        pub type PathFlags = u8;
+
+        /// Open flags used by `open-at`.
+        pub type OpenFlags = u8;
+
+        /// Descriptor flags.
+        pub type DescriptorFlags = u8;
+
+        /// Discriminant of a `new-timestamp`, i.e., `no-change`, `now` or `timestamp`.
+        pub type NewTimestampTag = u8;
+
+        // A `new-timestamp` is flattened into its tag and the
+        // `seconds` and `nanoseconds` of the `datetime` of `timestamp`.
+        declare_interface!(
+            "wasi:filesystem/types@0.2.0";
+            /// Return a stream for reading from a file, if available.
+            descriptor_read_via_stream = "[method]descriptor.read-via-stream"(
+                self: Descriptor,
+                offset: Filesize,
+                ret_ptr: Waddr,
+            );
+            /// Return a stream for writing to a file, if available.
+            descriptor_write_via_stream = "[method]descriptor.write-via-stream"(
+                self: Descriptor,
+                offset: Filesize,
+                ret_ptr: Waddr,
+            );
+            /// Return a stream for appending to a file, if available.
+            descriptor_append_via_stream = "[method]descriptor.append-via-stream"(
+                self: Descriptor,
+                ret_ptr: Waddr,
+            );
+            /// Provide file advisory information on a descriptor.
+            descriptor_advise = "[method]descriptor.advise"(
+                self: Descriptor,
+                offset: Filesize,
+                length: Filesize,
+                advice: Advice,
+                ret_ptr: Waddr,
+            );
+            /// Synchronize the data of a file to disk.
+            descriptor_sync_data = "[method]descriptor.sync-data"(self: Descriptor, ret_ptr: Waddr);
+            /// Get flags associated with a descriptor.
+            descriptor_get_flags = "[method]descriptor.get-flags"(self: Descriptor, ret_ptr: Waddr);
+            /// Get the dynamic type of a descriptor.
+            descriptor_get_type = "[method]descriptor.get-type"(self: Descriptor, ret_ptr: Waddr);
+            /// Adjust the size of an open file.
+            descriptor_set_size = "[method]descriptor.set-size"(
+                self: Descriptor,
+                size: Filesize,
+                ret_ptr: Waddr,
+            );
+            /// Adjust the timestamps of an open file or directory.
+            descriptor_set_times = "[method]descriptor.set-times"(
+                self: Descriptor,
+                data_access_timestamp_tag: NewTimestampTag,
+                data_access_timestamp_seconds: u64,
+                data_access_timestamp_nanoseconds: u32,
+                data_modification_timestamp_tag: NewTimestampTag,
+                data_modification_timestamp_seconds: u64,
+                data_modification_timestamp_nanoseconds: u32,
+                ret_ptr: Waddr,
+            );
+            /// Read from a descriptor, without using and updating the descriptor's offset.
+            descriptor_read = "[method]descriptor.read"(
+                self: Descriptor,
+                length: Filesize,
+                offset: Filesize,
+                ret_ptr: Waddr,
+            );
+            /// Write to a descriptor, without using and updating the descriptor's offset.
+            descriptor_write = "[method]descriptor.write"(
+                self: Descriptor,
+                buffer_ptr: Waddr,
+                buffer_len: Size,
+                offset: Filesize,
+                ret_ptr: Waddr,
+            );
+            /// Read directory entries from a directory.
+            descriptor_read_directory = "[method]descriptor.read-directory"(
+                self: Descriptor,
+                ret_ptr: Waddr,
+            );
+            /// Synchronize the data and metadata of a file to disk.
+            descriptor_sync = "[method]descriptor.sync"(self: Descriptor, ret_ptr: Waddr);
+            /// Create a directory.
+            descriptor_create_directory_at = "[method]descriptor.create-directory-at"(
+                self: Descriptor,
+                path_ptr: Waddr,
+                path_len: Size,
+                ret_ptr: Waddr,
+            );
+            /// Return the attributes of an open file or directory.
+            descriptor_stat = "[method]descriptor.stat"(self: Descriptor, ret_ptr: Waddr);
+            /// Return the attributes of a file or directory.
+            descriptor_stat_at = "[method]descriptor.stat-at"(
+                self: Descriptor,
+                path_flags: PathFlags,
+                path_ptr: Waddr,
+                path_len: Size,
+                ret_ptr: Waddr,
+            );
+            /// Adjust the timestamps of a file or directory.
+            descriptor_set_times_at = "[method]descriptor.set-times-at"(
+                self: Descriptor,
+                path_flags: PathFlags,
+                path_ptr: Waddr,
+                path_len: Size,
+                data_access_timestamp_tag: NewTimestampTag,
+                data_access_timestamp_seconds: u64,
+                data_access_timestamp_nanoseconds: u32,
+                data_modification_timestamp_tag: NewTimestampTag,
+                data_modification_timestamp_seconds: u64,
+                data_modification_timestamp_nanoseconds: u32,
+                ret_ptr: Waddr,
+            );
+            /// Create a hard link.
+            descriptor_link_at = "[method]descriptor.link-at"(
+                self: Descriptor,
+                old_path_flags: PathFlags,
+                old_path_ptr: Waddr,
+                old_path_len: Size,
+                new_descriptor: Descriptor,
+                new_path_ptr: Waddr,
+                new_path_len: Size,
+                ret_ptr: Waddr,
+            );
+            /// Open a file or directory.
+            descriptor_open_at = "[method]descriptor.open-at"(
+                self: Descriptor,
+                path_flags: PathFlags,
+                path_ptr: Waddr,
+                path_len: Size,
+                open_flags: OpenFlags,
+                flags: DescriptorFlags,
+                ret_ptr: Waddr,
+            );
+            /// Read the contents of a symbolic link.
+            descriptor_readlink_at = "[method]descriptor.readlink-at"(
+                self: Descriptor,
+                path_ptr: Waddr,
+                path_len: Size,
+                ret_ptr: Waddr,
+            );
+            /// Remove a directory.
+            descriptor_remove_directory_at = "[method]descriptor.remove-directory-at"(
+                self: Descriptor,
+                path_ptr: Waddr,
+                path_len: Size,
+                ret_ptr: Waddr,
+            );
+            /// Rename a filesystem object.
+            descriptor_rename_at = "[method]descriptor.rename-at"(
+                self: Descriptor,
+                old_path_ptr: Waddr,
+                old_path_len: Size,
+                new_descriptor: Descriptor,
+                new_path_ptr: Waddr,
+                new_path_len: Size,
+                ret_ptr: Waddr,
+            );
+            /// Create a symbolic link.
+            descriptor_symlink_at = "[method]descriptor.symlink-at"(
+                self: Descriptor,
+                old_path_ptr: Waddr,
+                old_path_len: Size,
+                new_path_ptr: Waddr,
+                new_path_len: Size,
+                ret_ptr: Waddr,
+            );
+            /// Unlink a filesystem object that is not a directory.
+            descriptor_unlink_file_at = "[method]descriptor.unlink-file-at"(
+                self: Descriptor,
+                path_ptr: Waddr,
+                path_len: Size,
+                ret_ptr: Waddr,
+            );
+            /// Test whether two descriptors refer to the same filesystem object.
+            descriptor_is_same_object = "[method]descriptor.is-same-object"(
+                self: Descriptor,
+                other: Descriptor,
+            );
+            /// Return a hash of the metadata associated with a filesystem object.
+            descriptor_metadata_hash = "[method]descriptor.metadata-hash"(
+                self: Descriptor,
+                ret_ptr: Waddr,
+            );
+            /// Return a hash of the metadata associated with a filesystem object
+            /// referred to by a directory descriptor and a relative path.
+            descriptor_metadata_hash_at = "[method]descriptor.metadata-hash-at"(
+                self: Descriptor,
+                path_flags: PathFlags,
+                path_ptr: Waddr,
+                path_len: Size,
+                ret_ptr: Waddr,
+            );
+            /// Read a single directory entry from a `directory-entry-stream`.
+            directory_entry_stream_read_directory_entry =
+                "[method]directory-entry-stream.read-directory-entry"(self: Handle, ret_ptr: Waddr);
+            /// Attempts to extract a filesystem-related `error-code` from the stream `error`.
+            filesystem_error_code = "filesystem-error-code"(err: Handle, ret_ptr: Waddr);
+            /// Drop a `descriptor`.
+            drop_descriptor = "[resource-drop]descriptor"(self: Descriptor);
+            /// Drop a `directory-entry-stream`.
+            drop_directory_entry_stream = "[resource-drop]directory-entry-stream"(self: Handle);
+        );
+    }
+}
+
+pub mod io {
+    pub mod streams {
+        use super::super::canonical::*;
+
+        /// A handle to an `input-stream` resource.
+        pub type InputStream = Handle;
+
+        /// A handle to an `output-stream` resource.
+        pub type OutputStream = Handle;
+
+        declare_interface!(
+            "wasi:io/streams@0.2.0";
+            /// Perform a non-blocking read from the stream.
+            input_stream_read = "[method]input-stream.read"(
+                self: InputStream,
+                len: u64,
+                ret_ptr: Waddr,
+            );
+            /// Read bytes from a stream, after blocking until at least one byte can be read.
+            input_stream_blocking_read = "[method]input-stream.blocking-read"(
+                self: InputStream,
+                len: u64,
+                ret_ptr: Waddr,
+            );
+            /// Skip bytes from a stream.
+            input_stream_skip = "[method]input-stream.skip"(
+                self: InputStream,
+                len: u64,
+                ret_ptr: Waddr,
+            );
+            /// Skip bytes from a stream, after blocking until at least one byte can be skipped.
+            input_stream_blocking_skip = "[method]input-stream.blocking-skip"(
+                self: InputStream,
+                len: u64,
+                ret_ptr: Waddr,
+            );
+            /// Create a `pollable` which will resolve once either the specified stream
+            /// has bytes available to read or the other end of the stream has been closed.
+            input_stream_subscribe = "[method]input-stream.subscribe"(self: InputStream);
+            /// Check readiness for writing.
+            output_stream_check_write = "[method]output-stream.check-write"(
+                self: OutputStream,
+                ret_ptr: Waddr,
+            );
+            /// Perform a write.
+            output_stream_write = "[method]output-stream.write"(
+                self: OutputStream,
+                contents_ptr: Waddr,
+                contents_len: Size,
+                ret_ptr: Waddr,
+            );
+            /// Perform a write of up to 4096 bytes, and then flush the stream.
+            output_stream_blocking_write_and_flush =
+                "[method]output-stream.blocking-write-and-flush"(
+                    self: OutputStream,
+                    contents_ptr: Waddr,
+                    contents_len: Size,
+                    ret_ptr: Waddr,
+                );
+            /// Request to flush buffered output.
+            output_stream_flush = "[method]output-stream.flush"(self: OutputStream, ret_ptr: Waddr);
+            /// Request to flush buffered output, and block until flush completes.
+            output_stream_blocking_flush = "[method]output-stream.blocking-flush"(
+                self: OutputStream,
+                ret_ptr: Waddr,
+            );
+            /// Create a `pollable` which will resolve once the output-stream
+            /// is ready for more writing, or an error has occured.
+            output_stream_subscribe = "[method]output-stream.subscribe"(self: OutputStream);
+            /// Write zeroes to a stream.
+            output_stream_write_zeroes = "[method]output-stream.write-zeroes"(
+                self: OutputStream,
+                len: u64,
+                ret_ptr: Waddr,
+            );
+            /// Perform a write of up to 4096 zeroes, and then flush the stream.
+            output_stream_blocking_write_zeroes_and_flush =
+                "[method]output-stream.blocking-write-zeroes-and-flush"(
+                    self: OutputStream,
+                    len: u64,
+                    ret_ptr: Waddr,
+                );
+            /// Read from one stream and write to another.
+            output_stream_splice = "[method]output-stream.splice"(
+                self: OutputStream,
+                src: InputStream,
+                len: u64,
+                ret_ptr: Waddr,
+            );
+            /// Read from one stream and write to another, with blocking.
+            output_stream_blocking_splice = "[method]output-stream.blocking-splice"(
+                self: OutputStream,
+                src: InputStream,
+                len: u64,
+                ret_ptr: Waddr,
+            );
+            /// Drop an `input-stream`.
+            drop_input_stream = "[resource-drop]input-stream"(self: InputStream);
+            /// Drop an `output-stream`.
+            drop_output_stream = "[resource-drop]output-stream"(self: OutputStream);
+        );
+    }
+}
+
+pub mod random {
+    // Named after `wasi:random/random`.
+    #[allow(clippy::module_inception)]
+    pub mod random {
+        use super::super::canonical::*;
+
+        declare_interface!(
+            "wasi:random/random@0.2.0";
+            /// Return `len` cryptographically-secure random or pseudo-random bytes.
+            get_random_bytes = "get-random-bytes"(len: u64, ret_ptr: Waddr);
+            /// Return a cryptographically-secure random or pseudo-random `u64` value.
+            get_random_u64 = "get-random-u64"();
+        );
+    }
+
+    pub mod insecure {
+        use super::super::canonical::*;
+
+        declare_interface!(
+            "wasi:random/insecure@0.2.0";
+            /// Return `len` insecure pseudo-random bytes.
+            get_insecure_random_bytes = "get-insecure-random-bytes"(len: u64, ret_ptr: Waddr);
+            /// Return an insecure pseudo-random `u64` value.
+            get_insecure_random_u64 = "get-insecure-random-u64"();
+        );
+    }
+
+    pub mod insecure_seed {
+        use super::super::canonical::*;
+
+        declare_interface!(
+            "wasi:random/insecure-seed@0.2.0";
+            /// Return a 128-bit value that may contain a pseudo-random value.
+            insecure_seed = "insecure-seed"(ret_ptr: Waddr);
+        );
+    }
+}
+
+pub mod sockets {
+    pub mod tcp {
+        use super::super::canonical::*;
+
+        /// A handle to a `tcp-socket` resource.
+        pub type TcpSocket = Handle;
+
+        /// A handle to a `network` resource.
+        pub type Network = Handle;
+
+        /// A duration of time, in nanoseconds.
+        pub type Duration = u64;
+
+        /// Discriminant of an `ip-socket-address`, i.e., `ipv4` or `ipv6`.
+        pub type IpSocketAddressTag = u8;
+
+        /// Flattened fields of an `ip-socket-address`, joined from the cases of `ipv4` and `ipv6`.
+        pub type IpSocketAddressField = u32;
+
+        /// Whether to shut down the receiving side, the sending side, or both.
+        pub type ShutdownType = u8;
+
+        // An `ip-socket-address` is flattened into its tag and 11 fields, i.e.,
+        // the `port`, `flow-info`, `address` and `scope-id` of an `ipv6-socket-address`,
+        // in which the `port` and `address` of an `ipv4-socket-address` are also placed.
+        declare_interface!(
+            "wasi:sockets/tcp@0.2.0";
+            /// Bind the socket to a specific network on the provided IP address and port.
+            tcp_socket_start_bind = "[method]tcp-socket.start-bind"(
+                self: TcpSocket,
+                network: Network,
+                local_address_tag: IpSocketAddressTag,
+                local_address_0: IpSocketAddressField,
+                local_address_1: IpSocketAddressField,
+                local_address_2: IpSocketAddressField,
+                local_address_3: IpSocketAddressField,
+                local_address_4: IpSocketAddressField,
+                local_address_5: IpSocketAddressField,
+                local_address_6: IpSocketAddressField,
+                local_address_7: IpSocketAddressField,
+                local_address_8: IpSocketAddressField,
+                local_address_9: IpSocketAddressField,
+                local_address_10: IpSocketAddressField,
+                ret_ptr: Waddr,
+            );
+            /// Finish binding the socket.
+            tcp_socket_finish_bind = "[method]tcp-socket.finish-bind"(self: TcpSocket, ret_ptr: Waddr);
+            /// Connect to a remote endpoint.
+            tcp_socket_start_connect = "[method]tcp-socket.start-connect"(
+                self: TcpSocket,
+                network: Network,
+                remote_address_tag: IpSocketAddressTag,
+                remote_address_0: IpSocketAddressField,
+                remote_address_1: IpSocketAddressField,
+                remote_address_2: IpSocketAddressField,
+                remote_address_3: IpSocketAddressField,
+                remote_address_4: IpSocketAddressField,
+                remote_address_5: IpSocketAddressField,
+                remote_address_6: IpSocketAddressField,
+                remote_address_7: IpSocketAddressField,
+                remote_address_8: IpSocketAddressField,
+                remote_address_9: IpSocketAddressField,
+                remote_address_10: IpSocketAddressField,
+                ret_ptr: Waddr,
+            );
+            /// Finish connecting, returning the streams of the connection.
+            tcp_socket_finish_connect = "[method]tcp-socket.finish-connect"(
+                self: TcpSocket,
+                ret_ptr: Waddr,
+            );
+            /// Start listening for new connections.
+            tcp_socket_start_listen = "[method]tcp-socket.start-listen"(
+                self: TcpSocket,
+                ret_ptr: Waddr,
+            );
+            /// Finish transitioning the socket to the listening state.
+            tcp_socket_finish_listen = "[method]tcp-socket.finish-listen"(
+                self: TcpSocket,
+                ret_ptr: Waddr,
+            );
+            /// Accept a new client socket.
+            tcp_socket_accept = "[method]tcp-socket.accept"(self: TcpSocket, ret_ptr: Waddr);
+            /// Get the bound local address.
+            tcp_socket_local_address = "[method]tcp-socket.local-address"(
+                self: TcpSocket,
+                ret_ptr: Waddr,
+            );
+            /// Get the remote address.
+            tcp_socket_remote_address = "[method]tcp-socket.remote-address"(
+                self: TcpSocket,
+                ret_ptr: Waddr,
+            );
+            /// Whether the socket is in the `listening` state.
+            tcp_socket_is_listening = "[method]tcp-socket.is-listening"(self: TcpSocket);
+            /// Whether this is a IPv4 or IPv6 socket.
+            tcp_socket_address_family = "[method]tcp-socket.address-family"(self: TcpSocket);
+            /// Hints the desired listen queue size.
+            tcp_socket_set_listen_backlog_size = "[method]tcp-socket.set-listen-backlog-size"(
+                self: TcpSocket,
+                value: u64,
+                ret_ptr: Waddr,
+            );
+            /// Enables or disables keepalive.
+            tcp_socket_keep_alive_enabled = "[method]tcp-socket.keep-alive-enabled"(
+                self: TcpSocket,
+                ret_ptr: Waddr,
+            );
+            tcp_socket_set_keep_alive_enabled = "[method]tcp-socket.set-keep-alive-enabled"(
+                self: TcpSocket,
+                value: bool,
+                ret_ptr: Waddr,
+            );
+            /// Amount of time the connection has to be idle before TCP starts sending keepalive packets.
+            tcp_socket_keep_alive_idle_time = "[method]tcp-socket.keep-alive-idle-time"(
+                self: TcpSocket,
+                ret_ptr: Waddr,
+            );
+            tcp_socket_set_keep_alive_idle_time = "[method]tcp-socket.set-keep-alive-idle-time"(
+                self: TcpSocket,
+                value: Duration,
+                ret_ptr: Waddr,
+            );
+            /// The time between keepalive packets.
+            tcp_socket_keep_alive_interval = "[method]tcp-socket.keep-alive-interval"(
+                self: TcpSocket,
+                ret_ptr: Waddr,
+            );
+            tcp_socket_set_keep_alive_interval = "[method]tcp-socket.set-keep-alive-interval"(
+                self: TcpSocket,
+                value: Duration,
+                ret_ptr: Waddr,
+            );
+            /// The maximum amount of keepalive packets TCP should send before aborting the connection.
+            tcp_socket_keep_alive_count = "[method]tcp-socket.keep-alive-count"(
+                self: TcpSocket,
+                ret_ptr: Waddr,
+            );
+            tcp_socket_set_keep_alive_count = "[method]tcp-socket.set-keep-alive-count"(
+                self: TcpSocket,
+                value: u32,
+                ret_ptr: Waddr,
+            );
+            /// Equivalent to the IP_TTL & IPV6_UNICAST_HOPS socket options.
+            tcp_socket_hop_limit = "[method]tcp-socket.hop-limit"(self: TcpSocket, ret_ptr: Waddr);
+            tcp_socket_set_hop_limit = "[method]tcp-socket.set-hop-limit"(
+                self: TcpSocket,
+                value: u8,
+                ret_ptr: Waddr,
+            );
+            /// The kernel buffer space reserved for receives on this socket.
+            tcp_socket_receive_buffer_size = "[method]tcp-socket.receive-buffer-size"(
+                self: TcpSocket,
+                ret_ptr: Waddr,
+            );
+            tcp_socket_set_receive_buffer_size = "[method]tcp-socket.set-receive-buffer-size"(
+                self: TcpSocket,
+                value: u64,
+                ret_ptr: Waddr,
+            );
+            /// The kernel buffer space reserved for sends on this socket.
+            tcp_socket_send_buffer_size = "[method]tcp-socket.send-buffer-size"(
+                self: TcpSocket,
+                ret_ptr: Waddr,
+            );
+            tcp_socket_set_send_buffer_size = "[method]tcp-socket.set-send-buffer-size"(
+                self: TcpSocket,
+                value: u64,
+                ret_ptr: Waddr,
+            );
+            /// Create a `pollable` which can be used to poll for, or block on,
+            /// completion of any of the asynchronous operations of this socket.
+            tcp_socket_subscribe = "[method]tcp-socket.subscribe"(self: TcpSocket);
+            /// Initiate a graceful shutdown.
+            tcp_socket_shutdown = "[method]tcp-socket.shutdown"(
+                self: TcpSocket,
+                shutdown_type: ShutdownType,
+                ret_ptr: Waddr,
+            );
+            /// Drop a `tcp-socket`.
+            drop_tcp_socket = "[resource-drop]tcp-socket"(self: TcpSocket);
+        );
+    }
+}
+
+/// Interfaces of WASI 0.2, with the names of their functions.
+pub const INTERFACES: [(&str, &[&str]); 9] = [
+    (cli::environment::INTERFACE, &cli::environment::FUNC_NAMES),
+    (
+        clocks::monotonic_clock::INTERFACE,
+        &clocks::monotonic_clock::FUNC_NAMES,
+    ),
+    (
+        clocks::wall_clock::INTERFACE,
+        &clocks::wall_clock::FUNC_NAMES,
+    ),
+    (filesystem::types::INTERFACE, &filesystem::types::FUNC_NAMES),
+    (io::streams::INTERFACE, &io::streams::FUNC_NAMES),
+    (random::random::INTERFACE, &random::random::FUNC_NAMES),
+    (random::insecure::INTERFACE, &random::insecure::FUNC_NAMES),
+    (
+        random::insecure_seed::INTERFACE,
+        &random::insecure_seed::FUNC_NAMES,
+    ),
+    (sockets::tcp::INTERFACE, &sockets::tcp::FUNC_NAMES),
+];
+
+/// Type-erased descriptors of the functions of all the [`INTERFACES`].
+pub const DESCRIPTORS: [ErasedDescriptor<'static>; 91] = crate::concat_consts!(
+    cli::environment::DESCRIPTORS,
+    clocks::monotonic_clock::DESCRIPTORS,
+    clocks::wall_clock::DESCRIPTORS,
+    filesystem::types::DESCRIPTORS,
+    io::streams::DESCRIPTORS,
+    random::random::DESCRIPTORS,
+    random::insecure::DESCRIPTORS,
+    random::insecure_seed::DESCRIPTORS,
+    sockets::tcp::DESCRIPTORS,
+);
+
+/// Rust names of the [`DESCRIPTORS`], in order.
+const WASI_NAMES: [&str; DESCRIPTORS.len()] = crate::concat_consts!(
+    cli::environment::WASI_NAMES,
+    clocks::monotonic_clock::WASI_NAMES,
+    clocks::wall_clock::WASI_NAMES,
+    filesystem::types::WASI_NAMES,
+    io::streams::WASI_NAMES,
+    random::random::WASI_NAMES,
+    random::insecure::WASI_NAMES,
+    random::insecure_seed::WASI_NAMES,
+    sockets::tcp::WASI_NAMES,
+);
+
+/// Returns the known interface of `interface`, ignoring its `0.2.x` version.
+fn known_interface(interface: &str) -> Option<&'static str> {
+    let (name, version) = interface.split_once('@')?;
+    if !version.starts_with("0.2.") {
+        return None;
+    }
+    INTERFACES.iter().map(|(known, _)| *known).find(|known| {
+        known
+            .split_once('@')
+            .is_some_and(|(known, _)| known == name)
+    })
+}
+
+/// Looks up the descriptor of the function `name` imported from `interface`,
+/// e.g., `("wasi:filesystem/types@0.2.0", "[method]descriptor.open-at")`.
+///
+/// Interfaces of any `0.2.x` version share the descriptors of `0.2.0`.
+pub fn descriptor_by_name(
+    interface: &str,
+    name: &str,
+) -> Option<&'static ErasedDescriptor<'static>> {
+    let interface = known_interface(interface)?;
+    let descriptors: &'static [ErasedDescriptor<'static>] = &DESCRIPTORS;
+    descriptors.iter().find(|desc| desc.is(interface, name))
+}
+
+/// Returns the Rust name of the function `name` imported from `interface`, e.g.,
+/// `descriptor_open_at` for `("wasi:filesystem/types@0.2.0", "[method]descriptor.open-at")`.
+///
+/// Interfaces of any `0.2.x` version share the names of `0.2.0`.
+pub fn wasi_name_of(interface: &str, name: &str) -> Option<&'static str> {
+    let interface = known_interface(interface)?;
+    DESCRIPTORS
+        .iter()
+        .position(|desc| desc.is(interface, name))
+        .map(|index| WASI_NAMES[index])
+}
+
+/// Returns the names of the functions of `interface`, e.g., `wasi:filesystem/types@0.2.0`.
+///
+/// Interfaces of any `0.2.x` version share the functions of `0.2.0`.
+pub fn funcs_of(interface: &str) -> Option<&'static [&'static str]> {
+    let interface = known_interface(interface)?;
+    INTERFACES
+        .iter()
+        .find(|(known, _)| *known == interface)
+        .map(|(_, funcs)| *funcs)
+}
+
+/// Whether `func` is a function imported from `interface`.
+pub fn has_func(interface: &str, func: &str) -> bool {
+    funcs_of(interface).is_some_and(|funcs| funcs.contains(&func))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names() {
+        use filesystem::types::*;
+        assert_eq!(INTERFACE, "wasi:filesystem/types@0.2.0");
+        assert_eq!(
+            descriptor_read_via_stream.name,
+            "[method]descriptor.read-via-stream"
+        );
+        assert_eq!(drop_descriptor.name, "[resource-drop]descriptor");
+        assert_eq!(FUNC_NAMES.len(), 31);
+        assert_eq!(FUNC_NAMES[0], descriptor_read_via_stream.name);
+        assert_eq!(WASI_NAMES[0], "descriptor_read_via_stream");
+        assert_eq!(WASI_NAMES.len(), FUNC_NAMES.len());
+    }
+
+    #[test]
+    fn lowered_args() {
+        use filesystem::types::*;
+        let args = descriptor_read_via_stream.args;
+        assert_eq!(args.map(|arg| arg.name), ["self", "offset", "ret_ptr"]);
+        assert_eq!(args.map(|arg| arg.size), [4, 8, 4]);
+        assert_eq!(descriptor_open_at.args.len(), 7);
+        assert_eq!(sockets::tcp::tcp_socket_start_bind.args.len(), 15);
+        assert!(sockets::tcp::tcp_socket_start_bind.args_are_distinct());
+        assert_eq!(clocks::monotonic_clock::now.args.len(), 0);
+
+        let _: descriptor_set_size_params_t = (3u32, 1024u64, 0u32);
+    }
+
+    #[test]
+    fn descriptors() {
+        let names: usize = INTERFACES.iter().map(|(_, funcs)| funcs.len()).sum();
+        assert_eq!(DESCRIPTORS.len(), names);
+        for (interface, funcs) in INTERFACES {
+            for func in funcs {
+                let desc = descriptor_by_name(interface, func).unwrap();
+                assert_eq!(desc.module, interface);
+                assert_eq!(desc.name, *func);
+            }
+        }
+        let desc = descriptor_by_name("wasi:io/streams@0.2.1", "[method]output-stream.write");
+        assert_eq!(desc.unwrap().args, io::streams::output_stream_write.args);
+        assert!(descriptor_by_name("wasi:io/streams@0.2.0", "fd_write").is_none());
+        assert_eq!(
+            wasi_name_of("wasi:filesystem/types@0.2.2", "[method]descriptor.open-at"),
+            Some("descriptor_open_at")
+        );
+        assert_eq!(
+            wasi_name_of("wasi:clocks/wall-clock@0.2.0", "now"),
+            Some("now")
+        );
+        assert!(wasi_name_of("wasi:io/streams@0.2.0", "fd_write").is_none());
+    }
+
+    #[test]
+    fn lookup() {
+        assert!(has_func(
+            "wasi:filesystem/types@0.2.0",
+            "[method]descriptor.open-at"
+        ));
+        assert!(has_func(
+            "wasi:filesystem/types@0.2.3",
+            "[method]descriptor.open-at"
+        ));
+        assert!(has_func("wasi:cli/environment@0.2.0", "get-arguments"));
+        assert!(has_func(
+            "wasi:sockets/tcp@0.2.0",
+            "[resource-drop]tcp-socket"
+        ));
+        assert!(!has_func(
+            "wasi:filesystem/types@0.3.0",
+            "[method]descriptor.open-at"
+        ));
+        assert!(!has_func(
+            "wasi:filesystem/types",
+            "[method]descriptor.open-at"
+        ));
+        assert!(!has_func("wasi:io/streams@0.2.0", "fd_write"));
+        assert_eq!(
+            funcs_of("wasi:clocks/wall-clock@0.2.1"),
+            Some(&["now", "resolution"][..])
+        );
+    }
+}
@@ -0,0 +1,41 @@
+//! Descriptors for wasi-threads, whose only ABI `thread-spawn` is imported from `wasi`.
+
+use wasi_descriptor::{erase_desc, ErasedDescriptor};
+
+use crate::p1::Waddr;
+
+/// Name of the module which the ABIs are imported from.
+pub const MODULE: &str = "wasi";
+
+crate::declare_wasi_abis!(
+    /// Spawns a thread running the exported `wasi_thread_start(tid, start_arg)`.
+    ///
+    /// Returns the positive id of the thread, or a negative value on failure.
+    thread_spawn = "thread-spawn"(start_arg: Waddr);
+);
+
+/// Rust names of the ABIs in [`MODULE`], e.g., `thread_spawn` for `thread-spawn`.
+pub const WASI_NAMES: [&str; 1] = ["thread_spawn"];
+
+/// Type-erased descriptors of the ABIs in [`MODULE`].
+pub const DESCRIPTORS: [ErasedDescriptor<'static>; 1] = [erase_desc!(MODULE, thread_spawn)];
+
+/// Looks up the descriptor of the ABI `name` imported from `module`.
+pub fn descriptor_by_name(module: &str, name: &str) -> Option<&'static ErasedDescriptor<'static>> {
+    let descriptors: &'static [ErasedDescriptor<'static>] = &DESCRIPTORS;
+    descriptors.iter().find(|desc| desc.is(module, name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn descriptors() {
+        assert_eq!(thread_spawn.name, "thread-spawn");
+        assert_eq!(thread_spawn.args.len(), 1);
+        assert!(descriptor_by_name("wasi", "thread-spawn").is_some());
+        assert!(descriptor_by_name("wasi", "thread_spawn").is_none());
+        let _: thread_spawn_params_t = (0,);
+    }
+}
@@ -0,0 +1,326 @@
+//! Descriptors for WASIX, whose extensions to preview1 are imported from `wasix_32v1`.
+//!
+//! WASIX guests import the preview1 ABIs from `wasi_snapshot_preview1` as usual,
+//! so only the ABIs of `wasix_32v1` are declared here. ABIs taking more arguments
+//! than a guard supports, e.g., `proc_spawn`, are left out.
+
+use wasi_descriptor::{erase_desc, ArgKind, ErasedDescriptor};
+
+use crate::p1::{Fd, Fdflags, Filesize, Riflags, Siflags, Signal, Size, Timestamp, Waddr};
+
+/// Name of the module which the ABIs are imported from.
+pub const MODULE: &str = "wasix_32v1";
+
+/// Identifier of a thread.
+pub type Tid = u32;
+/// Identifier of a process.
+pub type Pid = u32;
+
+/// The [`ArgKind`] of a pointer to a `pointee` without a length argument.
+const fn ptr(pointee: &str) -> ArgKind {
+    ArgKind::Pointer {
+        pointee,
+        len_arg: None,
+    }
+}
+
+/// The [`ArgKind`] of a pointer to where a `pointee` is written as a result.
+const fn out(pointee: &str) -> ArgKind {
+    ArgKind::Out { pointee }
+}
+
+crate::declare_wasi_abis!(
+    /// Duplicates a file descriptor.
+    fd_dup(fd: Fd @ ArgKind::Fd, ret_fd_ptr: Waddr @ out("fd"));
+    /// Creates an event file descriptor.
+    fd_event(initial_val: u64, flags: Fdflags @ Fdflags::KIND, ret_fd_ptr: Waddr @ out("fd"));
+    /// Creates a pair of connected file descriptors.
+    fd_pipe(ro_fd1_ptr: Waddr @ out("fd"), ro_fd2_ptr: Waddr @ out("fd"));
+    /// Reads the current working directory.
+    getcwd(path_ptr: Waddr @ ptr("u8"), path_len_ptr: Waddr @ ptr("size"));
+    /// Changes the current working directory.
+    chdir(
+        path_ptr: Waddr @ ArgKind::Pointer { pointee: "u8", len_arg: Some("path_len") },
+        path_len: Size,
+    );
+    /// Reads the state of the TTY.
+    tty_get(tty_state_ptr: Waddr @ ptr("tty"));
+    /// Updates the state of the TTY.
+    tty_set(tty_state_ptr: Waddr @ ptr("tty"));
+    /// Registers the exported function called back on signals.
+    callback_signal(
+        name_ptr: Waddr @ ArgKind::Pointer { pointee: "u8", len_arg: Some("name_len") },
+        name_len: Size,
+    );
+    /// Spawns a thread.
+    thread_spawn_v2(start_ptr: Waddr @ ptr("thread_start"), ret_tid_ptr: Waddr @ out("tid"));
+    /// Puts the current thread to sleep.
+    thread_sleep(duration: Timestamp @ ArgKind::Timestamp);
+    /// Reads the identifier of the current thread.
+    thread_id(ret_tid_ptr: Waddr @ out("tid"));
+    /// Waits for a thread to exit.
+    thread_join(tid: Tid);
+    /// Reads the available parallelism.
+    thread_parallelism(ret_size_ptr: Waddr @ out("size"));
+    /// Sends a signal to a thread.
+    thread_signal(tid: Tid, signal: Signal @ Signal::KIND);
+    /// Exits the current thread.
+    thread_exit(rval: u32);
+    /// Waits on a futex.
+    futex_wait(
+        futex_ptr: Waddr @ ptr("u32"),
+        expected: u32,
+        timeout_ptr: Waddr @ ptr("option_timestamp"),
+        ret_woken_ptr: Waddr @ out("bool"),
+    );
+    /// Wakes a thread waiting on a futex.
+    futex_wake(futex_ptr: Waddr @ ptr("u32"), ret_woken_ptr: Waddr @ out("bool"));
+    /// Wakes all the threads waiting on a futex.
+    futex_wake_all(futex_ptr: Waddr @ ptr("u32"), ret_woken_ptr: Waddr @ out("bool"));
+    /// Takes a snapshot of the stack.
+    stack_checkpoint(
+        snapshot_ptr: Waddr @ ptr("stack_snapshot"),
+        ret_val_ptr: Waddr @ out("longsize"),
+    );
+    /// Restores the stack from a snapshot.
+    stack_restore(snapshot_ptr: Waddr @ ptr("stack_snapshot"), val: u64);
+    /// Raises a signal repeatedly.
+    proc_raise_interval(sig: Signal @ Signal::KIND, interval: Timestamp @ ArgKind::Timestamp, repeat: bool);
+    /// Forks the current process.
+    proc_fork(copy_memory: bool, ret_pid_ptr: Waddr @ out("pid"));
+    /// Replaces the current process with a new one.
+    proc_exec(
+        name_ptr: Waddr @ ArgKind::Pointer { pointee: "u8", len_arg: Some("name_len") },
+        name_len: Size,
+        args_ptr: Waddr @ ArgKind::Pointer { pointee: "u8", len_arg: Some("args_len") },
+        args_len: Size,
+    );
+    /// Reads the identifier of the current process.
+    proc_id(ret_pid_ptr: Waddr @ out("pid"));
+    /// Reads the identifier of the parent of a process.
+    proc_parent(pid: Pid, ret_parent_ptr: Waddr @ out("pid"));
+    /// Waits for a child process to exit.
+    proc_join(pid_ptr: Waddr @ ptr("option_pid"), flags: u32, status_ptr: Waddr @ ptr("join_status"));
+    /// Sends a signal to a process.
+    proc_signal(pid: Pid, sig: Signal @ Signal::KIND);
+    /// Reads the status of a socket.
+    sock_status(fd: Fd @ ArgKind::Fd, ret_status_ptr: Waddr @ out("sock_status"));
+    /// Reads the local address of a socket.
+    sock_addr_local(fd: Fd @ ArgKind::Fd, ret_addr_ptr: Waddr @ out("addr_port"));
+    /// Reads the remote address of a socket.
+    sock_addr_peer(fd: Fd @ ArgKind::Fd, ro_addr_ptr: Waddr @ out("addr_port"));
+    /// Creates a socket.
+    sock_open(af: u16, socktype: u8, sock_proto: u16, ro_sock_ptr: Waddr @ out("fd"));
+    /// Sets a boolean option of a socket.
+    sock_set_opt_flag(fd: Fd @ ArgKind::Fd, sockopt: u8, flag: bool);
+    /// Reads a boolean option of a socket.
+    sock_get_opt_flag(fd: Fd @ ArgKind::Fd, sockopt: u8, ret_flag_ptr: Waddr @ out("bool"));
+    /// Sets a time option of a socket.
+    sock_set_opt_time(fd: Fd @ ArgKind::Fd, sockopt: u8, time_ptr: Waddr @ ptr("option_timestamp"));
+    /// Reads a time option of a socket.
+    sock_get_opt_time(fd: Fd @ ArgKind::Fd, sockopt: u8, ret_time_ptr: Waddr @ out("option_timestamp"));
+    /// Sets a size option of a socket.
+    sock_set_opt_size(fd: Fd @ ArgKind::Fd, sockopt: u8, size: Filesize);
+    /// Reads a size option of a socket.
+    sock_get_opt_size(fd: Fd @ ArgKind::Fd, sockopt: u8, ret_size_ptr: Waddr @ out("filesize"));
+    /// Joins an IPv4 multicast group.
+    sock_join_multicast_v4(
+        fd: Fd @ ArgKind::Fd,
+        multiaddr_ptr: Waddr @ ptr("addr_ip4"),
+        iface_ptr: Waddr @ ptr("addr_ip4"),
+    );
+    /// Leaves an IPv4 multicast group.
+    sock_leave_multicast_v4(
+        fd: Fd @ ArgKind::Fd,
+        multiaddr_ptr: Waddr @ ptr("addr_ip4"),
+        iface_ptr: Waddr @ ptr("addr_ip4"),
+    );
+    /// Joins an IPv6 multicast group.
+    sock_join_multicast_v6(fd: Fd @ ArgKind::Fd, multiaddr_ptr: Waddr @ ptr("addr_ip6"), iface: u32);
+    /// Leaves an IPv6 multicast group.
+    sock_leave_multicast_v6(fd: Fd @ ArgKind::Fd, multiaddr_ptr: Waddr @ ptr("addr_ip6"), iface: u32);
+    /// Binds a socket to an address.
+    sock_bind(fd: Fd @ ArgKind::Fd, addr_ptr: Waddr @ ptr("addr_port"));
+    /// Listens for connections on a socket.
+    sock_listen(fd: Fd @ ArgKind::Fd, backlog: Size);
+    /// Accepts a connection on a listening socket.
+    sock_accept_v2(
+        fd: Fd @ ArgKind::Fd,
+        flags: Fdflags @ Fdflags::KIND,
+        ro_fd_ptr: Waddr @ out("fd"),
+        ro_addr_ptr: Waddr @ out("addr_port"),
+    );
+    /// Connects a socket to an address.
+    sock_connect(fd: Fd @ ArgKind::Fd, addr_ptr: Waddr @ ptr("addr_port"));
+    /// Receives a message and its source address from a socket.
+    sock_recv_from(
+        fd: Fd @ ArgKind::Fd,
+        ri_data_ptr: Waddr @ ArgKind::Pointer { pointee: "iovec", len_arg: Some("ri_data_len") },
+        ri_data_len: Size,
+        ri_flags: Riflags @ Riflags::KIND,
+        ro_data_len_ptr: Waddr @ out("size"),
+        ro_flags_ptr: Waddr @ out("roflags"),
+        ro_addr_ptr: Waddr @ out("addr_port"),
+    );
+    /// Sends a message to an address through a socket.
+    sock_send_to(
+        fd: Fd @ ArgKind::Fd,
+        si_data_ptr: Waddr @ ArgKind::Pointer { pointee: "ciovec", len_arg: Some("si_data_len") },
+        si_data_len: Size,
+        si_flags: Siflags,
+        addr_ptr: Waddr @ ptr("addr_port"),
+        ret_data_len_ptr: Waddr @ out("size"),
+    );
+    /// Sends a part of a file through a socket.
+    sock_send_file(
+        out_fd: Fd @ ArgKind::Fd,
+        in_fd: Fd @ ArgKind::Fd,
+        offset: Filesize,
+        count: Filesize,
+        ret_sent_ptr: Waddr @ out("filesize"),
+    );
+    /// Resolves a host name into addresses.
+    resolve(
+        host_ptr: Waddr @ ArgKind::Pointer { pointee: "u8", len_arg: Some("host_len") },
+        host_len: Size,
+        port: u16,
+        addrs_ptr: Waddr @ ArgKind::Pointer { pointee: "addr", len_arg: Some("naddrs") },
+        naddrs: Size,
+        ret_naddrs_ptr: Waddr @ out("size"),
+    );
+);
+
+/// Names of the ABIs in [`MODULE`].
+pub const WASI_NAMES: [&str; 49] = [
+    "fd_dup",
+    "fd_event",
+    "fd_pipe",
+    "getcwd",
+    "chdir",
+    "tty_get",
+    "tty_set",
+    "callback_signal",
+    "thread_spawn_v2",
+    "thread_sleep",
+    "thread_id",
+    "thread_join",
+    "thread_parallelism",
+    "thread_signal",
+    "thread_exit",
+    "futex_wait",
+    "futex_wake",
+    "futex_wake_all",
+    "stack_checkpoint",
+    "stack_restore",
+    "proc_raise_interval",
+    "proc_fork",
+    "proc_exec",
+    "proc_id",
+    "proc_parent",
+    "proc_join",
+    "proc_signal",
+    "sock_status",
+    "sock_addr_local",
+    "sock_addr_peer",
+    "sock_open",
+    "sock_set_opt_flag",
+    "sock_get_opt_flag",
+    "sock_set_opt_time",
+    "sock_get_opt_time",
+    "sock_set_opt_size",
+    "sock_get_opt_size",
+    "sock_join_multicast_v4",
+    "sock_leave_multicast_v4",
+    "sock_join_multicast_v6",
+    "sock_leave_multicast_v6",
+    "sock_bind",
+    "sock_listen",
+    "sock_accept_v2",
+    "sock_connect",
+    "sock_recv_from",
+    "sock_send_to",
+    "sock_send_file",
+    "resolve",
+];
+
+/// Type-erased descriptors of the ABIs in [`MODULE`].
+pub const DESCRIPTORS: [ErasedDescriptor<'static>; WASI_NAMES.len()] = [
+    erase_desc!(MODULE, fd_dup),
+    erase_desc!(MODULE, fd_event),
+    erase_desc!(MODULE, fd_pipe),
+    erase_desc!(MODULE, getcwd),
+    erase_desc!(MODULE, chdir),
+    erase_desc!(MODULE, tty_get),
+    erase_desc!(MODULE, tty_set),
+    erase_desc!(MODULE, callback_signal),
+    erase_desc!(MODULE, thread_spawn_v2),
+    erase_desc!(MODULE, thread_sleep),
+    erase_desc!(MODULE, thread_id),
+    erase_desc!(MODULE, thread_join),
+    erase_desc!(MODULE, thread_parallelism),
+    erase_desc!(MODULE, thread_signal),
+    erase_desc!(MODULE, thread_exit),
+    erase_desc!(MODULE, futex_wait),
+    erase_desc!(MODULE, futex_wake),
+    erase_desc!(MODULE, futex_wake_all),
+    erase_desc!(MODULE, stack_checkpoint),
+    erase_desc!(MODULE, stack_restore),
+    erase_desc!(MODULE, proc_raise_interval),
+    erase_desc!(MODULE, proc_fork),
+    erase_desc!(MODULE, proc_exec),
+    erase_desc!(MODULE, proc_id),
+    erase_desc!(MODULE, proc_parent),
+    erase_desc!(MODULE, proc_join),
+    erase_desc!(MODULE, proc_signal),
+    erase_desc!(MODULE, sock_status),
+    erase_desc!(MODULE, sock_addr_local),
+    erase_desc!(MODULE, sock_addr_peer),
+    erase_desc!(MODULE, sock_open),
+    erase_desc!(MODULE, sock_set_opt_flag),
+    erase_desc!(MODULE, sock_get_opt_flag),
+    erase_desc!(MODULE, sock_set_opt_time),
+    erase_desc!(MODULE, sock_get_opt_time),
+    erase_desc!(MODULE, sock_set_opt_size),
+    erase_desc!(MODULE, sock_get_opt_size),
+    erase_desc!(MODULE, sock_join_multicast_v4),
+    erase_desc!(MODULE, sock_leave_multicast_v4),
+    erase_desc!(MODULE, sock_join_multicast_v6),
+    erase_desc!(MODULE, sock_leave_multicast_v6),
+    erase_desc!(MODULE, sock_bind),
+    erase_desc!(MODULE, sock_listen),
+    erase_desc!(MODULE, sock_accept_v2),
+    erase_desc!(MODULE, sock_connect),
+    erase_desc!(MODULE, sock_recv_from),
+    erase_desc!(MODULE, sock_send_to),
+    erase_desc!(MODULE, sock_send_file),
+    erase_desc!(MODULE, resolve),
+];
+
+/// Looks up the descriptor of the ABI `name` imported from `module`.
+pub fn descriptor_by_name(module: &str, name: &str) -> Option<&'static ErasedDescriptor<'static>> {
+    let descriptors: &'static [ErasedDescriptor<'static>] = &DESCRIPTORS;
+    descriptors.iter().find(|desc| desc.is(module, name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn descriptors() {
+        for (name, desc) in WASI_NAMES.iter().zip(&DESCRIPTORS) {
+            assert!(desc.is(MODULE, name));
+        }
+        assert!(descriptor_by_name("wasix_32v1", "proc_fork").is_some());
+        assert!(descriptor_by_name("wasi_snapshot_preview1", "proc_fork").is_none());
+        assert_eq!(fd_pipe.args.len(), 2);
+        assert_eq!(thread_signal.arg("signal").unwrap().kind, Signal::KIND);
+        assert_eq!(futex_wait.arg_index("expected"), Some(1));
+    }
+
+    #[test]
+    fn param_types() {
+        let _: proc_fork_params_t = (true, 0);
+        let _: sock_send_file_params_t = (1, 3, 0u64, 4096u64, 0);
+    }
+}
@@ -0,0 +1,251 @@
+//! Descriptors for the socket extensions of WasmEdge, which are imported from
+//! `wasi_snapshot_preview1` along with the standard ABIs.
+//!
+//! [`v1`] replaces the standard `sock_accept` with its own `(fd, fd_ptr)` shape,
+//! while [`v2`] keeps the standard one and adds `_v2` ABIs taking socket addresses
+//! with ports. As both shapes of `sock_accept` share the module and the name, tell
+//! them apart by signature with [`crate::descriptor_by_signature`].
+
+use wasi_descriptor::ArgKind;
+
+/// Name of the module which the ABIs are imported from.
+pub const MODULE: &str = crate::p1::MODULE;
+
+/// The [`ArgKind`] of a pointer to a `pointee` without a length argument.
+const fn ptr(pointee: &str) -> ArgKind {
+    ArgKind::Pointer {
+        pointee,
+        len_arg: None,
+    }
+}
+
+/// The [`ArgKind`] of a pointer to where a `pointee` is written as a result.
+const fn out(pointee: &str) -> ArgKind {
+    ArgKind::Out { pointee }
+}
+
+/// ABIs shared by all the versions.
+mod common {
+    use wasi_descriptor::{erase_desc, ArgKind, ErasedDescriptor};
+
+    use super::{out, ptr, MODULE};
+    use crate::p1::{Fd, Riflags, Siflags, Size, Waddr};
+
+    crate::declare_wasi_abis!(
+        sock_listen(fd: Fd @ ArgKind::Fd, backlog: Size);
+        sock_bind(fd: Fd @ ArgKind::Fd, addr_buf_ptr: Waddr @ ptr("address"), port_num: u32);
+        sock_connect(fd: Fd @ ArgKind::Fd, addr_ptr: Waddr @ ptr("address"), port_num: u32);
+        sock_open(addr_family: u8, sock_type: u8, fd_ptr: Waddr @ out("fd"));
+        sock_recv_from(
+            fd: Fd @ ArgKind::Fd,
+            ri_data_ptr: Waddr @ ArgKind::Pointer { pointee: "iovec", len_arg: Some("ri_data_len") },
+            ri_data_len: Size,
+            src_addr_ptr: Waddr @ ptr("address"),
+            ri_flags: Riflags @ Riflags::KIND,
+            src_port_ptr: Waddr @ out("u32"),
+            ro_data_len_ptr: Waddr @ out("size"),
+            ro_flags_ptr: Waddr @ out("roflags"),
+        );
+        sock_send_to(
+            fd: Fd @ ArgKind::Fd,
+            si_data_ptr: Waddr @ ArgKind::Pointer { pointee: "ciovec", len_arg: Some("si_data_len") },
+            si_data_len: Size,
+            dst_addr_ptr: Waddr @ ptr("address"),
+            dst_port: u32,
+            si_flags: Siflags,
+            so_data_len_ptr: Waddr @ out("size"),
+        );
+        sock_getpeeraddr(
+            fd: Fd @ ArgKind::Fd,
+            peeraddr_ptr: Waddr @ ptr("address"),
+            peeraddr_type_ptr: Waddr @ out("u32"),
+            peerport_ptr: Waddr @ out("u32"),
+        );
+        sock_getlocaladdr(
+            fd: Fd @ ArgKind::Fd,
+            localaddr_ptr: Waddr @ ptr("address"),
+            localaddr_type_ptr: Waddr @ out("u32"),
+            localport_ptr: Waddr @ out("u32"),
+        );
+        sock_getsockopt(
+            fd: Fd @ ArgKind::Fd,
+            level,
+            name,
+            flag_ptr: Waddr @ out("i32"),
+            flag_size_ptr: Waddr @ out("size"),
+        );
+        sock_setsockopt(
+            fd: Fd @ ArgKind::Fd,
+            level,
+            name,
+            flag_ptr: Waddr @ ArgKind::Pointer { pointee: "i32", len_arg: Some("flag_size") },
+            flag_size: Size,
+        );
+        /// Resolves the host `node` and the service `server` into at most
+        /// `max_res_len` `addrinfo`s.
+        sock_getaddrinfo(
+            node_ptr: Waddr @ ArgKind::Pointer { pointee: "u8", len_arg: Some("node_len") },
+            node_len: Size,
+            server_ptr: Waddr @ ArgKind::Pointer { pointee: "u8", len_arg: Some("server_len") },
+            server_len: Size,
+            hint_ptr: Waddr @ ptr("addrinfo"),
+            res_ptr: Waddr @ ptr("*addrinfo"),
+            max_res_len: Size,
+            res_len_ptr: Waddr @ out("size"),
+        );
+    );
+
+    /// Names of the ABIs shared by all the versions.
+    pub const WASI_NAMES: [&str; 11] = [
+        "sock_listen",
+        "sock_bind",
+        "sock_connect",
+        "sock_open",
+        "sock_recv_from",
+        "sock_send_to",
+        "sock_getpeeraddr",
+        "sock_getlocaladdr",
+        "sock_getsockopt",
+        "sock_setsockopt",
+        "sock_getaddrinfo",
+    ];
+
+    /// Descriptors of the ABIs shared by all the versions.
+    pub const DESCRIPTORS: [ErasedDescriptor<'static>; 11] = [
+        erase_desc!(MODULE, sock_listen),
+        erase_desc!(MODULE, sock_bind),
+        erase_desc!(MODULE, sock_connect),
+        erase_desc!(MODULE, sock_open),
+        erase_desc!(MODULE, sock_recv_from),
+        erase_desc!(MODULE, sock_send_to),
+        erase_desc!(MODULE, sock_getpeeraddr),
+        erase_desc!(MODULE, sock_getlocaladdr),
+        erase_desc!(MODULE, sock_getsockopt),
+        erase_desc!(MODULE, sock_setsockopt),
+        erase_desc!(MODULE, sock_getaddrinfo),
+    ];
+}
+
+/// The first version of the WasmEdge sockets, with its own `sock_accept`.
+#[cfg(feature = "wasmedge-sock-v1")]
+pub mod v1 {
+    use wasi_descriptor::{erase_desc, ArgKind, ErasedDescriptor};
+
+    use super::{common, out};
+    pub use super::{common::*, MODULE};
+    use crate::p1::{Fd, Waddr};
+
+    crate::declare_wasi_abis!(
+        /// Accepts a connection without the `flags` of the standard `sock_accept`.
+        sock_accept(fd: Fd @ ArgKind::Fd, accepted_fd_ptr: Waddr @ out("fd"));
+    );
+
+    /// Names of the ABIs.
+    pub const WASI_NAMES: [&str; common::WASI_NAMES.len() + 1] =
+        crate::concat(common::WASI_NAMES, ["sock_accept"]);
+
+    /// Type-erased descriptors of the ABIs.
+    pub const DESCRIPTORS: [ErasedDescriptor<'static>; common::DESCRIPTORS.len() + 1] =
+        crate::concat(common::DESCRIPTORS, [erase_desc!(MODULE, sock_accept)]);
+
+    /// Looks up the descriptor of the ABI `name` imported from `module`.
+    pub fn descriptor_by_name(
+        module: &str,
+        name: &str,
+    ) -> Option<&'static ErasedDescriptor<'static>> {
+        let descriptors: &'static [ErasedDescriptor<'static>] = &DESCRIPTORS;
+        descriptors.iter().find(|desc| desc.is(module, name))
+    }
+}
+
+/// The second version of the WasmEdge sockets, with the standard `sock_accept`.
+#[cfg(feature = "wasmedge-sock-v2")]
+pub mod v2 {
+    use wasi_descriptor::{erase_desc, ArgKind, ErasedDescriptor};
+
+    use super::{common, out, ptr};
+    pub use super::{common::*, MODULE};
+    use crate::p1::{Fd, Riflags, Siflags, Size, Waddr};
+
+    crate::declare_wasi_abis!(
+        /// Receives a message and its source `sockaddr`, including the port.
+        sock_recv_from_v2(
+            fd: Fd @ ArgKind::Fd,
+            ri_data_ptr: Waddr @ ArgKind::Pointer { pointee: "iovec", len_arg: Some("ri_data_len") },
+            ri_data_len: Size,
+            src_addr_ptr: Waddr @ ptr("sockaddr"),
+            ri_flags: Riflags @ Riflags::KIND,
+            ro_data_len_ptr: Waddr @ out("size"),
+            ro_flags_ptr: Waddr @ out("roflags"),
+        );
+        /// Sends a message to a `sockaddr`, including the port.
+        sock_send_to_v2(
+            fd: Fd @ ArgKind::Fd,
+            si_data_ptr: Waddr @ ArgKind::Pointer { pointee: "ciovec", len_arg: Some("si_data_len") },
+            si_data_len: Size,
+            dst_addr_ptr: Waddr @ ptr("sockaddr"),
+            si_flags: Siflags,
+            so_data_len_ptr: Waddr @ out("size"),
+        );
+    );
+
+    /// Names of the ABIs.
+    pub const WASI_NAMES: [&str; common::WASI_NAMES.len() + 2] =
+        crate::concat(common::WASI_NAMES, ["sock_recv_from_v2", "sock_send_to_v2"]);
+
+    /// Type-erased descriptors of the ABIs.
+    pub const DESCRIPTORS: [ErasedDescriptor<'static>; common::DESCRIPTORS.len() + 2] =
+        crate::concat(
+            common::DESCRIPTORS,
+            [
+                erase_desc!(MODULE, sock_recv_from_v2),
+                erase_desc!(MODULE, sock_send_to_v2),
+            ],
+        );
+
+    /// Looks up the descriptor of the ABI `name` imported from `module`.
+    pub fn descriptor_by_name(
+        module: &str,
+        name: &str,
+    ) -> Option<&'static ErasedDescriptor<'static>> {
+        let descriptors: &'static [ErasedDescriptor<'static>] = &DESCRIPTORS;
+        descriptors.iter().find(|desc| desc.is(module, name))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use wasi_descriptor::ValType::I32;
+
+    use super::*;
+
+    #[test]
+    fn getaddrinfo() {
+        assert_eq!(common::sock_getaddrinfo.args.len(), 8);
+        assert_eq!(
+            common::sock_getaddrinfo.arg("node_ptr").unwrap().kind,
+            ArgKind::Pointer {
+                pointee: "u8",
+                len_arg: Some("node_len")
+            }
+        );
+    }
+
+    #[cfg(feature = "wasmedge-sock-v1")]
+    #[test]
+    fn v1() {
+        assert_eq!(v1::WASI_NAMES.len(), v1::DESCRIPTORS.len());
+        assert!(v1::WASI_NAMES.contains(&"sock_getaddrinfo"));
+        assert!(v1::sock_accept.val_types() == [I32, I32]);
+        assert!(v1::DESCRIPTORS[11].is(crate::p1::MODULE, "sock_accept"));
+        assert!(v1::DESCRIPTORS[11].has_signature(&[I32, I32]));
+    }
+
+    #[cfg(feature = "wasmedge-sock-v2")]
+    #[test]
+    fn v2() {
+        assert_eq!(v2::WASI_NAMES.len(), v2::DESCRIPTORS.len());
+        assert!(!v2::WASI_NAMES.contains(&"sock_accept"));
+        assert_eq!(v2::sock_send_to_v2.val_types(), [I32; 6]);
+    }
+}
@@ -0,0 +1,289 @@
+//! Typed witx `enum`s and `flags`.
+//!
+//! Both are declared as transparent newtypes of their representations rather than
+//! Rust enums, so that any value passed by a guest, valid or not, can be held and
+//! inspected by a bound.
+
+/// Declares a witx `enum`, e.g., `Whence(u8)`, whose variants are associated constants.
+///
+/// A variant whose witx name is not a Rust identifier, e.g., `2big`, is declared with
+/// an explicit name: `_2BIG = 1 => "2BIG"`.
+#[macro_export]
+macro_rules! declare_witx_enum {
+    (
+        $(#[$attr:meta])*
+        $name:ident($repr:ty) {
+            $( $(#[$vattr:meta])* $variant:ident = $value:expr => $vname:literal ),* $(,)?
+        }
+    ) => {
+        $(#[$attr])*
+        #[repr(transparent)]
+        #[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+        pub struct $name($repr);
+
+        impl $name {
+            $(
+                $(#[$vattr])*
+                pub const $variant: $name = $name($value);
+            )*
+            /// Names and values of the variants.
+            pub const NAMES: [(&'static str, $name); [$($vname),*].len()] = [$(($vname, $name::$variant)),*];
+            /// The [`ArgKind`](wasi_descriptor::ArgKind) of arguments of this type.
+            pub const KIND: wasi_descriptor::ArgKind<'static> = wasi_descriptor::ArgKind::Enum(&[$($vname),*]);
+
+            pub const fn from_raw(raw: $repr) -> Self {
+                Self(raw)
+            }
+            pub const fn raw(&self) -> $repr {
+                self.0
+            }
+            /// The name of the variant, or `None` if the value is out of range.
+            pub const fn name(&self) -> Option<&'static str> {
+                let mut i = 0;
+                while i < Self::NAMES.len() {
+                    if Self::NAMES[i].1 .0 == self.0 {
+                        return Some(Self::NAMES[i].0);
+                    }
+                    i += 1;
+                }
+                None
+            }
+            /// Looks up a variant by its witx name, e.g., `"PERM"`.
+            pub fn from_name(name: &str) -> Option<Self> {
+                Self::NAMES
+                    .iter()
+                    .find(|(variant, _)| variant.eq_ignore_ascii_case(name))
+                    .map(|(_, value)| *value)
+            }
+        }
+
+        impl core::fmt::Debug for $name {
+            fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                match self.name() {
+                    Some(name) => write!(f, "{}::{}", stringify!($name), name),
+                    None => write!(f, "{}({})", stringify!($name), self.0),
+                }
+            }
+        }
+        impl From<$repr> for $name {
+            fn from(raw: $repr) -> Self {
+                Self(raw)
+            }
+        }
+        impl From<$name> for $repr {
+            fn from(value: $name) -> Self {
+                value.0
+            }
+        }
+        impl<Raw> wasi_descriptor::FromRawArg<Raw> for $name
+        where
+            $repr: wasi_descriptor::FromRawArg<Raw>,
+        {
+            fn from_raw_arg(raw: Raw) -> Self {
+                Self::from_raw(<$repr as wasi_descriptor::FromRawArg<Raw>>::from_raw_arg(raw))
+            }
+        }
+        impl wasi_descriptor::AbiArgType for $name {
+            const KIND: wasi_descriptor::ArgKind<'static> = $name::KIND;
+        }
+    };
+}
+
+/// Declares a witx `flags`, e.g., `Oflags(u16)`, whose flags are associated constants.
+#[macro_export]
+macro_rules! declare_witx_flags {
+    (
+        $(#[$attr:meta])*
+        $name:ident($repr:ty) {
+            $( $(#[$fattr:meta])* $flag:ident = $value:expr => $fname:literal ),* $(,)?
+        }
+    ) => {
+        $(#[$attr])*
+        #[repr(transparent)]
+        #[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+        pub struct $name($repr);
+
+        impl $name {
+            $(
+                $(#[$fattr])*
+                pub const $flag: $name = $name($value);
+            )*
+            /// Names and values of the flags.
+            pub const NAMES: [(&'static str, $name); [$($fname),*].len()] = [$(($fname, $name::$flag)),*];
+            /// The [`ArgKind`](wasi_descriptor::ArgKind) of arguments of this type.
+            pub const KIND: wasi_descriptor::ArgKind<'static> = wasi_descriptor::ArgKind::Flags(&[$($fname),*]);
+
+            pub const fn empty() -> Self {
+                Self(0)
+            }
+            pub const fn all() -> Self {
+                Self(0 $(| $value)*)
+            }
+            pub const fn from_raw(raw: $repr) -> Self {
+                Self(raw)
+            }
+            pub const fn raw(&self) -> $repr {
+                self.0
+            }
+            pub const fn is_empty(&self) -> bool {
+                self.0 == 0
+            }
+            /// Whether all the flags in `other` are set.
+            pub const fn contains(&self, other: Self) -> bool {
+                self.0 & other.0 == other.0
+            }
+            /// Whether any of the flags in `other` is set.
+            pub const fn intersects(&self, other: Self) -> bool {
+                self.0 & other.0 != 0
+            }
+            /// Bits set which are not defined by witx.
+            pub const fn unknown_bits(&self) -> $repr {
+                self.0 & !Self::all().0
+            }
+            pub const fn union(self, other: Self) -> Self {
+                Self(self.0 | other.0)
+            }
+            pub const fn difference(self, other: Self) -> Self {
+                Self(self.0 & !other.0)
+            }
+            /// Looks up a flag by its witx name, e.g., `"CREAT"`.
+            pub fn from_name(name: &str) -> Option<Self> {
+                Self::NAMES
+                    .iter()
+                    .find(|(flag, _)| flag.eq_ignore_ascii_case(name))
+                    .map(|(_, value)| *value)
+            }
+            /// Names and values of the flags set.
+            pub fn iter_names(&self) -> impl Iterator<Item = (&'static str, Self)> {
+                let this = *self;
+                Self::NAMES.into_iter().filter(move |(_, flag)| this.contains(*flag))
+            }
+        }
+
+        impl core::fmt::Debug for $name {
+            fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                write!(f, "{}(", stringify!($name))?;
+                let mut first = true;
+                for (name, _) in self.iter_names() {
+                    if !first {
+                        f.write_str(" | ")?;
+                    }
+                    f.write_str(name)?;
+                    first = false;
+                }
+                if self.unknown_bits() != 0 {
+                    if !first {
+                        f.write_str(" | ")?;
+                    }
+                    write!(f, "{:#x}", self.unknown_bits())?;
+                }
+                f.write_str(")")
+            }
+        }
+        impl core::ops::BitOr for $name {
+            type Output = Self;
+            fn bitor(self, rhs: Self) -> Self {
+                self.union(rhs)
+            }
+        }
+        impl core::ops::BitOrAssign for $name {
+            fn bitor_assign(&mut self, rhs: Self) {
+                self.0 |= rhs.0;
+            }
+        }
+        impl core::ops::BitAnd for $name {
+            type Output = Self;
+            fn bitand(self, rhs: Self) -> Self {
+                Self(self.0 & rhs.0)
+            }
+        }
+        impl core::ops::BitAndAssign for $name {
+            fn bitand_assign(&mut self, rhs: Self) {
+                self.0 &= rhs.0;
+            }
+        }
+        impl core::ops::BitXor for $name {
+            type Output = Self;
+            fn bitxor(self, rhs: Self) -> Self {
+                Self(self.0 ^ rhs.0)
+            }
+        }
+        impl core::ops::Sub for $name {
+            type Output = Self;
+            fn sub(self, rhs: Self) -> Self {
+                self.difference(rhs)
+            }
+        }
+        impl core::ops::Not for $name {
+            type Output = Self;
+            fn not(self) -> Self {
+                Self(!self.0 & Self::all().0)
+            }
+        }
+        impl From<$repr> for $name {
+            fn from(raw: $repr) -> Self {
+                Self(raw)
+            }
+        }
+        impl From<$name> for $repr {
+            fn from(value: $name) -> Self {
+                value.0
+            }
+        }
+        impl<Raw> wasi_descriptor::FromRawArg<Raw> for $name
+        where
+            $repr: wasi_descriptor::FromRawArg<Raw>,
+        {
+            fn from_raw_arg(raw: Raw) -> Self {
+                Self::from_raw(<$repr as wasi_descriptor::FromRawArg<Raw>>::from_raw_arg(raw))
+            }
+        }
+        impl wasi_descriptor::AbiArgType for $name {
+            const KIND: wasi_descriptor::ArgKind<'static> = $name::KIND;
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    crate::declare_witx_enum! {
+        Color(u8) {
+            RED = 0 => "RED",
+            _2ND = 1 => "2ND",
+        }
+    }
+    crate::declare_witx_flags! {
+        Mode(u16) {
+            READ = 1 << 0 => "READ",
+            WRITE = 1 << 1 => "WRITE",
+        }
+    }
+
+    #[test]
+    fn witx_enum() {
+        assert_eq!(Color::_2ND.raw(), 1);
+        assert_eq!(Color::_2ND.name(), Some("2ND"));
+        assert_eq!(Color::from_raw(7).name(), None);
+        assert_eq!(Color::from_name("2nd"), Some(Color::_2ND));
+        assert_eq!(Color::from_name("BLUE"), None);
+        assert_eq!(Color::KIND, wasi_descriptor::ArgKind::Enum(&["RED", "2ND"]));
+        use wasi_descriptor::FromRawArg;
+        assert_eq!(Color::from_raw_arg(0x101i32), Color::_2ND);
+    }
+
+    #[test]
+    fn witx_flags() {
+        let rw = Mode::READ | Mode::WRITE;
+        assert_eq!(rw, Mode::all());
+        assert!(rw.contains(Mode::WRITE));
+        assert!(!Mode::READ.contains(rw));
+        assert!(Mode::READ.intersects(rw));
+        assert_eq!(rw - Mode::READ, Mode::WRITE);
+        assert_eq!(!Mode::READ, Mode::WRITE);
+        assert_eq!(Mode::from_raw(0b101).unknown_bits(), 0b100);
+        assert_eq!(Mode::from_name("write"), Some(Mode::WRITE));
+        assert_eq!(rw.iter_names().map(|(name, _)| name).last(), Some("WRITE"));
+        use wasi_descriptor::FromRawArg;
+        assert_eq!(Mode::from_raw_arg(0b11i32), rw);
+    }
+}
@@ -2,34 +2,179 @@
 
 pub type ArgSize = usize;
 pub type DefaultAbiArgType = i32;
+/// The default type of the errno returned by an ABI, as seen after the call.
+pub type DefaultErrnoType = u16;
+/// The default type of the values written behind [`ArgKind::Out`] arguments.
+pub type DefaultOutType = u64;
+
+/// The wasm value type of an argument in the core signature of an ABI.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValType {
+    I32,
+    I64,
+}
+
+/// What an argument means to the callee, beyond its size.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArgKind<'a> {
+    /// An integer, e.g., a size or an exit code.
+    Int { signed: bool },
+    /// An address in the linear memory of the guest.
+    Pointer {
+        /// The type of the pointee(s), e.g., `iovec` or `u8`.
+        pointee: &'a str,
+        /// The name of the argument holding the number of pointees, if any.
+        len_arg: Option<&'a str>,
+    },
+    /// An address in the linear memory of the guest, where the callee writes a result,
+    /// e.g., the number of bytes written by `fd_write`.
+    Out {
+        /// The type of the result, e.g., `size` or `fd`.
+        pointee: &'a str,
+    },
+    /// A file descriptor.
+    Fd,
+    /// A set of flags, where the `i`-th name is of the flag `1 << i`.
+    Flags(&'a [&'a str]),
+    /// An enumeration, where the `i`-th name is of the variant `i`.
+    Enum(&'a [&'a str]),
+    /// A timestamp in nanoseconds.
+    Timestamp,
+}
+
+/// Types of arguments declared without an [`ArgKind`].
+pub trait AbiArgType {
+    /// The [`ArgKind`] of arguments of this type.
+    const KIND: ArgKind<'static>;
+}
+macro_rules! impl_abi_arg_type {
+    ($signed:literal: $($ty:ty),*) => {
+        $(
+            impl AbiArgType for $ty {
+                const KIND: ArgKind<'static> = ArgKind::Int { signed: $signed };
+            }
+        )*
+    };
+}
+impl_abi_arg_type!(true: i8, i16, i32, i64, isize);
+impl_abi_arg_type!(false: u8, u16, u32, u64, usize, bool);
+
+/// An argument of `SIZE` bytes, to tell the type of its raw value by [`RawArg`].
+pub struct ArgOfSize<const SIZE: usize>;
+/// The type of the raw value of an argument, i.e., its wasm value in the core signature.
+pub trait RawArg {
+    type Raw;
+}
+macro_rules! impl_raw_arg {
+    ($raw:ty: $($size:literal),*) => {
+        $(
+            impl RawArg for ArgOfSize<$size> {
+                type Raw = $raw;
+            }
+        )*
+    };
+}
+impl_raw_arg!(i32: 1, 2, 4);
+impl_raw_arg!(i64: 8);
+/// The type of the raw value of an argument of `SIZE` bytes, e.g., `i64` for `offset` of `fd_seek`.
+pub type RawArgType<const SIZE: usize> = <ArgOfSize<SIZE> as RawArg>::Raw;
+
+/// Conversion from the raw value of an argument, e.g., the `i32` or `i64` of the core
+/// signature, or the errno and the out values seen after the call, keeping its bits
+/// like `as` does, where a wider type is zero-extended from the raw width, e.g., the
+/// `i32` `-1` is `0xffff_ffff` as an `u64`.
+pub trait FromRawArg<Raw>: Sized {
+    fn from_raw_arg(raw: Raw) -> Self;
+}
+macro_rules! impl_from_raw_arg {
+    ($raw:ty as $unsigned:ty: $($ty:ty),*) => {
+        $(
+            impl FromRawArg<$raw> for $ty {
+                fn from_raw_arg(raw: $raw) -> Self {
+                    raw as $unsigned as $ty
+                }
+            }
+        )*
+        impl FromRawArg<$raw> for bool {
+            fn from_raw_arg(raw: $raw) -> Self {
+                raw != 0
+            }
+        }
+        impl FromRawArg<$raw> for f32 {
+            fn from_raw_arg(raw: $raw) -> Self {
+                f32::from_bits(raw as u32)
+            }
+        }
+        impl FromRawArg<$raw> for f64 {
+            fn from_raw_arg(raw: $raw) -> Self {
+                f64::from_bits(raw as $unsigned as u64)
+            }
+        }
+    };
+}
+impl_from_raw_arg!(i32 as u32: i8, u8, i16, u16, i32, u32, i64, u64);
+impl_from_raw_arg!(i64 as u64: i8, u8, i16, u16, i32, u32, i64, u64);
+impl_from_raw_arg!(DefaultErrnoType as u16: i8, u8, i16, u16, i32, u32, i64, u64);
+impl_from_raw_arg!(DefaultOutType as u64: i8, u8, i16, u16, i32, u32, i64, u64);
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct AbiArg<'a> {
     pub name: &'a str,
     /// The size of the argument in bytes
     pub size: ArgSize,
+    pub kind: ArgKind<'a>,
+}
+impl AbiArg<'_> {
+    /// The wasm value type of the argument, i.e., `i64` for arguments wider than 4 bytes.
+    pub const fn val_type(&self) -> ValType {
+        if self.size > core::mem::size_of::<i32>() {
+            ValType::I64
+        } else {
+            ValType::I32
+        }
+    }
+    pub const fn is_pointer(&self) -> bool {
+        matches!(self.kind, ArgKind::Pointer { .. } | ArgKind::Out { .. })
+    }
+    /// Whether the argument points to where the callee writes a result.
+    pub const fn is_out(&self) -> bool {
+        matches!(self.kind, ArgKind::Out { .. })
+    }
 }
 
 /// Define an [`AbiArg`].
+///
+/// The [`ArgKind`] of the argument can be specified after an `@`,
+/// e.g., `fd: u32 @ ArgKind::Fd`, or is inferred from its type by [`AbiArgType`].
 #[macro_export]
 macro_rules! desc_abi_arg {
+    ($arg_name:ident : $arg_type:tt @ $kind:expr) => {{
+        $crate::AbiArg {
+            name: stringify!($arg_name),
+            size: core::mem::size_of::<$arg_type>(),
+            kind: $kind,
+        }
+    }};
     ($arg_name:ident [ $arg_size:expr ]) => {{
         const ARG_SIZE: $crate::ArgSize = $arg_size;
         $crate::AbiArg {
             name: stringify!($arg_name),
             size: ARG_SIZE,
+            kind: $crate::ArgKind::Int { signed: false },
         }
     }};
     ($arg_name:ident : $arg_type:ty) => {{
         $crate::AbiArg {
             name: stringify!($arg_name),
             size: core::mem::size_of::<$arg_type>(),
+            kind: <$arg_type as $crate::AbiArgType>::KIND,
         }
     }};
     ($arg_name:ident) => {{
         $crate::AbiArg {
             name: stringify!($arg_name),
             size: core::mem::size_of::<$crate::DefaultAbiArgType>(),
+            kind: <$crate::DefaultAbiArgType as $crate::AbiArgType>::KIND,
         }
     }};
 }
@@ -44,6 +189,46 @@ impl<'a, const ARG_NUM: usize> WasiAbiDescriptor<'a, ARG_NUM> {
     pub const fn ret_val_size() -> usize {
         core::mem::size_of::<i32>()
     }
+    /// Returns the index of the argument named `name`.
+    pub const fn arg_index(&self, name: &str) -> Option<usize> {
+        let mut i = 0;
+        while i < ARG_NUM {
+            if str_eq(self.args[i].name, name) {
+                return Some(i);
+            }
+            i += 1;
+        }
+        None
+    }
+    /// Returns the argument named `name`.
+    pub const fn arg(&self, name: &str) -> Option<&AbiArg<'a>> {
+        match self.arg_index(name) {
+            Some(i) => Some(&self.args[i]),
+            None => None,
+        }
+    }
+    /// The wasm value types of the arguments, i.e., the params of the core signature.
+    pub const fn val_types(&self) -> [ValType; ARG_NUM] {
+        let mut val_types = [ValType::I32; ARG_NUM];
+        let mut i = 0;
+        while i < ARG_NUM {
+            val_types[i] = self.args[i].val_type();
+            i += 1;
+        }
+        val_types
+    }
+    /// The number of [`ArgKind::Out`] arguments, whose values are seen after the call.
+    pub const fn out_count(&self) -> usize {
+        let mut count = 0;
+        let mut i = 0;
+        while i < ARG_NUM {
+            if self.args[i].is_out() {
+                count += 1;
+            }
+            i += 1;
+        }
+        count
+    }
     pub fn args_are_distinct(&self) -> bool {
         if self.args.is_empty() {
             return true;
@@ -54,6 +239,119 @@ impl<'a, const ARG_NUM: usize> WasiAbiDescriptor<'a, ARG_NUM> {
     }
 }
 
+/// Parameters seen after a call to an ABI taking `Self` as arguments, i.e., the
+/// arguments, then the returned errno, then the values behind the `OUTS`
+/// [`ArgKind::Out`] arguments.
+pub trait PostCallParams<const OUTS: usize> {
+    type Output;
+}
+macro_rules! impl_post_call_params {
+    ($($P:ident),*) => {
+        impl<$($P),*> PostCallParams<0> for ($($P,)*) {
+            type Output = ($($P,)* DefaultErrnoType,);
+        }
+        impl<$($P),*> PostCallParams<1> for ($($P,)*) {
+            type Output = ($($P,)* DefaultErrnoType, DefaultOutType);
+        }
+        impl<$($P),*> PostCallParams<2> for ($($P,)*) {
+            type Output = ($($P,)* DefaultErrnoType, DefaultOutType, DefaultOutType);
+        }
+        impl<$($P),*> PostCallParams<3> for ($($P,)*) {
+            type Output = ($($P,)* DefaultErrnoType, DefaultOutType, DefaultOutType, DefaultOutType);
+        }
+    };
+}
+impl_post_call_params!();
+impl_post_call_params!(P0);
+impl_post_call_params!(P0, P1);
+impl_post_call_params!(P0, P1, P2);
+impl_post_call_params!(P0, P1, P2, P3);
+impl_post_call_params!(P0, P1, P2, P3, P4);
+impl_post_call_params!(P0, P1, P2, P3, P4, P5);
+impl_post_call_params!(P0, P1, P2, P3, P4, P5, P6);
+impl_post_call_params!(P0, P1, P2, P3, P4, P5, P6, P7);
+impl_post_call_params!(P0, P1, P2, P3, P4, P5, P6, P7, P8);
+impl_post_call_params!(P0, P1, P2, P3, P4, P5, P6, P7, P8, P9);
+
+/// A [`WasiAbiDescriptor`] with its number of arguments erased,
+/// so that descriptors of different ABIs can be held in one collection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ErasedDescriptor<'a> {
+    /// The module which the ABI is imported from, e.g., `wasi_snapshot_preview1`.
+    pub module: &'a str,
+    pub name: &'a str,
+    pub args: &'a [AbiArg<'a>],
+}
+impl ErasedDescriptor<'_> {
+    /// Whether this is the descriptor of the ABI `name` imported from `module`.
+    pub const fn is(&self, module: &str, name: &str) -> bool {
+        str_eq(self.module, module) && str_eq(self.name, name)
+    }
+    /// Whether `params` are the wasm value types of the arguments, i.e., the params of the core signature.
+    pub fn has_signature(&self, params: &[ValType]) -> bool {
+        self.args.len() == params.len()
+            && self
+                .args
+                .iter()
+                .zip(params)
+                .all(|(arg, val_type)| arg.val_type() == *val_type)
+    }
+}
+
+/// Erases the number of arguments of a [`WasiAbiDescriptor`] constant,
+/// e.g., `erase_desc!("wasi_snapshot_preview1", fd_write)`.
+#[macro_export]
+macro_rules! erase_desc {
+    ($module:expr, $desc:path) => {
+        $crate::ErasedDescriptor {
+            module: $module,
+            name: $desc.name,
+            args: &$desc.args,
+        }
+    };
+}
+
+/// Common accessors of [`WasiAbiDescriptor`]s and [`ErasedDescriptor`]s.
+pub trait AbiDescriptor {
+    fn name(&self) -> &str;
+    fn args(&self) -> &[AbiArg<'_>];
+    /// Returns the index of the argument named `name`.
+    fn arg_index(&self, name: &str) -> Option<usize> {
+        self.args().iter().position(|arg| arg.name == name)
+    }
+}
+impl<const ARG_NUM: usize> AbiDescriptor for WasiAbiDescriptor<'_, ARG_NUM> {
+    fn name(&self) -> &str {
+        self.name
+    }
+    fn args(&self) -> &[AbiArg<'_>] {
+        &self.args
+    }
+}
+impl AbiDescriptor for ErasedDescriptor<'_> {
+    fn name(&self) -> &str {
+        self.name
+    }
+    fn args(&self) -> &[AbiArg<'_>] {
+        self.args
+    }
+}
+
+const fn str_eq(a: &str, b: &str) -> bool {
+    let (a, b) = (a.as_bytes(), b.as_bytes());
+    if a.len() != b.len() {
+        return false;
+    }
+    let mut i = 0;
+    while i < a.len() {
+        if a[i] != b[i] {
+            return false;
+        }
+        i += 1;
+    }
+    true
+}
+
 #[doc(hidden)]
 #[macro_export]
 macro_rules! _desc_abi_arg_list {
@@ -70,6 +368,10 @@ macro_rules! _desc_abi_arg_list {
     (@accum ($arg_name:ident, $($tail:tt)*) -> ($($body:tt)*)) => {
         $crate::_desc_abi_arg_list!(@accum ($($tail)*) -> ($($body)* $crate::desc_abi_arg!($arg_name),))
     };
+    // arg: type @ kind ...
+    (@accum ($arg_name:ident:$arg_type:tt @ $kind:expr $(, $($tail:tt)*)?) -> ($($body:tt)*)) => {
+        $crate::_desc_abi_arg_list!(@accum ($($($tail)*)?) -> ($($body)* $crate::desc_abi_arg!($arg_name:$arg_type @ $kind),))
+    };
     // arg: type ...
     (@accum ($arg_name:ident:$arg_type:tt $($tail:tt)*) -> ($($body:tt)*)) => {
         $crate::_desc_abi_arg_list!(@accum ($($tail)*) -> ($($body)* $crate::desc_abi_arg!($arg_name:$arg_type),))
@@ -85,6 +387,7 @@ macro_rules! _desc_abi_arg_list {
 macro_rules! __count_idents {
     () => { 0usize };
     (, $($remain:tt)*) => { $crate::__count_idents!($($remain)*) }; // drop heading ','s
+    (@ $kind:expr $(, $($remain:tt)*)?) => { $crate::__count_idents!($($($remain)*)?) }; // drop kinds
     ($head:ident) => { 1usize };
     ($head:ident, $($tail:tt)*) => { 1usize + $crate::__count_idents!($($tail)*) };
     ($head:ident : $head_ty:tt $($tail:tt)*) => { 1usize + $crate::__count_idents!($($tail)*) };
@@ -95,7 +398,7 @@ macro_rules! __count_idents {
 #[macro_export]
 macro_rules! desc_wasi_abi {
     ($wasi_name:ident) => {{
-        WasiAbiDescriptor::<0> {
+        $crate::WasiAbiDescriptor::<0> {
             name: stringify!($wasi_name),
             args: [],
         }
@@ -106,4 +409,11 @@ macro_rules! desc_wasi_abi {
             args: $crate::_desc_abi_arg_list!(@accum ($($arg)*) -> ()),
         }
     }};
+    // ABIs whose names are not Rust identifiers, e.g., `"[method]descriptor.read"`.
+    ($wasi_name:literal ( $($arg:tt)* ) ) => {{
+        $crate::WasiAbiDescriptor::<{$crate::__count_idents!($($arg)*)}> {
+            name: $wasi_name,
+            args: $crate::_desc_abi_arg_list!(@accum ($($arg)*) -> ()),
+        }
+    }};
 }
@@ -1,4 +1,6 @@
-use wasi_descriptor::{desc_abi_arg, desc_wasi_abi, AbiArg, DefaultAbiArgType, WasiAbiDescriptor};
+use wasi_descriptor::{
+    desc_abi_arg, desc_wasi_abi, AbiArg, DefaultAbiArgType, FromRawArg, WasiAbiDescriptor,
+};
 
 #[test]
 fn abi_arg_from_macro() {
@@ -61,3 +63,109 @@ fn distinct_arg_names() {
     let wasi_abi = desc_wasi_abi!(wasi_abi(arg0, arg1, arg0));
     assert!(!wasi_abi.args_are_distinct());
 }
+
+#[test]
+fn arg_kinds() {
+    use wasi_descriptor::{ArgKind, ValType};
+
+    const FLAGS: ArgKind = ArgKind::Flags(&["READ", "WRITE"]);
+    const F: WasiAbiDescriptor<5> = desc_wasi_abi!(f(
+        fd: u32 @ ArgKind::Fd,
+        buf: u32 @ ArgKind::Pointer { pointee: "u8", len_arg: Some("len") },
+        len: u32,
+        flags: u16 @ FLAGS,
+        offset: i64,
+    ));
+    assert_eq!(F.args[0].kind, ArgKind::Fd);
+    assert!(F.args[1].is_pointer());
+    assert_eq!(F.args[2].kind, ArgKind::Int { signed: false });
+    assert_eq!(F.args[3].kind, FLAGS);
+    assert_eq!(F.args[3].size, size_of::<u16>());
+    assert_eq!(F.args[4].kind, ArgKind::Int { signed: true });
+    assert_eq!(C.args[1].kind, ArgKind::Int { signed: false });
+
+    const LEN: Option<usize> = F.arg_index("len");
+    assert_eq!(LEN, Some(2));
+    assert_eq!(F.arg_index("length"), None);
+    assert_eq!(
+        F.val_types(),
+        [
+            ValType::I32,
+            ValType::I32,
+            ValType::I32,
+            ValType::I32,
+            ValType::I64
+        ]
+    );
+}
+
+#[test]
+fn erased_descriptors() {
+    use wasi_descriptor::{erase_desc, AbiDescriptor, ErasedDescriptor};
+
+    const DESCRIPTORS: [ErasedDescriptor; 3] = [
+        erase_desc!("env", A),
+        erase_desc!("env", C),
+        erase_desc!("env", E),
+    ];
+    let descriptors: [&dyn AbiDescriptor; 2] = [&DESCRIPTORS[1], &C];
+    for desc in descriptors {
+        assert_eq!(desc.name(), "clock_time_get");
+        assert_eq!(desc.args().len(), 2);
+        assert_eq!(desc.arg_index("precision"), Some(1));
+    }
+    assert!(DESCRIPTORS[2].is("env", "wasi"));
+    assert!(!DESCRIPTORS[2].is("wasi", "wasi"));
+    assert_eq!(DESCRIPTORS[2].args, E.args);
+
+    use wasi_descriptor::ValType;
+    assert!(DESCRIPTORS[1].has_signature(&C.val_types()));
+    assert!(!DESCRIPTORS[1].has_signature(&[ValType::I32]));
+}
+
+#[test]
+fn out_args() {
+    use wasi_descriptor::{ArgKind, DefaultErrnoType, DefaultOutType, PostCallParams};
+
+    const W: WasiAbiDescriptor<3> = desc_wasi_abi!(w(
+        fd: u32 @ ArgKind::Fd,
+        buf: u32 @ ArgKind::Pointer { pointee: "u8", len_arg: None },
+        nwritten_ptr: u32 @ ArgKind::Out { pointee: "size" },
+    ));
+    assert!(W.args[2].is_out());
+    assert!(W.args[2].is_pointer());
+    assert!(!W.args[1].is_out());
+    const OUTS: usize = W.out_count();
+    assert_eq!(OUTS, 1);
+
+    let _: <(i32, i32, i32) as PostCallParams<{ W.out_count() }>>::Output =
+        (0, 0, 0, 0 as DefaultErrnoType, 0 as DefaultOutType);
+}
+
+#[test]
+fn raw_args() {
+    use wasi_descriptor::{FromRawArg, RawArgType};
+
+    let _: RawArgType<1> = 0i32;
+    let _: RawArgType<4> = 0i32;
+    let _: RawArgType<8> = 0i64;
+    assert_eq!(u32::from_raw_arg(-1i32), u32::MAX);
+    assert_eq!(u64::from_raw_arg(-1i64), u64::MAX);
+    assert_eq!(u8::from_raw_arg(0x1ffi32), 0xff);
+    assert!(bool::from_raw_arg(2i32));
+    assert_eq!(f64::from_raw_arg(1.5f64.to_bits() as i64), 1.5);
+    assert_eq!(i32::from_raw_arg(u16::MAX), 0xffff);
+}
+
+#[test]
+fn raw_args_keep_bits() {
+    let high = 0x8000_0000_u32 as i32;
+    assert_eq!(u64::from_raw_arg(high), 0x8000_0000);
+    assert_eq!(i64::from_raw_arg(high), 0x8000_0000);
+    assert_eq!(u32::from_raw_arg(high), 0x8000_0000);
+    assert_eq!(i32::from_raw_arg(high), i32::MIN);
+    assert_eq!(u64::from_raw_arg(-1_i32), 0xffff_ffff);
+    assert_eq!(u8::from_raw_arg(0x1ff_i32), 0xff);
+    assert_eq!(u64::from_raw_arg(-1_i64), u64::MAX);
+    assert_eq!(f64::from_raw_arg(high).to_bits(), 0x8000_0000);
+}